@@ -16,8 +16,45 @@ fn bench_selector() -> Result<(), BoxDynError> {
 	list.find("li:nth-child(2n)");
 	Ok(())
 }
+
+// a large page with nested elements, attributes and texts
+fn large_page() -> String {
+	let item = r##"<div class="item" data-id="1"><a href="/item/1" title="item">item <b>1</b></a><p>the description &amp; more</p><ul><li>a</li><li class="on">b</li><li>c</li></ul></div>"##;
+	format!(
+		"<!doctype html><html><head><title>large</title></head><body>{}</body></html>",
+		item.repeat(5000)
+	)
+}
+
 fn criterion_benchmark(c: &mut Criterion) {
 	c.bench_function("bench-selector", |b| b.iter(bench_selector));
+	// the document backed by the `Rc<RefCell>` nodes
+	let html = large_page();
+	let root = Vis::load(&html).unwrap();
+	c.bench_function("find-all", |b| b.iter(|| root.find("*").length()));
+	c.bench_function("find-selector", |b| {
+		b.iter(|| root.find("div.item > ul li:nth-child(2n)").length())
+	});
+	c.bench_function("parents-attr", |b| {
+		b.iter(|| root.find("b").closest("[data-id]").length())
+	});
+	c.bench_function("load-and-drop", |b| b.iter(|| Vis::load(&html).unwrap()));
+	// the document backed by the arena
+	#[cfg(feature = "sync")]
+	{
+		let doc = Vis::load_sync(&html).unwrap();
+		let root = doc.elements();
+		c.bench_function("sync-find-all", |b| b.iter(|| root.find("*").length()));
+		c.bench_function("sync-find-selector", |b| {
+			b.iter(|| root.find("div.item > ul li:nth-child(2n)").length())
+		});
+		c.bench_function("sync-parents-attr", |b| {
+			b.iter(|| root.find("b").closest("[data-id]").length())
+		});
+		c.bench_function("sync-load-and-drop", |b| {
+			b.iter(|| Vis::load_sync(&html).unwrap())
+		});
+	}
 }
 
 criterion_group!(benches, criterion_benchmark);
@@ -0,0 +1,93 @@
+use rphtml::parser::{Doc, Node, RefNode};
+use std::cell::RefCell;
+use std::collections::HashMap;
+use std::rc::{Rc, Weak};
+
+type RefDoc = Rc<RefCell<Doc>>;
+
+// a document loaded in the nested mode, its content documents are parsed once and kept
+struct NestedDoc {
+	// keep the root allocation, so the address used as the key will not be reused
+	root: Weak<RefCell<Node>>,
+	// the content documents keyed by the address of the element
+	contents: HashMap<usize, (Weak<RefCell<Node>>, RefDoc)>,
+}
+
+// the `Rc` nodes can't be shared across threads, so the nested documents are saved per thread
+thread_local! {
+	static NESTED_DOCS: RefCell<HashMap<usize, NestedDoc>> = RefCell::new(HashMap::new());
+}
+
+fn get_key(node: &RefNode) -> usize {
+	Rc::as_ptr(node) as usize
+}
+
+// remove the dropped documents and elements, so their allocations can be freed
+fn purge(docs: &mut HashMap<usize, NestedDoc>) {
+	docs.retain(|_, doc| {
+		if doc.root.strong_count() == 0 {
+			return false;
+		}
+		doc.contents.retain(|_, (node, _)| node.strong_count() > 0);
+		true
+	});
+}
+
+// set the document to the nested mode
+pub(crate) fn enable(root: &RefNode) {
+	NESTED_DOCS.with(|docs| {
+		let mut docs = docs.borrow_mut();
+		purge(&mut docs);
+		docs.entry(get_key(root)).or_insert_with(|| NestedDoc {
+			root: Rc::downgrade(root),
+			contents: HashMap::new(),
+		});
+	});
+}
+
+// check if the document is in the nested mode
+pub(crate) fn is_nested(root: &RefNode) -> bool {
+	NESTED_DOCS.with(|docs| docs.borrow().contains_key(&get_key(root)))
+}
+
+// get the kept content document of the element
+pub(crate) fn get(root: &RefNode, node: &RefNode) -> Option<RefDoc> {
+	NESTED_DOCS.with(|docs| {
+		let mut docs = docs.borrow_mut();
+		purge(&mut docs);
+		docs
+			.get(&get_key(root))
+			.and_then(|doc| doc.contents.get(&get_key(node)))
+			.map(|(_, content)| Rc::clone(content))
+	})
+}
+
+// keep the content document of the element
+pub(crate) fn insert(root: &RefNode, node: &RefNode, content: RefDoc) {
+	NESTED_DOCS.with(|docs| {
+		if let Some(doc) = docs.borrow_mut().get_mut(&get_key(root)) {
+			doc
+				.contents
+				.insert(get_key(node), (Rc::downgrade(node), content));
+		}
+	});
+}
+
+// remove the content documents of the changed node and its ancestors, the last node is the root
+pub(crate) fn invalidate(nodes: &[RefNode]) {
+	NESTED_DOCS.with(|docs| {
+		let mut docs = docs.borrow_mut();
+		purge(&mut docs);
+		let root = nodes.last().expect("The nodes must contain the root");
+		if let Some(doc) = docs.get_mut(&get_key(root)) {
+			for node in nodes {
+				doc.contents.remove(&get_key(node));
+			}
+		}
+	});
+}
+
+// check if any document is in the nested mode
+pub(crate) fn has_nested() -> bool {
+	NESTED_DOCS.with(|docs| !docs.borrow().is_empty())
+}
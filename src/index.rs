@@ -0,0 +1,286 @@
+use crate::content;
+use crate::mesdoc::interface::{BoxDynElement, DocumentStats, Elements};
+use crate::mesdoc::utils::get_class_list;
+use rphtml::parser::{Node, NodeType, RefNode};
+use std::cell::RefCell;
+use std::collections::{HashMap, HashSet};
+use std::rc::{Rc, Weak};
+
+type NodeMap = HashMap<String, Vec<Weak<RefCell<Node>>>>;
+
+// the keys of the indexes
+#[derive(Clone, Copy)]
+pub(crate) enum IndexKey<'a> {
+	// lowercase tag name
+	Tag(&'a str),
+	// class name token
+	Class(&'a str),
+	// lowercase attribute name
+	Attr(&'a str),
+}
+
+// the indexes of a document, each index is built when it's first used
+struct DocIndex {
+	// keep the root allocation, so the address used as the key will not be reused
+	root: Weak<RefCell<Node>>,
+	tags: Option<NodeMap>,
+	classes: Option<NodeMap>,
+	attrs: Option<NodeMap>,
+	stats: Stats,
+}
+
+// the statistics are rebuilt after the document is queried so many times without changes
+const STATS_REBUILD_QUERIES: usize = 16;
+
+// the statistics of a document
+enum Stats {
+	// not used yet, they are counted when first used
+	Unbuilt,
+	Built(Rc<DocumentStats>),
+	// the document has been changed, count the queries since the last change
+	Stale(usize),
+}
+
+// the `Rc` nodes can't be shared across threads, so the indexes are saved per thread
+thread_local! {
+	static INDEXES: RefCell<HashMap<usize, DocIndex>> = RefCell::new(HashMap::new());
+}
+
+fn get_key(root: &RefNode) -> usize {
+	Rc::as_ptr(root) as usize
+}
+
+// the lowercase tag name
+fn tag_keys(node: &Node) -> Vec<String> {
+	match &node.meta {
+		Some(meta) => vec![meta
+			.borrow()
+			.name
+			.iter()
+			.map(|ch| ch.to_ascii_lowercase())
+			.collect()],
+		None => vec![],
+	}
+}
+
+// the unique class names
+fn class_keys(node: &Node) -> Vec<String> {
+	let mut keys = vec![];
+	if let Some(meta) = &node.meta {
+		let meta = meta.borrow();
+		if let Some(&index) = meta.lc_name_map.get("class") {
+			if let Some(value) = &meta.attrs[index].value {
+				let mut uniques = HashSet::new();
+				for class_name in get_class_list(&value.content.iter().collect::<String>()) {
+					let class_name = class_name.iter().collect::<String>();
+					if uniques.insert(class_name.clone()) {
+						keys.push(class_name);
+					}
+				}
+			}
+		}
+	}
+	keys
+}
+
+// the lowercase attribute names, the same as the names used by `get_attribute`
+fn attr_keys(node: &Node) -> Vec<String> {
+	match &node.meta {
+		Some(meta) => meta.borrow().lc_name_map.keys().cloned().collect(),
+		None => vec![],
+	}
+}
+
+// build the index of the tag nodes in document order
+fn build(root: &RefNode, get_keys: fn(&Node) -> Vec<String>) -> NodeMap {
+	fn loop_handle(node: &RefNode, map: &mut NodeMap, get_keys: fn(&Node) -> Vec<String>) {
+		if let Some(childs) = &node.borrow().childs {
+			for child in childs {
+				if child.borrow().node_type == NodeType::Tag {
+					for key in get_keys(&child.borrow()) {
+						map.entry(key).or_default().push(Rc::downgrade(child));
+					}
+					loop_handle(child, map, get_keys);
+				}
+			}
+		}
+	}
+	let mut map = HashMap::new();
+	loop_handle(root, &mut map, get_keys);
+	map
+}
+
+// count the tag nodes and their descendants by the keys, sum the depths and count the parents
+fn build_stats(root: &RefNode) -> DocumentStats {
+	// return the count of the descendant tag nodes
+	fn loop_handle(node: &RefNode, stats: &mut DocumentStats, depth: usize) -> usize {
+		let mut descendants = 0;
+		if let Some(childs) = &node.borrow().childs {
+			for child in childs {
+				if child.borrow().node_type == NodeType::Tag {
+					if descendants == 0 {
+						stats.parents += 1;
+					}
+					stats.elements += 1;
+					stats.depths += depth;
+					let child_descendants = loop_handle(child, stats, depth + 1);
+					descendants += child_descendants + 1;
+					let child_node = child.borrow();
+					for (keys, map) in [
+						(tag_keys(&child_node), &mut stats.tags),
+						(class_keys(&child_node), &mut stats.classes),
+						(attr_keys(&child_node), &mut stats.attrs),
+					] {
+						for key in keys {
+							let item = map.entry(key).or_default();
+							item.count += 1;
+							item.descendants += child_descendants;
+						}
+					}
+				}
+			}
+		}
+		descendants
+	}
+	let mut stats = DocumentStats::default();
+	loop_handle(root, &mut stats, 1);
+	stats
+}
+
+// remove the indexes of the dropped documents, the weak pointers keep the allocations of all the indexed nodes
+fn purge(indexes: &mut HashMap<usize, DocIndex>) {
+	indexes.retain(|_, index| index.root.strong_count() > 0);
+}
+
+// get the index of the document, add an empty one if it's not exist
+fn with_index<T>(root: &RefNode, handle: impl FnOnce(&mut DocIndex) -> T) -> T {
+	INDEXES.with(|indexes| {
+		let mut indexes = indexes.borrow_mut();
+		purge(&mut indexes);
+		let index = indexes.entry(get_key(root)).or_insert_with(|| DocIndex {
+			root: Rc::downgrade(root),
+			tags: None,
+			classes: None,
+			attrs: None,
+			stats: Stats::Unbuilt,
+		});
+		handle(index)
+	})
+}
+
+// get the statistics of the document, they are counted when first used
+// after the document is changed, they are not rebuilt until the document is queried `STATS_REBUILD_QUERIES` times without changes,
+// so the queries between the changes don't walk through the whole document
+pub(crate) fn get_stats(root: &RefNode) -> Option<Rc<DocumentStats>> {
+	with_index(root, |index| {
+		match index.stats {
+			Stats::Built(ref stats) => return Some(Rc::clone(stats)),
+			Stats::Stale(queries) if queries + 1 < STATS_REBUILD_QUERIES => {
+				index.stats = Stats::Stale(queries + 1);
+				return None;
+			}
+			_ => {}
+		}
+		let stats = Rc::new(build_stats(root));
+		index.stats = Stats::Built(Rc::clone(&stats));
+		Some(stats)
+	})
+}
+
+// get the elements in the document by the index, the index will be built if it's not exist
+pub(crate) fn get_elements<'b>(root: &RefNode, key: IndexKey) -> Elements<'b> {
+	with_index(root, |index| {
+		let (map, name, get_keys): (_, _, fn(&Node) -> Vec<String>) = match key {
+			IndexKey::Tag(name) => (&mut index.tags, name, tag_keys),
+			IndexKey::Class(name) => (&mut index.classes, name, class_keys),
+			IndexKey::Attr(name) => (&mut index.attrs, name, attr_keys),
+		};
+		let map = map.get_or_insert_with(|| build(root, get_keys));
+		let nodes = map
+			.get(name)
+			.map(|nodes| nodes.as_slice())
+			.unwrap_or_default();
+		Elements::with_nodes(
+			nodes
+				.iter()
+				.filter_map(|node| node.upgrade())
+				.map(|node| Box::new(node) as BoxDynElement)
+				.collect(),
+		)
+	})
+}
+
+// the node and its ancestors, the last one is the root
+// the inserted nodes may still keep the root of the original document, so find the root by the parents
+pub(crate) fn get_ancestors(node: &RefNode) -> Vec<RefNode> {
+	let mut nodes = vec![Rc::clone(node)];
+	loop {
+		let parent = nodes.last().and_then(|node| {
+			node
+				.borrow()
+				.parent
+				.as_ref()
+				.and_then(|parent| parent.upgrade())
+		});
+		match parent {
+			Some(parent) => nodes.push(parent),
+			None => break,
+		}
+	}
+	nodes
+}
+
+// remove the indexes of the document the node is in, and the kept content documents of the node and its ancestors
+// must be called before the node is changed
+pub(crate) fn invalidate(node: &RefNode) {
+	let has_indexes = INDEXES.with(|indexes| !indexes.borrow().is_empty());
+	if !has_indexes && !content::has_nested() {
+		return;
+	}
+	let nodes = get_ancestors(node);
+	let root = nodes
+		.last()
+		.expect("The ancestors must contain the node itself");
+	INDEXES.with(|indexes| {
+		let mut indexes = indexes.borrow_mut();
+		if let Some(index) = indexes.get_mut(&get_key(root)) {
+			index.tags = None;
+			index.classes = None;
+			index.attrs = None;
+			// keep the statistics stale, so they're not rebuilt by the next query
+			index.stats = Stats::Stale(0);
+		}
+		purge(&mut indexes);
+	});
+	content::invalidate(&nodes);
+}
+
+#[cfg(test)]
+mod tests {
+	use super::INDEXES;
+	use crate::Vis;
+
+	fn indexed_docs() -> usize {
+		INDEXES.with(|indexes| indexes.borrow().len())
+	}
+
+	#[test]
+	fn test_purge_dropped_documents() {
+		let root = Vis::load("<div><p class='a'></p></div>").unwrap();
+		let dropped = Vis::load("<ul><li class='a'></li></ul>").unwrap();
+		assert_eq!(dropped.find(".a").length(), 1);
+		assert_eq!(root.find(".a").length(), 1);
+		assert_eq!(indexed_docs(), 2);
+		drop(dropped);
+		// the indexes of the dropped document are removed on the next lookup
+		assert_eq!(root.find("p").length(), 1);
+		assert_eq!(indexed_docs(), 1);
+		// and on the invalidation
+		let dropped = Vis::load("<ul><li class='a'></li></ul>").unwrap();
+		assert_eq!(dropped.find(".a").length(), 1);
+		assert_eq!(indexed_docs(), 2);
+		drop(dropped);
+		root.find("p").set_attr("class", Some("b"));
+		assert_eq!(indexed_docs(), 1);
+	}
+}
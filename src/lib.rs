@@ -11,6 +11,8 @@
 //! - Well tested: the unit tests have covered most cases, but if you meet any bugs or questions, welcome to submit issues or PR to us.
 #[macro_use]
 mod macros;
+mod content;
+mod index;
 mod mesdoc;
 // feature="text"
 cfg_feat_text! {
@@ -20,16 +22,21 @@ cfg_feat_text! {
 cfg_feat_insertion! {
 	use mesdoc::interface::InsertPosition;
 }
+// feature="sync"
+cfg_feat_sync! {
+	mod sync;
+	pub use sync::{SyncDocument, SyncElements};
+}
 use mesdoc::interface::{
-	BoxDynElement, BoxDynNode, BoxDynText, BoxDynUncareNode, Elements, IDocumentTrait, IElementTrait,
-	IErrorHandle, IFormValue, INodeTrait, ITextTrait, IUncareNodeTrait, MaybeDoc, MaybeElement,
+	BoxDynElement, BoxDynNode, BoxDynText, BoxDynUncareNode, DocumentStats, Elements, IDocumentTrait,
+	IElementTrait, IErrorHandle, INodeTrait, ITextTrait, IUncareNodeTrait, MaybeDoc, MaybeElement,
 };
 
-use mesdoc::utils::is_equal_chars;
+use index::IndexKey;
 use mesdoc::{error::Error as IError, utils::retain_by_index};
 use rphtml::{
 	config::RenderOptions,
-	entity::{encode, encode_chars, EncodeType, EntitySet},
+	entity::{decode_chars, encode, encode_chars, EncodeType, EntitySet},
 	parser::{
 		allow_insert, is_content_tag, Attr, AttrData, Doc, DocHolder, NameCase, Node, NodeType, RefNode,
 	},
@@ -42,20 +49,51 @@ pub mod types {
 	cfg_feat_text! {
 		pub use crate::mesdoc::interface::Texts;
 	}
-	pub use crate::mesdoc::error::BoxDynError;
+	pub use crate::mesdoc::constants::{
+		NAMESPACE_HTML, NAMESPACE_MATHML, NAMESPACE_SVG, NAMESPACE_XLINK, NAMESPACE_XML,
+		NAMESPACE_XMLNS,
+	};
+	pub use crate::mesdoc::error::{BoxDynError, Error, SelectorErrorKind};
 	pub use crate::mesdoc::interface::{
-		BoxDynElement, BoxDynNode, BoxDynText, Elements, IAttrValue, IDocumentTrait, IEnumTyped,
-		IFormValue, INodeType,
+		BoxDynElement, BoxDynNode, BoxDynText, DocumentStats, Elements, IAttrValue, IDocumentTrait,
+		IElementTrait, IEnumTyped, IFormValue, INodeTrait, INodeType, SelectorCandidate, StatsItem,
+		UniqueSelectorOptions,
 	};
 	pub use crate::mesdoc::selector::Combinator;
 }
 
+// re export the types for custom selectors
+pub mod selector {
+	pub use crate::mesdoc::constants::{
+		PRIORITY_ALL_SELECTOR, PRIORITY_ATTR_SELECTOR, PRIORITY_CLASS_SELECTOR, PRIORITY_ID_SELECTOR,
+		PRIORITY_NAME_SELECTOR, PRIORITY_PSEUDO_SELECTOR,
+	};
+	pub use crate::mesdoc::selector::ast::{
+		ComplexSelector, CompoundSelector, PseudoArgument, SelectorList, SimpleSelector, Specificity,
+	};
+	pub use crate::mesdoc::selector::pattern::{
+		check_params_return, BoxDynPattern, FromParamsFn, Matched, MatchedData, MatchedQueue, Pattern,
+	};
+	pub use crate::mesdoc::selector::rule::{
+		MatchAllHandle, MatchEstimateHandle, MatchIndexHandle, MatchOneHandle, MatchSpecifiedHandle,
+		Matcher, MatcherFactory,
+	};
+	pub use crate::mesdoc::selector::CompiledSelector;
+	pub use crate::mesdoc::selector::{QueryPlan, QueryStrategy};
+}
+
+// re export the xpath result types
+pub mod xpath {
+	pub use crate::mesdoc::xpath::{XPathNode, XPathValue};
+}
+
 // re export `ParseOptions`
 pub mod html {
 	pub use rphtml::config::ParseOptions;
 }
 
 use crate::html::ParseOptions;
+use crate::selector::{CompiledSelector, FromParamsFn, MatcherFactory, SelectorList};
 use crate::types::{BoxDynError, IAttrValue, IEnumTyped, INodeType};
 /// type implement INodeTrait with Node
 struct Dom;
@@ -114,10 +152,6 @@ impl Dom {
 	}
 }
 
-fn to_static_str(orig: String) -> &'static str {
-	Box::leak(orig.into_boxed_str())
-}
-
 fn reset_next_siblings_index(start_index: usize, childs: &[RefNode]) {
 	for (step, node) in childs.iter().enumerate() {
 		node.borrow_mut().index = start_index + step;
@@ -143,6 +177,32 @@ fn check_if_content_tag(name: &[char]) -> bool {
 	is_content_tag(name, &Some(NameCase::Lower))
 }
 
+// the parse options of the document the node belongs to, so the nested documents are parsed in the same way
+fn get_parse_options(node: &RefNode) -> ParseOptions {
+	let root = node.borrow().root.as_ref().and_then(|root| root.upgrade());
+	let doc = root.and_then(|root| {
+		root
+			.borrow()
+			.document
+			.as_ref()
+			.and_then(|doc| doc.upgrade())
+	});
+	match doc {
+		Some(doc) => {
+			let options = &doc.borrow().parse_options;
+			ParseOptions {
+				case_sensitive_tagname: options.case_sensitive_tagname,
+				allow_attr_key_starts_with_equal_sign: options.allow_attr_key_starts_with_equal_sign,
+				allow_self_closing: options.allow_self_closing,
+				auto_fix_unclosed_tag: options.auto_fix_unclosed_tag,
+				auto_fix_unexpected_endtag: options.auto_fix_unexpected_endtag,
+				auto_fix_unescaped_lt: options.auto_fix_unescaped_lt,
+			}
+		}
+		None => Vis::options(),
+	}
+}
+
 impl INodeTrait for Rc<RefCell<Node>> {
 	/// impl `to_node`
 	fn to_node(self: Box<Self>) -> Box<dyn Any> {
@@ -245,6 +305,7 @@ impl INodeTrait for Rc<RefCell<Node>> {
 		let node_type = self.node_type();
 		match node_type {
 			INodeType::Element => {
+				index::invalidate(self);
 				let no_content_tag = !check_if_content_tag(&self.tag_names());
 				let mut node = self.borrow_mut();
 				if !content.is_empty() {
@@ -286,6 +347,7 @@ impl INodeTrait for Rc<RefCell<Node>> {
 
 	/// impl `set_html`
 	fn set_html(&mut self, content: &str) {
+		index::invalidate(self);
 		let mut is_element = true;
 		let target = match self.node_type() {
 			INodeType::Element => Some(Rc::clone(self)),
@@ -445,101 +507,6 @@ impl IElementTrait for Rc<RefCell<Node>> {
 		};
 		vec![]
 	}
-	/// impl `value`
-	fn value(&self) -> IFormValue {
-		let tag_name = self.tag_names();
-		let input_tag = ['i', 'n', 'p', 'u', 't'];
-		let select_tag = ['s', 'e', 'l', 'e', 'c', 't'];
-		let option_tag = ['o', 'p', 't', 'i', 'o', 'n'];
-		let textarea_tag = ['t', 'e', 'x', 't', 'a', 'r', 'e', 'a'];
-		// if it's an (input)
-		if is_equal_chars(&tag_name, &input_tag) || is_equal_chars(&tag_name, &option_tag) {
-			// input
-			if let Some(IAttrValue::Value(v, _)) = self.get_attribute("value") {
-				return IFormValue::Single(v);
-			}
-			return IFormValue::Single(String::from(""));
-		}
-		// if it's a (select)
-		if is_equal_chars(&tag_name, &select_tag) {
-			let is_multiple = self.has_attribute("multiple");
-			// default value, if no option has "selected"
-			let mut default_value: Option<String> = None;
-			// if select's 'multiple' attribute is setted
-			let mut values: Vec<String> = vec![];
-			// collect option values
-			fn collect_option_values(
-				parent: &RefNode,
-				default_value: &mut Option<String>,
-				values: &mut Vec<String>,
-				option_tag: &[char],
-				is_multiple: bool,
-				level: u8,
-			) {
-				if let Some(childs) = &parent.borrow().childs {
-					for child in childs {
-						if matches!(child.node_type(), INodeType::Element) {
-							let child_tag = child.tag_names();
-							if is_equal_chars(&child_tag, option_tag) {
-								let is_selected = child.has_attribute("selected");
-								if is_selected || (default_value.is_none() && level == 0) {
-									let value = if let Some(IAttrValue::Value(v, _)) = child.get_attribute("value") {
-										v
-									} else {
-										String::from("")
-									};
-									if is_selected {
-										values.push(value);
-										// break if is single select
-										if !is_multiple {
-											break;
-										}
-									} else {
-										*default_value = Some(value);
-									}
-								}
-							} else {
-								collect_option_values(
-									child,
-									default_value,
-									values,
-									option_tag,
-									is_multiple,
-									level + 1,
-								);
-							}
-						}
-					}
-				}
-			}
-			// get the values of option
-			collect_option_values(
-				self,
-				&mut default_value,
-				&mut values,
-				&option_tag,
-				is_multiple,
-				0,
-			);
-			// if is multiple select
-			if is_multiple {
-				return IFormValue::Multiple(values);
-			}
-			// single select, check if has selected option
-			if !values.is_empty() {
-				return IFormValue::Single(values.remove(0));
-			}
-			// no selected option or no option tag
-			return IFormValue::Single(default_value.unwrap_or_else(|| String::from("")));
-		}
-		// if it's an (textarea)
-		if is_equal_chars(&tag_name, &textarea_tag) {
-			// textarea
-			return IFormValue::Single(self.text());
-		}
-		// other element
-		IFormValue::Single(String::from(""))
-	}
 	/// impl `children`
 	fn child_nodes_length(&self) -> usize {
 		self
@@ -598,10 +565,8 @@ impl IElementTrait for Rc<RefCell<Node>> {
 	fn get_attribute(&self, name: &str) -> Option<IAttrValue> {
 		// use lowercase to get attribute: issue: #2
 		let node = &self.borrow();
-		let meta = node
-			.meta
-			.as_ref()
-			.expect("Element node must have a meta field.");
+		// the document root node has no meta, e.g. the selectors begin with ':scope' find from the root
+		let meta = node.meta.as_ref()?;
 		// if has meta, then compare with lowercase
 		let lc_name_map = &meta.borrow().lc_name_map;
 		if !lc_name_map.is_empty() {
@@ -619,8 +584,22 @@ impl IElementTrait for Rc<RefCell<Node>> {
 		None
 	}
 
+	/// impl `get_attribute_names`
+	fn get_attribute_names(&self) -> Vec<String> {
+		if let Some(meta) = &self.borrow().meta {
+			return meta
+				.borrow()
+				.attrs
+				.iter()
+				.filter_map(|attr| attr.key.as_ref().map(|key| key.content.iter().collect()))
+				.collect();
+		}
+		vec![]
+	}
+
 	/// impl `set_attribute`
 	fn set_attribute(&mut self, name: &str, value: Option<&str>) {
+		index::invalidate(self);
 		let mut need_quote = false;
 		let mut quote: char = '"';
 		if let Some(meta) = &self.borrow().meta {
@@ -687,6 +666,7 @@ impl IElementTrait for Rc<RefCell<Node>> {
 
 	/// impl `remove_attribute`
 	fn remove_attribute(&mut self, name: &str) {
+		index::invalidate(self);
 		if let Some(meta) = &self.borrow().meta {
 			let mut find_index: Option<usize> = None;
 			if !meta.borrow().lc_name_map.is_empty() {
@@ -735,12 +715,63 @@ impl IElementTrait for Rc<RefCell<Node>> {
 			.collect::<String>()
 	}
 
+	/// impl `content_document`
+	fn content_document<'b>(&self) -> Option<Elements<'b>> {
+		let content = match self.tag_names().iter().collect::<String>().as_str() {
+			"template" | "noscript" => self.inner_html(),
+			"iframe" => match self.get_attribute("srcdoc")? {
+				IAttrValue::Value(srcdoc, _) => {
+					let srcdoc = srcdoc.chars().collect::<Vec<char>>();
+					decode_chars(&srcdoc).iter().collect()
+				}
+				IAttrValue::True => String::new(),
+			},
+			_ => return None,
+		};
+		// the content documents of the nested mode documents are parsed once
+		let root = index::get_ancestors(self)
+			.pop()
+			.expect("The ancestors must contain the node itself");
+		let is_nested = content::is_nested(&root);
+		if is_nested {
+			if let Some(doc) = content::get(&root, self) {
+				return Some(Document { doc: doc.into() }.elements());
+			}
+		}
+		match Vis::parse_doc_options(&content, get_parse_options(self)) {
+			Ok(doc) => {
+				// the errors are handled by the owner document's handle
+				if let Some(owner) = &self.owner_document() {
+					*doc.doc.borrow().onerror.borrow_mut() = owner.onerror();
+				}
+				if is_nested {
+					let doc_root = doc.doc.get_root_node();
+					let ref_doc = doc_root
+						.borrow()
+						.document
+						.as_ref()
+						.and_then(|doc| doc.upgrade());
+					if let Some(ref_doc) = ref_doc {
+						content::enable(&doc_root);
+						content::insert(&root, self, ref_doc);
+					}
+				}
+				Some(doc.elements())
+			}
+			Err(err) => {
+				Dom::halt(self, "content_document", &err.to_string());
+				None
+			}
+		}
+	}
+
 	// when the feature `destory` or `insertion` is open
 	cfg_feat_mutation! {
 		/// impl `remov_child`
 		fn remove_child(&mut self, ele: BoxDynElement) {
 			if let Some(parent) = &ele.parent() {
 				if self.is(parent) {
+					index::invalidate(self);
 					// is a child
 					if let Some(childs) = self.borrow_mut().childs.as_mut() {
 						let index = ele.index();
@@ -767,6 +798,9 @@ impl IElementTrait for Rc<RefCell<Node>> {
 			let node_type = node.node_type();
 			let specified: Box<dyn Any> = node.cloned().to_node();
 			if let Ok(dom) = specified.downcast::<RefNode>() {
+				// both the documents of the target and the inserted node are changed
+				index::invalidate(self);
+				index::invalidate(&dom);
 				// get the nodes
 				let mut nodes = match node_type {
 					INodeType::DocumentFragement => {
@@ -1005,13 +1039,38 @@ impl IDocumentTrait for Document {
 		None
 	}
 	// source code
-	fn source_code(&self) -> &'static str {
-		to_static_str(self.doc.render(&Default::default()))
+	fn source_code(&self) -> String {
+		self.doc.render(&Default::default())
 	}
 	// get root node, in rphtml is abstract root node
 	fn get_root_node<'b>(&self) -> BoxDynNode<'b> {
 		Box::new(Rc::clone(&self.doc.borrow().root))
 	}
+	// get elements by the lazily built indexes
+	fn get_elements_by_tag_name<'b>(&self, name: &str) -> Option<Elements<'b>> {
+		let name = name.to_ascii_lowercase();
+		Some(index::get_elements(
+			&self.doc.borrow().root,
+			IndexKey::Tag(&name),
+		))
+	}
+	fn get_elements_by_class_name<'b>(&self, class_name: &str) -> Option<Elements<'b>> {
+		Some(index::get_elements(
+			&self.doc.borrow().root,
+			IndexKey::Class(class_name),
+		))
+	}
+	fn get_elements_by_attribute_name<'b>(&self, name: &str) -> Option<Elements<'b>> {
+		let name = name.to_ascii_lowercase();
+		Some(index::get_elements(
+			&self.doc.borrow().root,
+			IndexKey::Attr(&name),
+		))
+	}
+	// the statistics are counted when first used, and `None` for a while after the document is changed
+	fn stats(&self) -> Option<Rc<DocumentStats>> {
+		index::get_stats(&self.doc.borrow().root)
+	}
 	// onerror
 	fn onerror(&self) -> Option<Rc<IErrorHandle>> {
 		(*self.doc.borrow().onerror.borrow())
@@ -1136,8 +1195,147 @@ impl Vis {
 	pub fn load_catch(html: &str, handle: IErrorHandle) -> Elements {
 		Vis::load_options_catch(html, Vis::options(), handle)
 	}
+	/// load the html with options in the nested mode, the content documents of `<template>`, `<noscript>` and `<iframe srcdoc>`
+	/// are parsed once when first used and kept until the element is changed, so the changes of them are kept too
+	///
+	/// The elements found by `>>>` keep their content documents alive only in themselves, the new elements of the chained traversals,
+	/// e.g. `.find("iframe >>> p").parent("")`, don't keep them, so once the element is changed and the content document is no longer cached,
+	/// the traversals lose the ancestors in the content document, the `owner_document()` and the error handle.
+	/// Keep the `>>>` results while using the traversals of them, or find from the `content_document()` of the element.
+	pub fn load_nested_options(
+		html: &str,
+		options: ParseOptions,
+	) -> Result<Elements<'_>, BoxDynError> {
+		let doc = Vis::parse_doc_options(html, options)?;
+		content::enable(&doc.doc.get_root_node());
+		Ok(doc.elements())
+	}
+	/// load the html in the nested mode, see `load_nested_options` for the lifetime of the content documents
+	///
+	/// ```
+	/// use visdom::Vis;
+	/// use visdom::types::BoxDynError;
+	/// fn main()-> Result<(), BoxDynError>{
+	///   let html = r##"<template><p class="intro">hello</p></template>"##;
+	///   let root = Vis::load_nested(html)?;
+	///   root.find("template >>> .intro").set_text("bye");
+	///   assert_eq!(root.find("template >>> .intro").text(), "bye");
+	///   // the changes of the content documents are not synchronized to the elements
+	///   assert_eq!(root.find("template").html(), r#"<p class="intro">hello</p>"#);
+	///   Ok(())
+	/// }
+	/// ```
+	pub fn load_nested(html: &str) -> Result<Elements<'_>, BoxDynError> {
+		Vis::load_nested_options(html, Vis::options())
+	}
+	cfg_feat_sync! {
+		/// load the html with options into a read-only document, it can be shared across threads
+		pub fn load_sync_options(html: &str, options: ParseOptions) -> Result<SyncDocument, BoxDynError> {
+			let doc = Vis::parse_doc_options(html, options)?;
+			let root = Rc::clone(&doc.doc.borrow().root);
+			Ok(SyncDocument::from_root(&root))
+		}
+		/// load the html into a read-only document, it can be shared across threads
+		pub fn load_sync(html: &str) -> Result<SyncDocument, BoxDynError> {
+			Vis::load_sync_options(html, Vis::options())
+		}
+	}
 	/// return an elements collection from an BoxDynElement
 	pub fn dom<'b>(ele: &BoxDynElement) -> Elements<'b> {
 		Elements::with_nodes(vec![ele.cloned()])
 	}
+	/// register a custom pseudo selector, the `context` is a rule with patterns, e.g. `:price-above({spaces}{regexp#(\d+)#}{spaces})`
+	///
+	/// The `factory` receive the matched queue of the `context`'s patterns, and return a `Matcher` with an `one_handle` or an `all_handle`.
+	/// Return an error if the selector name has been used by a built-in or registered pseudo selector.
+	///
+	/// ```
+	/// use visdom::Vis;
+	/// use visdom::types::BoxDynError;
+	/// use visdom::selector::{Matcher, MatchedQueue, PRIORITY_PSEUDO_SELECTOR};
+	/// fn main()-> Result<(), BoxDynError>{
+	///   Vis::register_pseudo(
+	///     ":price-above({spaces}{regexp#(\\d+)#}{spaces})",
+	///     PRIORITY_PSEUDO_SELECTOR,
+	///     Box::new(|data: MatchedQueue| {
+	///       // the params of the regexp pattern are saved in `data`, the key is the group index
+	///       let min = data[2].data.get("1").unwrap().parse::<f64>().unwrap();
+	///       Matcher {
+	///         one_handle: Some(Box::new(move |ele, _| {
+	///           ele.get_attribute("data-price")
+	///             .map(|price| price.to_string().parse::<f64>().unwrap_or(0.0) > min)
+	///             .unwrap_or(false)
+	///         })),
+	///         ..Default::default()
+	///       }
+	///     }),
+	///   )?;
+	///   let html = r##"<ul><li data-price="5">a</li><li data-price="50">b</li></ul>"##;
+	///   let root = Vis::load(html)?;
+	///   assert_eq!(root.find("li:price-above(10)").text(), "b");
+	///   // register the same name again will return an error
+	///   assert!(Vis::register_pseudo(":price-above", 10, Box::new(|_| Matcher::default())).is_err());
+	///   Ok(())
+	/// }
+	/// ```
+	pub fn register_pseudo(
+		context: &'static str,
+		priority: u32,
+		factory: MatcherFactory,
+	) -> Result<(), BoxDynError> {
+		mesdoc::init();
+		mesdoc::selector::rule::add_pseudo_rule(context, priority, factory)?;
+		Ok(())
+	}
+	/// compile the selector, the compiled selector can be reused by the `*_compiled` methods
+	///
+	/// ```
+	/// use visdom::Vis;
+	/// use visdom::types::BoxDynError;
+	/// fn main()-> Result<(), BoxDynError>{
+	///   let selector = Vis::compile("a[href^='https']")?;
+	///   for html in ["<a href='https://a'>a</a>", "<a href='http://b'>b</a>"] {
+	///     let root = Vis::load(html)?;
+	///     let links = root.find_compiled(&selector);
+	///     assert_eq!(links.is_empty(), html.contains("http:"));
+	///   }
+	///   assert!(Vis::compile("a[href").is_err());
+	///   Ok(())
+	/// }
+	/// ```
+	pub fn compile(selector: &str) -> Result<CompiledSelector, BoxDynError> {
+		mesdoc::init();
+		Ok(CompiledSelector::new(selector)?)
+	}
+	/// parse the selector to a syntax tree, it can be used to get the specificity or serialize the selector canonically
+	///
+	/// ```
+	/// use visdom::Vis;
+	/// use visdom::selector::Specificity;
+	/// use visdom::types::BoxDynError;
+	/// fn main()-> Result<(), BoxDynError>{
+	///   let selector = Vis::parse_selector("ul>li.item:not( #first ),a[href^='https' i]")?;
+	///   assert_eq!(selector.to_string(), r#"ul > li.item:not(#first), a[href^="https" i]"#);
+	///   assert_eq!(selector.selectors[0].specificity(), Specificity(1, 1, 2));
+	///   assert_eq!(selector.selectors[1].specificity(), Specificity(0, 1, 1));
+	///   assert!(Vis::parse_selector("a >").is_err());
+	///   Ok(())
+	/// }
+	/// ```
+	pub fn parse_selector(selector: &str) -> Result<SelectorList, BoxDynError> {
+		Ok(selector.parse::<SelectorList>()?)
+	}
+	/// set the capacity of the internal LRU cache for selectors used by string, e.g. `find("a")`, default is `0` that means no cache
+	pub fn selector_cache(capacity: usize) {
+		mesdoc::selector::set_cache_capacity(capacity);
+	}
+	/// register a custom pattern, then it can be used in the context of `register_pseudo` as `{name}`
+	pub fn register_pattern(
+		name: &'static str,
+		from_params: FromParamsFn,
+	) -> Result<(), BoxDynError> {
+		mesdoc::init();
+		mesdoc::selector::pattern::try_add_pattern(name, from_params)?;
+		Ok(())
+	}
 }
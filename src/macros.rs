@@ -24,3 +24,12 @@ macro_rules! cfg_feat_mutation {
     )*
   };
 }
+
+macro_rules! cfg_feat_sync {
+	($($item:item)*) => {
+    $(
+      #[cfg(feature = "sync")]
+      $item
+    )*
+  };
+}
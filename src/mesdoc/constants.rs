@@ -17,9 +17,60 @@ pub const PRIORITY_ID_SELECTOR: u32 = 10000;
 // selector names
 pub const NAME_SELECTOR_ALL: &str = "all";
 pub const NAME_SELECTOR_ATTR: &str = "attr";
+pub const NAME_SELECTOR_ATTR_REGEXP: &str = "attr_regexp";
+pub const NAME_SELECTOR_ATTR_NAMESPACE: &str = "attr_namespace";
 pub const NAME_SELECTOR_NAME: &str = "name";
+pub const NAME_SELECTOR_NAMESPACE_NAME: &str = "namespace_name";
 pub const NAME_SELECTOR_CLASS: &str = "class";
 pub const NAME_SELECTOR_ID: &str = "id";
+pub const NAME_SELECTOR_SCOPE: &str = ":scope";
+// namespaces
+pub const NAMESPACE_HTML: &str = "http://www.w3.org/1999/xhtml";
+pub const NAMESPACE_SVG: &str = "http://www.w3.org/2000/svg";
+pub const NAMESPACE_MATHML: &str = "http://www.w3.org/1998/Math/MathML";
+pub const NAMESPACE_XLINK: &str = "http://www.w3.org/1999/xlink";
+pub const NAMESPACE_XML: &str = "http://www.w3.org/XML/1998/namespace";
+pub const NAMESPACE_XMLNS: &str = "http://www.w3.org/2000/xmlns/";
+// the svg tag names need to keep the camel case, the html parser will lowercase them
+pub const SVG_CAMEL_CASE_TAG_NAMES: [&str; 37] = [
+	"altGlyph",
+	"altGlyphDef",
+	"altGlyphItem",
+	"animateColor",
+	"animateMotion",
+	"animateTransform",
+	"clipPath",
+	"feBlend",
+	"feColorMatrix",
+	"feComponentTransfer",
+	"feComposite",
+	"feConvolveMatrix",
+	"feDiffuseLighting",
+	"feDisplacementMap",
+	"feDistantLight",
+	"feDropShadow",
+	"feFlood",
+	"feFuncA",
+	"feFuncB",
+	"feFuncG",
+	"feFuncR",
+	"feGaussianBlur",
+	"feImage",
+	"feMerge",
+	"feMergeNode",
+	"feMorphology",
+	"feOffset",
+	"fePointLight",
+	"feSpecularLighting",
+	"feSpotLight",
+	"feTile",
+	"feTurbulence",
+	"foreignObject",
+	"glyphRef",
+	"linearGradient",
+	"radialGradient",
+	"textPath",
+];
 // alias selectors
 pub const SELECTOR_ALIAS_NAME_HEADER: (&str, &str) = (":header", "h1,h2,h3,h4,h5,h6");
 pub const SELECTOR_ALIAS_NAME_SUBMIT: (&str, &str) =
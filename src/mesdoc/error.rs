@@ -1,11 +1,50 @@
+use std::fmt;
 use thiserror::Error;
 pub type BoxDynError = Box<dyn std::error::Error + Send + Sync + 'static>;
+
+/// The kind of a selector parse error
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum SelectorErrorKind {
+	// an empty selector in the selector list, e.g. `a,,b` or `:not()`
+	EmptySelector,
+	// a combinator can't be used here, e.g. `a > > b`
+	UnexpectedCombinator,
+	// no selector rule matched the token
+	UnexpectedToken,
+	// the selector ended too early, e.g. `a >` or `:not(a`
+	UnexpectedEnd,
+	// the selector is valid only in some methods, e.g. the deep combinator `>>>` outside `find` and `extract`
+	Unsupported,
+}
+
+impl fmt::Display for SelectorErrorKind {
+	fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+		f.write_str(match self {
+			SelectorErrorKind::EmptySelector => "empty selector",
+			SelectorErrorKind::UnexpectedCombinator => "unexpected combinator",
+			SelectorErrorKind::UnexpectedToken => "unexpected token",
+			SelectorErrorKind::UnexpectedEnd => "unexpected end",
+			SelectorErrorKind::Unsupported => "unsupported",
+		})
+	}
+}
+
 #[derive(Error, Debug)]
 pub enum Error {
-	#[error("Invalid selector:'{context}'<{reason}>")]
-	InvalidSelector { context: String, reason: String },
+	/// the `offset` is the byte offset of the `token` in the selector `context`
+	#[error("Invalid selector:'{context}'<{kind} '{token}' at offset {offset}>")]
+	InvalidSelector {
+		kind: SelectorErrorKind,
+		context: String,
+		offset: usize,
+		token: String,
+	},
+	#[error("Invalid xpath:'{context}'<{reason}>")]
+	InvalidXPath { context: String, reason: String },
 	#[error("Call method '{method}' with {error}")]
 	MethodOnInvalidSelector { method: String, error: String },
 	#[error("Call method '{method}' cause an error: {message}")]
 	InvalidTraitMethodCall { method: String, message: String },
+	#[error("Can't register '{name}': {reason}")]
+	InvalidRegistration { name: String, reason: String },
 }
@@ -1,15 +1,75 @@
 use super::{BoxDynElement, BoxDynNode, Elements};
 use crate::mesdoc::error::BoxDynError;
-use crate::mesdoc::utils::to_static_str;
+use std::collections::HashMap;
 use std::rc::Rc;
 
 pub type MaybeDoc<'a> = Option<Box<dyn IDocumentTrait + 'a>>;
 pub type IErrorHandle = Box<dyn Fn(BoxDynError)>;
+
+/// The statistics of the elements with the same tag name, class name or attribute name.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct StatsItem {
+	/// the count of the elements
+	pub count: usize,
+	/// the sum of the descendant elements count of the elements
+	pub descendants: usize,
+}
+
+/// The statistics of the elements in a document, the query planner use them to estimate the cost of the queries.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct DocumentStats {
+	/// the count of the elements
+	pub elements: usize,
+	/// the sum of the depths of the elements, the depth of the top level elements is 1
+	pub depths: usize,
+	/// the count of the elements and the root those have child elements
+	pub parents: usize,
+	/// the statistics by the lowercase tag name
+	pub tags: HashMap<String, StatsItem>,
+	/// the statistics by the class name
+	pub classes: HashMap<String, StatsItem>,
+	/// the statistics by the lowercase attribute name
+	pub attrs: HashMap<String, StatsItem>,
+}
+
+impl DocumentStats {
+	/// the average depth of the elements, at least 1
+	pub fn average_depth(&self) -> usize {
+		if self.elements == 0 {
+			return 1;
+		}
+		(self.depths / self.elements).max(1)
+	}
+	/// the average count of the sibling elements include itself, at least 1
+	pub fn average_siblings(&self) -> usize {
+		if self.parents == 0 {
+			return 1;
+		}
+		(self.elements / self.parents).max(1)
+	}
+}
+
 pub trait IDocumentTrait {
 	fn get_element_by_id<'b>(&self, id: &str) -> Option<BoxDynElement<'b>>;
-	fn source_code(&self) -> &'static str;
+	fn source_code(&self) -> String;
 	// get root node
 	fn get_root_node<'b>(&self) -> BoxDynNode<'b>;
+	// the elements with the tag name in document order, `None` means the document has no index
+	fn get_elements_by_tag_name<'b>(&self, _name: &str) -> Option<Elements<'b>> {
+		None
+	}
+	// the elements with the class name in document order, `None` means the document has no index
+	fn get_elements_by_class_name<'b>(&self, _class_name: &str) -> Option<Elements<'b>> {
+		None
+	}
+	// the elements with the attribute in document order, `None` means the document has no index
+	fn get_elements_by_attribute_name<'b>(&self, _name: &str) -> Option<Elements<'b>> {
+		None
+	}
+	// the statistics of the elements, `None` means the queries use the fixed priorities of the rules
+	fn stats(&self) -> Option<Rc<DocumentStats>> {
+		None
+	}
 	// document element, html tag
 	fn document_element<'b>(&self) -> Option<BoxDynElement<'b>> {
 		if let Some(root) = &self.get_root_node().root_element() {
@@ -19,12 +79,12 @@ pub trait IDocumentTrait {
 		None
 	}
 	// title
-	fn title(&self) -> Option<&'static str> {
+	fn title(&self) -> Option<String> {
 		if let Some(root) = &self.get_root_node().root_element() {
 			let root = Elements::with_node(root);
 			let title = root.find("head").eq(0).find("title");
 			if !title.is_empty() {
-				return Some(to_static_str(String::from(title.text())));
+				return Some(title.text());
 			}
 		}
 		None
@@ -1,8 +1,15 @@
 cfg_feat_text! {
 	use super::Texts;
 }
-use super::{BoxDynNode, BoxDynText, Elements, INodeTrait, INodeType};
+use super::{
+	unique, BoxDynNode, BoxDynText, Elements, INodeTrait, INodeType, UniqueSelectorOptions,
+};
+use crate::mesdoc::constants::{
+	NAMESPACE_HTML, NAMESPACE_MATHML, NAMESPACE_SVG, SVG_CAMEL_CASE_TAG_NAMES,
+};
 use crate::mesdoc::error::{BoxDynError, Error as IError};
+use crate::mesdoc::selector::CompiledSelector;
+use crate::mesdoc::utils::is_equal_chars;
 use std::ops::Range;
 
 pub type BoxDynElement<'a> = Box<dyn IElementTrait + 'a>;
@@ -241,7 +248,96 @@ pub trait IElementTrait: INodeTrait {
 		Elements::new()
 	}
 	// value
-	fn value(&self) -> IFormValue;
+	fn value(&self) -> IFormValue {
+		let tag_name = self.tag_names();
+		let input_tag = ['i', 'n', 'p', 'u', 't'];
+		let select_tag = ['s', 'e', 'l', 'e', 'c', 't'];
+		let option_tag = ['o', 'p', 't', 'i', 'o', 'n'];
+		let textarea_tag = ['t', 'e', 'x', 't', 'a', 'r', 'e', 'a'];
+		// if it's an (input)
+		if is_equal_chars(&tag_name, &input_tag) || is_equal_chars(&tag_name, &option_tag) {
+			// input
+			if let Some(IAttrValue::Value(v, _)) = self.get_attribute("value") {
+				return IFormValue::Single(v);
+			}
+			return IFormValue::Single(String::from(""));
+		}
+		// if it's a (select)
+		if is_equal_chars(&tag_name, &select_tag) {
+			let is_multiple = self.has_attribute("multiple");
+			// default value, if no option has "selected"
+			let mut default_value: Option<String> = None;
+			// if select's 'multiple' attribute is setted
+			let mut values: Vec<String> = vec![];
+			// collect option values
+			fn collect_option_values(
+				childs: &Elements,
+				default_value: &mut Option<String>,
+				values: &mut Vec<String>,
+				option_tag: &[char],
+				is_multiple: bool,
+				level: u8,
+			) {
+				for child in childs.get_ref() {
+					let child_tag = child.tag_names();
+					if is_equal_chars(&child_tag, option_tag) {
+						let is_selected = child.has_attribute("selected");
+						if is_selected || (default_value.is_none() && level == 0) {
+							let value = if let Some(IAttrValue::Value(v, _)) = child.get_attribute("value") {
+								v
+							} else {
+								String::from("")
+							};
+							if is_selected {
+								values.push(value);
+								// break if is single select
+								if !is_multiple {
+									break;
+								}
+							} else {
+								*default_value = Some(value);
+							}
+						}
+					} else {
+						collect_option_values(
+							&child.children(),
+							default_value,
+							values,
+							option_tag,
+							is_multiple,
+							level + 1,
+						);
+					}
+				}
+			}
+			// get the values of option
+			collect_option_values(
+				&self.children(),
+				&mut default_value,
+				&mut values,
+				&option_tag,
+				is_multiple,
+				0,
+			);
+			// if is multiple select
+			if is_multiple {
+				return IFormValue::Multiple(values);
+			}
+			// single select, check if has selected option
+			if !values.is_empty() {
+				return IFormValue::Single(values.remove(0));
+			}
+			// no selected option or no option tag
+			return IFormValue::Single(default_value.unwrap_or_else(|| String::from("")));
+		}
+		// if it's an (textarea)
+		if is_equal_chars(&tag_name, &textarea_tag) {
+			// textarea
+			return IFormValue::Single(self.text());
+		}
+		// other element
+		IFormValue::Single(String::from(""))
+	}
 	// tag name
 	fn tag_name(&self) -> String {
 		self
@@ -251,6 +347,57 @@ pub trait IElementTrait: INodeTrait {
 			.collect::<String>()
 	}
 	fn tag_names(&self) -> Vec<char>;
+	// namespace uri, only html, svg and mathml elements can be in a html document
+	fn namespace_uri(&self) -> &'static str {
+		let tag_name = self.tag_names().iter().collect::<String>();
+		match tag_name.as_str() {
+			"svg" => return NAMESPACE_SVG,
+			"math" => return NAMESPACE_MATHML,
+			_ => {}
+		}
+		if let Some(parent) = self.parent() {
+			if !parent.is_root_element() {
+				let parent_name = parent.tag_names().iter().collect::<String>();
+				match parent.namespace_uri() {
+					// html integration points
+					NAMESPACE_SVG if !matches!(parent_name.as_str(), "foreignobject" | "desc" | "title") => {
+						return NAMESPACE_SVG;
+					}
+					NAMESPACE_MATHML => {
+						// mathml text integration points and html integration points
+						let is_integration_point = match parent_name.as_str() {
+							"mi" | "mo" | "mn" | "ms" | "mtext" => true,
+							"annotation-xml" => matches!(
+								parent.get_attribute("encoding"),
+								Some(IAttrValue::Value(encoding, _))
+									if encoding.eq_ignore_ascii_case("text/html")
+										|| encoding.eq_ignore_ascii_case("application/xhtml+xml")
+							),
+							_ => false,
+						};
+						if !is_integration_point {
+							return NAMESPACE_MATHML;
+						}
+					}
+					_ => {}
+				}
+			}
+		}
+		NAMESPACE_HTML
+	}
+	// local name, keep the camel case of the svg tag names
+	fn local_name(&self) -> String {
+		let tag_name = self.tag_names().iter().collect::<String>();
+		if self.namespace_uri() == NAMESPACE_SVG {
+			if let Some(&name) = SVG_CAMEL_CASE_TAG_NAMES
+				.iter()
+				.find(|name| name.eq_ignore_ascii_case(&tag_name))
+			{
+				return String::from(name);
+			}
+		}
+		tag_name
+	}
 	// element child nodes
 	fn child_nodes_length(&self) -> usize;
 	fn child_nodes_item<'b>(&self, index: usize) -> Option<BoxDynNode<'b>>;
@@ -289,17 +436,40 @@ pub trait IElementTrait: INodeTrait {
 	fn children_by<'a>(&'a self, matcher: Box<dyn FnMut(&dyn IElementTrait) + 'a>);
 	// attribute
 	fn get_attribute(&self, name: &str) -> Option<IAttrValue>;
+	// the names of the attributes in source order, the elements without attributes can keep the default
+	fn get_attribute_names(&self) -> Vec<String> {
+		vec![]
+	}
 	fn set_attribute(&mut self, name: &str, value: Option<&str>);
 	fn remove_attribute(&mut self, name: &str);
 	fn has_attribute(&self, name: &str) -> bool {
 		self.get_attribute(name).is_some()
 	}
+	// check if the element matches the compiled selector
+	fn matches(&self, selector: &CompiledSelector) -> bool {
+		Elements::with_node(&self.cloned()).is_compiled(selector)
+	}
+	// a selector matches exactly the element in the document, `None` if not found in the max depth
+	fn unique_selector(&self, options: &UniqueSelectorOptions) -> Option<String> {
+		unique::unique_selector(&self.cloned(), options)
+	}
+	// the absolute xpath from the root, e.g. `/html/body/div[2]`
+	fn absolute_xpath(&self) -> String {
+		unique::absolute_xpath(&self.cloned())
+	}
 	// html
 	fn html(&self) -> String {
 		self.inner_html()
 	}
 	fn inner_html(&self) -> String;
 	fn outer_html(&self) -> String;
+	// the nested document of `<template>`, `<noscript>` and `<iframe srcdoc>`, it's independent of the element, so the changes are not synchronized
+	// it's parsed on each call, or parsed once and kept until the element is changed if the document is loaded by `Vis::load_nested`
+	// the returned elements keep the document alive, but the elements found by `>>>` keep it only in themselves, not in their chained traversals
+	// so if it's not kept by `Vis::load_nested`, `.find("iframe >>> p").parent("")` loses the `owner_document()` and the error handle
+	fn content_document<'b>(&self) -> Option<Elements<'b>> {
+		None
+	}
 
 	// append child, insert before, remove child
 	cfg_feat_insertion! {
@@ -5,19 +5,23 @@ cfg_feat_text! {
 cfg_feat_insertion! {
 	use super::InsertPosition;
 }
-use super::{BoxDynElement, IAttrValue, IElementTrait, IFormValue, MaybeDoc};
+use super::{
+	extract, induce, nested, BoxDynElement, IAttrValue, IElementTrait, IFormValue, MaybeDoc,
+	SelectorCandidate,
+};
 use crate::mesdoc::error::BoxDynError;
+use crate::mesdoc::xpath::{XPath, XPathValue};
 use crate::mesdoc::{constants::ATTR_CLASS, error::Error as IError, utils::class_list_to_string};
 use crate::mesdoc::{
 	constants::DEF_NODES_LEN,
 	selector::{
 		rule::{MatchAllHandle, MatchOneHandle},
-		Combinator, QueryProcess, Selector, SelectorSegment,
+		Combinator, CompiledSelector, QueryPlan, QueryProcess, Selector, SelectorSegment,
 	},
 };
 use crate::mesdoc::{
 	selector::rule::MatchSpecifiedHandle,
-	utils::{get_class_list, retain_by_index, to_static_str},
+	utils::{get_class_list, retain_by_index},
 };
 use std::collections::HashMap;
 use std::collections::HashSet;
@@ -100,6 +104,9 @@ pub struct Elements<'a> {
 	// doc will used by root elements
 	#[allow(dead_code)]
 	doc: MaybeDoc<'a>,
+	// the nested documents of the nodes finded by '>>>', keep them alive with the nodes
+	#[allow(dead_code)]
+	nested: Vec<Elements<'a>>,
 }
 
 /*
@@ -107,18 +114,17 @@ pub struct Elements<'a> {
 */
 impl<'a> Elements<'a> {
 	/*-----------trigger method proxy---------------*/
-	pub(crate) fn trigger_method<F, T: Default>(&self, method: &str, selector: &str, handle: F) -> T
+	// the lenient methods call the `try_*` methods
+	// the error will be triggered to the document's error handle, and return the default value
+	pub(crate) fn trigger_method<F, T: Default>(&self, method: &str, handle: F) -> T
 	where
-		F: Fn(&mut Selector) -> T,
+		F: FnOnce() -> Result<T, IError>,
 	{
 		if !self.is_empty() {
-			// filter handles don't use lookup
-			const USE_LOOKUP: bool = false;
-			let s = Selector::from_str(selector, USE_LOOKUP);
-			if let Ok(mut s) = s {
-				return handle(&mut s);
+			match handle() {
+				Ok(result) => return result,
+				Err(e) => self.trigger_method_throw_error(method, Box::new(e)),
 			}
-			self.trigger_method_throw_error(method, Box::new(s.unwrap_err()));
 		}
 		Default::default()
 	}
@@ -156,7 +162,11 @@ impl<'a> Elements<'a> {
 	}
 	// with all
 	pub(crate) fn with_all(nodes: Vec<BoxDynElement<'a>>, doc: MaybeDoc<'a>) -> Self {
-		Elements { nodes, doc }
+		Elements {
+			nodes,
+			doc,
+			..Default::default()
+		}
 	}
 	// with capacity
 	pub fn with_capacity(size: usize) -> Self {
@@ -344,7 +354,7 @@ impl<'a> Elements<'a> {
 	///   let root = Vis::load(html)?;
 	///   let mut document = root.document();
 	///   assert!(document.is_some());
-	///   assert_eq!(document.unwrap().title(), Some("document"));
+	///   assert_eq!(document.unwrap().title().as_deref(), Some("document"));
 	///   Ok(())
 	/// }
 	/// ```
@@ -487,7 +497,7 @@ impl<'a> Elements<'a> {
 		self.get_mut_ref().dedup_by(|a, b| a.is(b));
 	}
 	// sort then unique
-	fn sort_and_unique(&mut self) {
+	pub(crate) fn sort_and_unique(&mut self) {
 		self.sort();
 		self.unique();
 	}
@@ -499,87 +509,94 @@ impl<'a> Elements<'a> {
 */
 impl<'a> Elements<'a> {
 	// for all combinator selectors
-	fn select_with_comb(&self, method: &str, selector: &str, comb: Combinator) -> Elements<'a> {
+	fn select_with_comb(&self, selector: &str, comb: Combinator) -> Result<Elements<'a>, IError> {
 		if selector.is_empty() {
 			let segment = Selector::make_comb_all(comb);
 			let selector = Selector::from_segment(segment);
-			return self.find_selector(&selector);
+			return Ok(self.find_selector(&selector));
 		}
-		self.trigger_method(method, selector, |selector| {
-			selector.head_combinator(comb);
-			self.find_selector(selector)
-		})
+		// filter handles don't use lookup
+		const USE_LOOKUP: bool = false;
+		let mut selector = Selector::from_str(selector, USE_LOOKUP)?;
+		selector.head_combinator(comb);
+		Ok(self.find_selector(&selector))
 	}
 
 	// for all combinator until selectors
 	fn select_with_comb_until(
 		&self,
-		method: &str,
 		selector: &str,
 		filter: &str,
 		contains: bool,
 		comb: Combinator,
-	) -> Elements<'a> {
-		let selector = selector.parse::<Selector>();
-		if let Ok(selector) = &selector {
-			let segment = Selector::make_comb_all(comb);
-			let next_selector = Selector::from_segment(segment);
-			let mut result = Elements::with_capacity(DEF_NODES_LEN);
-			let (next_ok, filter) = if !filter.is_empty() {
-				let filter = filter.parse::<Selector>();
-				if let Ok(filter) = filter {
-					(true, Some(filter))
-				} else {
-					self.trigger_method_throw_error(method, Box::new(filter.unwrap_err()));
-					(false, None)
-				}
-			} else {
-				(true, None)
-			};
-			if next_ok {
-				// has filter
-				for ele in self.get_ref() {
-					let mut cur_eles = Elements::with_node(ele);
-					loop {
-						// find the next element
-						cur_eles = cur_eles.find_selector(&next_selector);
-						if !cur_eles.is_empty() {
-							let meet_until = cur_eles.filter_type_handle(selector, &FilterType::Is).1;
-							// meet the until element, and not contains, stop before check element
-							if meet_until && !contains {
-								break;
-							}
-							// check if cur_eles filter
-							let should_add = if let Some(filter) = &filter {
-								// filter true
-								cur_eles.filter_type_handle(filter, &FilterType::Is).1
-							} else {
-								// no need filter, just add
-								true
-							};
-							if should_add {
-								result.push(
-									cur_eles
-										.get(0)
-										.expect("Elements get 0 must have when length > 0")
-										.cloned(),
-								);
-							}
-							// find the until, stop the loop at the end whenever contains or not
-							if meet_until {
-								break;
-							}
-						} else {
-							break;
-						}
+	) -> Result<Elements<'a>, IError> {
+		let selector = selector.parse::<Selector>()?;
+		let filter = if !filter.is_empty() {
+			Some(filter.parse::<Selector>()?)
+		} else {
+			None
+		};
+		let segment = Selector::make_comb_all(comb);
+		let next_selector = Selector::from_segment(segment);
+		let mut result = Elements::with_capacity(DEF_NODES_LEN);
+		for ele in self.get_ref() {
+			let mut cur_eles = Elements::with_node(ele);
+			loop {
+				// find the next element
+				cur_eles = cur_eles.find_selector(&next_selector);
+				if !cur_eles.is_empty() {
+					let meet_until = cur_eles.filter_type_handle(&selector, &FilterType::Is).1;
+					// meet the until element, and not contains, stop before check element
+					if meet_until && !contains {
+						break;
+					}
+					// check if cur_eles filter
+					let should_add = if let Some(filter) = &filter {
+						// filter true
+						cur_eles.filter_type_handle(filter, &FilterType::Is).1
+					} else {
+						// no need filter, just add
+						true
+					};
+					if should_add {
+						result.push(
+							cur_eles
+								.get(0)
+								.expect("Elements get 0 must have when length > 0")
+								.cloned(),
+						);
 					}
+					// find the until, stop the loop at the end whenever contains or not
+					if meet_until {
+						break;
+					}
+				} else {
+					break;
 				}
-				return result;
 			}
-		} else {
-			self.trigger_method_throw_error(method, Box::new(selector.unwrap_err()));
 		}
-		Elements::new()
+		Ok(result)
+	}
+
+	// select the descendants of the root node from the candidates of the document indexes
+	// return `None` if the indexes can't be used
+	fn select_by_index(
+		elements: &Elements<'a>,
+		rule_item: &SelectorSegment,
+		comb: &Combinator,
+	) -> Option<Elements<'a>> {
+		let (matcher, ..) = rule_item;
+		let handle = matcher.index_handle.as_ref()?;
+		// all the elements in the document are the descendants of the root node
+		if !matches!(comb, Combinator::ChildrenAll) || elements.length() != 1 {
+			return None;
+		}
+		let root = &elements.get_ref()[0];
+		if !root.is_root_element() {
+			return None;
+		}
+		let candidates = handle(&*root.owner_document()?)?;
+		Some(matcher.apply(&candidates, None))
 	}
 
 	// select one rule
@@ -591,6 +608,11 @@ impl<'a> Elements<'a> {
 	) -> Elements<'a> {
 		let cur_comb = comb.unwrap_or(&rule_item.1);
 		let (matcher, ..) = rule_item;
+		if matcher.positional && !matches!(cur_comb, Combinator::Chain) {
+			// positional matcher need the whole matched set
+			let all = Elements::select_by_rule(elements, &Selector::make_comb_all(*cur_comb), None);
+			return matcher.apply(&all, None);
+		}
 		let mut result = Elements::with_capacity(DEF_NODES_LEN);
 		use Combinator::*;
 		match cur_comb {
@@ -920,6 +942,34 @@ impl<'a> Elements<'a> {
 		&self,
 		selector: &Selector,
 		filter_type: &FilterType,
+	) -> (Elements<'a>, bool) {
+		self.filter_process_handle(selector.process.iter(), filter_type, false)
+	}
+
+	// the `filter_type_handle` of the nested selectors such as `:not` and `:is`
+	// the positional matchers of a single compound are relative to the set matched so far, not the document
+	fn filter_in_set_handle(&self, selector: &Selector, filter_type: &FilterType) -> Elements<'a> {
+		self
+			.filter_process_handle(selector.process.iter(), filter_type, true)
+			.0
+	}
+
+	// the nested selector `:is` in a compound
+	pub(crate) fn filter_in_set(&self, selector: &CompiledSelector) -> Elements<'a> {
+		self.filter_in_set_handle(&selector.selector, &FilterType::Filter)
+	}
+
+	// the nested selector `:not` in a compound
+	pub(crate) fn not_in_set(&self, selector: &CompiledSelector) -> Elements<'a> {
+		self.filter_in_set_handle(&selector.selector, &FilterType::Not)
+	}
+
+	// the `filter_type_handle` of the query processes
+	fn filter_process_handle<'b>(
+		&self,
+		processes: impl Iterator<Item = &'b QueryProcess>,
+		filter_type: &FilterType,
+		in_set: bool,
 	) -> (Elements<'a>, bool) {
 		let eles = self.get_ref();
 		let total = eles.len();
@@ -927,12 +977,36 @@ impl<'a> Elements<'a> {
 		let mut all_matched = false;
 		let chain_comb = Combinator::Chain;
 		let mut root: Option<Elements> = None;
-		for process in selector.process.iter() {
-			// filter methods make sure do not use `should_in`
-			let QueryProcess { query, .. } = process;
+		let mut merged = 0;
+		for process in processes {
+			// filter methods make sure do not use lookup
+			let QueryProcess { query, scope, .. } = process;
 			let query_num = query.len();
 			let mut filtered = Elements::new();
-			if query_num > 0 {
+			if scope.is_some() || process.is_relative() {
+				match (&process.anchored, scope) {
+					// the relative query can begin from any element
+					(Some(anchored), _) => {
+						filtered = self.filter_type_handle(anchored, &FilterType::Filter).0;
+					}
+					// the ':scope' without the query after it is the element itself
+					(None, Some(scope)) => {
+						filtered = self.filter_type_handle(scope, &FilterType::Filter).0;
+					}
+					_ => {}
+				}
+			} else if process.is_positional() && (query_num > 1 || (query_num > 0 && !in_set)) {
+				// positional matchers are relative to the whole matched set, find from root then filter
+				if let Some(first) = self.get(0) {
+					root = root.or_else(|| {
+						let root_element = first.root_element().unwrap_or_else(|| first.cloned());
+						Some(Elements::with_node(&root_element))
+					});
+					let root_eles = root.as_ref().expect("root element must have");
+					let finded = root_eles.find_process(process);
+					filtered = self.filter_in_handle(&finded, FilterType::Filter).0;
+				}
+			} else if query_num > 0 {
 				let last_query = &query[query_num - 1];
 				let last_query_first_rule = &last_query[0];
 				filtered =
@@ -975,11 +1049,16 @@ impl<'a> Elements<'a> {
 						break;
 					}
 					_ => {
-						result = result.add(filtered);
+						merged += 1;
+						result.get_mut_ref().extend(filtered);
 					}
 				}
 			}
 		}
+		// the elements matched the processes may overlap and out of order
+		if merged > 1 {
+			result.sort_and_unique();
+		}
 		match filter_type {
 			FilterType::IsAll => {
 				all_matched = result.length() == total;
@@ -1061,7 +1140,11 @@ impl<'a> Elements<'a> {
 		}
 		(result, all_matched)
 	}
-	/// Get the descendants of each element in the Elements, filtered by the selector
+	/// Get the descendants of each element in the Elements, filtered by the selector.
+	/// The selector begin with `:scope` or a combinator is relative to each element.
+	/// The `>>>` combinator pierces into the nested documents of `<template>`, `<noscript>` and `<iframe srcdoc>`, e.g. `iframe >>> p`.
+	/// The elements in the nested documents follow the elements in the current documents.
+	/// It's supported only by `find`, `try_find` and `extract`, the other methods and `Vis::compile` return an `Unsupported` error.
 	///
 	/// ```
 	/// use visdom::Vis;
@@ -1091,19 +1174,245 @@ impl<'a> Elements<'a> {
 	///   assert_eq!(doc.find("ul li").length(), 5);
 	///   assert_eq!(doc.find("ul > li").length(), 3);
 	///   assert_eq!(doc.find("ul li:first-child").text(), "item1subitem1");
+	///   // relative to each element
+	///   let ul = doc.find("ul");
+	///   assert_eq!(ul.find(":scope > li").length(), 3);
+	///   assert_eq!(ul.find("> li > ol > li").length(), 2);
 	///   Ok(())
 	/// }
 	/// ```
 	pub fn find(&self, selector: &str) -> Elements<'a> {
-		let s = Selector::from_str(selector, true);
-		if let Ok(selector) = &s {
-			return self.find_selector(selector);
+		const METHOD: &str = "find";
+		self.trigger_method(METHOD, || self.try_find(selector))
+	}
+
+	/// Same as `find`, but return the error of an invalid selector instead of triggering the document's error handle.
+	///
+	/// The `try_*` methods are strict, the methods without the prefix are lenient, so it can be chosen at each call.
+	///
+	/// ```
+	/// use visdom::Vis;
+	/// use visdom::types::{BoxDynError, Error, SelectorErrorKind};
+	/// fn main()-> Result<(), BoxDynError>{
+	///   let html = r##"
+	///     <html>
+	///       <head>
+	///         <title>document</title>
+	///       </head>
+	///       <body>
+	///         <ul>
+	///           <li>item1</li>
+	///           <li>item2</li>
+	///         </ul>
+	///       </body>
+	///     </html>
+	///   "##;
+	///   let doc = Vis::load(html)?;
+	///   assert_eq!(doc.try_find("ul > li")?.length(), 2);
+	///   match doc.try_find("ul >") {
+	///     Err(Error::InvalidSelector { kind, .. }) => assert_eq!(kind, SelectorErrorKind::UnexpectedEnd),
+	///     _ => unreachable!(),
+	///   }
+	///   Ok(())
+	/// }
+	/// ```
+	pub fn try_find(&self, selector: &str) -> Result<Elements<'a>, IError> {
+		if let Some(parts) = nested::split_deep(selector)? {
+			let (mut finded, nested) = nested::find_deep(self, selector, &parts)?;
+			finded.nested = nested;
+			return Ok(finded);
 		}
-		self.trigger_method_throw_error("find", Box::new(s.unwrap_err()));
-		Elements::new()
+		let selector = CompiledSelector::cached(selector)?;
+		Ok(self.find_compiled(&selector))
+	}
+
+	/// Get the descendants of each element in the Elements, filtered by the compiled selector
+	///
+	/// ```
+	/// use visdom::Vis;
+	/// use visdom::types::BoxDynError;
+	/// fn main()-> Result<(), BoxDynError>{
+	///   let html = r##"
+	///     <ul>
+	///       <li>item1</li>
+	///       <li class="active">item2</li>
+	///     </ul>
+	///     <ol>
+	///       <li class="active">item3</li>
+	///     </ol>
+	///   "##;
+	///   let doc = Vis::load(html)?;
+	///   let selector = Vis::compile("li.active")?;
+	///   assert_eq!(doc.find("ul").find_compiled(&selector).text(), "item2");
+	///   assert_eq!(doc.find("ol").find_compiled(&selector).text(), "item3");
+	///   Ok(())
+	/// }
+	/// ```
+	pub fn find_compiled(&self, selector: &CompiledSelector) -> Elements<'a> {
+		self.find_selector(&selector.selector)
+	}
+
+	/// Evaluate the XPath 1.0 expression with each element in the Elements as the context node.
+	/// The node-sets are merged in document order, other values are evaluated with the first element.
+	/// If the Elements is empty, the expression is evaluated without context node, the location paths are empty node-sets.
+	///
+	/// ```
+	/// use visdom::Vis;
+	/// use visdom::types::BoxDynError;
+	/// use visdom::xpath::XPathValue;
+	/// fn main()-> Result<(), BoxDynError>{
+	///   let html = r##"
+	///     <ul>
+	///       <li>item1</li>
+	///       <li class="active">item2</li>
+	///       <li>item3</li>
+	///     </ul>
+	///   "##;
+	///   let doc = Vis::load(html)?;
+	///   let items = doc.xpath("//li[@class='active']/following-sibling::li")?.into_elements();
+	///   assert_eq!(items.text(), "item3");
+	///   assert_eq!(doc.xpath("count(//li)")?.number(), 3.0);
+	///   assert!(matches!(doc.xpath("string(//li[last()])")?, XPathValue::String(s) if s == "item3"));
+	///   Ok(())
+	/// }
+	/// ```
+	pub fn xpath(&self, expression: &str) -> Result<XPathValue<'a>, BoxDynError> {
+		Ok(XPath::from_str(expression)?.evaluate(self)?)
+	}
+
+	/// Learn the selectors from the elements as the positive examples, the negative examples should not be matched.
+	/// The examples can be in different documents, the document roots are skipped.
+	/// The candidates are validated in the documents of the examples, and ranked by the matched positive examples, the matched negative examples, then the more precise and the simpler.
+	///
+	/// ```
+	/// use visdom::Vis;
+	/// use visdom::types::{BoxDynError, Elements};
+	/// fn main()-> Result<(), BoxDynError>{
+	///   let html = r##"
+	///     <div class="products">
+	///       <div class="product"><h2 class="title">A</h2><span class="price">1</span></div>
+	///       <div class="product"><h2 class="title">B</h2><span class="price">2</span></div>
+	///       <div class="product"><h2 class="title">C</h2><span class="price">3</span></div>
+	///     </div>
+	///     <div class="news"><h2 class="title">News</h2></div>
+	///   "##;
+	///   let doc = Vis::load(html)?;
+	///   let examples = doc.find(".product:lt(2) h2");
+	///   let candidates = examples.induce_selectors(&doc.find(".news h2"));
+	///   let best = &candidates[0];
+	///   assert_eq!(best.selector, "div.product > h2");
+	///   assert_eq!((best.matched, best.positives, best.negatives), (3, 2, 0));
+	///   assert!(Elements::new().induce_selectors(&Elements::new()).is_empty());
+	///   Ok(())
+	/// }
+	/// ```
+	pub fn induce_selectors(&self, negatives: &Elements) -> Vec<SelectorCandidate> {
+		induce::induce_selectors(self, negatives)
+	}
+
+	/// Extract the values by the selector list, each selector can end with a pseudo element:
+	/// `::text`, `::own-text`, `::html`, `::outer-html` or `::attr(name)`.
+	/// The selector without pseudo element extract the outer html, the pseudo element without selector extract the elements themselves.
+	/// The pseudo element must follow the selector directly, `a ::text` is an invalid selector.
+	/// The elements without the attribute are skipped, the pseudo element without selector skip the document root.
+	/// The values of a selector list are grouped by the selectors in the order of the list, not in document order,
+	/// the values of each selector are in document order.
+	///
+	/// ```
+	/// use visdom::Vis;
+	/// use visdom::types::BoxDynError;
+	/// fn main()-> Result<(), BoxDynError>{
+	///   let html = r##"
+	///     <h1>Title <small>sub</small></h1>
+	///     <ul>
+	///       <li><a href="/a">a</a></li>
+	///       <li><a href="/b" class="next">b</a></li>
+	///       <li><a>c</a></li>
+	///     </ul>
+	///   "##;
+	///   let doc = Vis::load(html)?;
+	///   assert_eq!(doc.extract("h1::text")?, vec!["Title sub"]);
+	///   assert_eq!(doc.extract("h1::own-text")?, vec!["Title "]);
+	///   assert_eq!(doc.extract("a::attr(href)")?, vec!["/a", "/b"]);
+	///   assert_eq!(doc.extract("a.next::attr(href), h1 small")?, vec!["/b", "<small>sub</small>"]);
+	///   assert_eq!(doc.find("li").extract("::html")?.len(), 3);
+	///   assert!(doc.extract("a::src").is_err());
+	///   Ok(())
+	/// }
+	/// ```
+	pub fn extract(&self, selector: &str) -> Result<Vec<String>, IError> {
+		extract::extract(self, selector)
+	}
+
+	/// Extract the first value by the selector list, see `extract`.
+	///
+	/// ```
+	/// use visdom::Vis;
+	/// use visdom::types::BoxDynError;
+	/// fn main()-> Result<(), BoxDynError>{
+	///   let html = r##"<a href="/a">a</a><a href="/b">b</a>"##;
+	///   let doc = Vis::load(html)?;
+	///   assert_eq!(doc.extract_first("a::attr(href)")?, Some(String::from("/a")));
+	///   assert_eq!(doc.extract_first("a::attr(title)")?, None);
+	///   Ok(())
+	/// }
+	/// ```
+	pub fn extract_first(&self, selector: &str) -> Result<Option<String>, IError> {
+		Ok(self.extract(selector)?.into_iter().next())
+	}
+
+	/// Get the plans used by `find` for each selector in the selector list.
+	/// When find from the root of a document, the plan is chosen by the statistics of the tags, classes and attributes in the document,
+	/// otherwise the fixed priorities of the rules are used, and the `estimates` and `cost` are `None`.
+	///
+	/// ```
+	/// use visdom::Vis;
+	/// use visdom::selector::QueryStrategy;
+	/// use visdom::types::BoxDynError;
+	/// fn main()-> Result<(), BoxDynError>{
+	///   let html = format!(
+	///     r##"{}<div class="item"><span>last</span></div>"##,
+	///     r##"<div class="item"><b>item</b></div>"##.repeat(50)
+	///   );
+	///   let doc = Vis::load(&html)?;
+	///   // the only 'span' is more selective than the '.item'
+	///   let plans = doc.explain(".item span, .item")?;
+	///   assert_eq!(plans[0].strategy, QueryStrategy::BottomUp { start: 1 });
+	///   assert_eq!(plans[0].estimates, Some(vec![51, 1]));
+	///   assert_eq!(plans[1].strategy, QueryStrategy::TopDown);
+	///   assert_eq!(doc.find(".item span").text(), "last");
+	///   // not from the root
+	///   let plans = doc.find("div").explain(".item span")?;
+	///   assert_eq!(plans[0].strategy, QueryStrategy::TopDown);
+	///   assert_eq!(plans[0].estimates, None);
+	///   Ok(())
+	/// }
+	/// ```
+	pub fn explain(&self, selector: &str) -> Result<Vec<QueryPlan>, IError> {
+		let selector = CompiledSelector::cached(selector)?;
+		Ok(
+			selector
+				.selector
+				.process
+				.iter()
+				.map(|p| match &p.scope {
+					// the query is relative to the elements matched the scope
+					Some(scope) => {
+						let scopes = self.filter_type_handle(scope, &FilterType::Filter).0;
+						match scopes.get(0) {
+							Some(ele) => Elements::with_node(ele).plan_query(p),
+							None => QueryPlan::fixed(p),
+						}
+					}
+					None => self.plan_query(p),
+				})
+				.collect(),
+		)
 	}
 
 	/// Reduce the Elements to those that match the selector.
+	/// The positional pseudo selectors such as `:first` are relative to the document, same as `find`, not to the Elements.
+	/// The selector begin with a combinator matches the elements can be found by it from any element, e.g. `> li` is same as `* > li`.
 	///
 	/// ```
 	/// use visdom::Vis;
@@ -1135,9 +1444,20 @@ impl<'a> Elements<'a> {
 	/// ```
 	pub fn filter(&self, selector: &str) -> Elements<'a> {
 		const METHOD: &str = "filter";
-		self.trigger_method(METHOD, selector, |selector| {
-			self.filter_type_handle(selector, &FilterType::Filter).0
-		})
+		self.trigger_method(METHOD, || self.try_filter(selector))
+	}
+
+	/// Same as `filter`, but return the error of an invalid selector.
+	pub fn try_filter(&self, selector: &str) -> Result<Elements<'a>, IError> {
+		let selector = CompiledSelector::cached(selector)?;
+		Ok(self.filter_compiled(&selector))
+	}
+
+	/// Reduce the Elements to those that match the compiled selector.
+	pub fn filter_compiled(&self, selector: &CompiledSelector) -> Elements<'a> {
+		self
+			.filter_type_handle(&selector.selector, &FilterType::Filter)
+			.0
 	}
 
 	/// Reduce the Elements to those that pass the handle function test.
@@ -1259,7 +1579,12 @@ impl<'a> Elements<'a> {
 	/// }
 	/// ```
 	pub fn children(&self, selector: &str) -> Elements<'a> {
-		self.select_with_comb("children", selector, Combinator::Children)
+		self.trigger_method("children", || self.try_children(selector))
+	}
+
+	/// Same as `children`, but return the error of an invalid selector.
+	pub fn try_children(&self, selector: &str) -> Result<Elements<'a>, IError> {
+		self.select_with_comb(selector, Combinator::Children)
 	}
 
 	/// Get the previous sibling of each element in Elements, when the selector is not empty, will filtered by the selector.
@@ -1292,7 +1617,12 @@ impl<'a> Elements<'a> {
 	/// }
 	/// ```
 	pub fn prev(&self, selector: &str) -> Elements<'a> {
-		self.select_with_comb("prev", selector, Combinator::Prev)
+		self.trigger_method("prev", || self.try_prev(selector))
+	}
+
+	/// Same as `prev`, but return the error of an invalid selector.
+	pub fn try_prev(&self, selector: &str) -> Result<Elements<'a>, IError> {
+		self.select_with_comb(selector, Combinator::Prev)
 	}
 
 	/// Get all preceding siblings of each element in Elements, when the selector is not empty, will filtered by the selector.
@@ -1327,8 +1657,13 @@ impl<'a> Elements<'a> {
 	/// }
 	/// ```
 	pub fn prev_all(&self, selector: &str) -> Elements<'a> {
+		self.trigger_method("prev_all", || self.try_prev_all(selector))
+	}
+
+	/// Same as `prev_all`, but return the error of an invalid selector.
+	pub fn try_prev_all(&self, selector: &str) -> Result<Elements<'a>, IError> {
 		let uniques = self.unique_sibling_last();
-		uniques.select_with_comb("prev_all", selector, Combinator::PrevAll)
+		uniques.select_with_comb(selector, Combinator::PrevAll)
 	}
 
 	/// Get all preceding siblings of each element in Elements, until the previous sibling element matched the selector, when contains is true, the matched previous sibling will be included, otherwise it will exclude; when the filter is not empty, will filtered by the selector;
@@ -1365,15 +1700,27 @@ impl<'a> Elements<'a> {
 	/// }
 	/// ```
 	pub fn prev_until(&self, selector: &str, filter: &str, contains: bool) -> Elements<'a> {
+		self.trigger_method("prev_until", || {
+			self.try_prev_until(selector, filter, contains)
+		})
+	}
+
+	/// Same as `prev_until`, but return the error of an invalid selector or filter.
+	pub fn try_prev_until(
+		&self,
+		selector: &str,
+		filter: &str,
+		contains: bool,
+	) -> Result<Elements<'a>, IError> {
 		let uniques = self.unique_sibling_last();
 		let mut result =
-			uniques.select_with_comb_until("prev_until", selector, filter, contains, Combinator::Prev);
+			uniques.select_with_comb_until(selector, filter, contains, Combinator::Prev)?;
 		// should reverse the result when length > 1
 		// because the prevs executed from last to first
 		if result.length() > 1 {
 			result.get_mut_ref().reverse();
 		}
-		result
+		Ok(result)
 	}
 
 	/// Get the next sibling of each element in Elements, when the selector is not empty, will filtered by the selector.
@@ -1406,7 +1753,12 @@ impl<'a> Elements<'a> {
 	/// }
 	/// ```
 	pub fn next(&self, selector: &str) -> Elements<'a> {
-		self.select_with_comb("next", selector, Combinator::Next)
+		self.trigger_method("next", || self.try_next(selector))
+	}
+
+	/// Same as `next`, but return the error of an invalid selector.
+	pub fn try_next(&self, selector: &str) -> Result<Elements<'a>, IError> {
+		self.select_with_comb(selector, Combinator::Next)
 	}
 
 	/// Get all following siblings of each element in Elements, when the selector is not empty, will filtered by the selector.
@@ -1440,9 +1792,14 @@ impl<'a> Elements<'a> {
 	/// }
 	/// ```
 	pub fn next_all(&self, selector: &str) -> Elements<'a> {
+		self.trigger_method("next_all", || self.try_next_all(selector))
+	}
+
+	/// Same as `next_all`, but return the error of an invalid selector.
+	pub fn try_next_all(&self, selector: &str) -> Result<Elements<'a>, IError> {
 		// unique, keep the first sibling node
 		let uniques = self.unique_sibling_first();
-		uniques.select_with_comb("next_all", selector, Combinator::NextAll)
+		uniques.select_with_comb(selector, Combinator::NextAll)
 	}
 
 	/// Get all following siblings of each element in Elements, until the sibling element matched the selector, when contains is true, the matched sibling will be included, otherwise it will exclude; when the filter is not empty, will filtered by the selector;
@@ -1477,9 +1834,21 @@ impl<'a> Elements<'a> {
 	/// }
 	/// ```
 	pub fn next_until(&self, selector: &str, filter: &str, contains: bool) -> Elements<'a> {
+		self.trigger_method("next_until", || {
+			self.try_next_until(selector, filter, contains)
+		})
+	}
+
+	/// Same as `next_until`, but return the error of an invalid selector or filter.
+	pub fn try_next_until(
+		&self,
+		selector: &str,
+		filter: &str,
+		contains: bool,
+	) -> Result<Elements<'a>, IError> {
 		// unique, keep the first sibling node
 		let uniques = self.unique_sibling_first();
-		uniques.select_with_comb_until("next_until", selector, filter, contains, Combinator::Next)
+		uniques.select_with_comb_until(selector, filter, contains, Combinator::Next)
 	}
 
 	/// Get the siblings of each element in Elements, when the selector is not empty, will filtered by the selector.
@@ -1513,6 +1882,11 @@ impl<'a> Elements<'a> {
 	/// }
 	/// ```
 	pub fn siblings(&self, selector: &str) -> Elements<'a> {
+		self.trigger_method("siblings", || self.try_siblings(selector))
+	}
+
+	/// Same as `siblings`, but return the error of an invalid selector.
+	pub fn try_siblings(&self, selector: &str) -> Result<Elements<'a>, IError> {
 		let uniques = self.unique_all_siblings();
 		// when selector is empty or only
 		let mut siblings_selector: Selector;
@@ -1524,30 +1898,14 @@ impl<'a> Elements<'a> {
 			siblings_selector = Selector::from_segment(Selector::make_comb_all(siblings_comb));
 			child_selector = Selector::from_segment(Selector::make_comb_all(child_comb));
 		} else {
-			// self
-			let sib_selector = selector.parse::<Selector>();
-			if let Ok(sib_selector) = sib_selector {
-				// clone the selector to a child selector
-				child_selector = selector
-					.parse::<Selector>()
-					.expect("The selector has detected");
-				child_selector.head_combinator(child_comb);
-				// use siblings selector
-				siblings_selector = sib_selector;
-				siblings_selector.head_combinator(siblings_comb);
-			} else {
-				self.trigger_method_throw_error(
-					"siblings",
-					Box::new(IError::InvalidTraitMethodCall {
-						method: "siblings".to_string(),
-						message: format!(
-							"Invalid selector:{}",
-							sib_selector.err().expect("Selector parse error")
-						),
-					}),
-				);
-				return Elements::new();
-			}
+			// use siblings selector
+			siblings_selector = selector.parse::<Selector>()?;
+			siblings_selector.head_combinator(siblings_comb);
+			// clone the selector to a child selector
+			child_selector = selector
+				.parse::<Selector>()
+				.expect("The selector has detected");
+			child_selector.head_combinator(child_comb);
 		}
 		// uniques
 		let mut result = Elements::with_capacity(DEF_NODES_LEN);
@@ -1562,7 +1920,7 @@ impl<'a> Elements<'a> {
 		}
 		// sort the result
 		result.sort();
-		result
+		Ok(result)
 	}
 
 	/// Get the parent of each element in Elements, when the selector is not empty, will filtered by the selector.
@@ -1596,9 +1954,14 @@ impl<'a> Elements<'a> {
 	/// }
 	/// ```
 	pub fn parent(&self, selector: &str) -> Elements<'a> {
+		self.trigger_method("parent", || self.try_parent(selector))
+	}
+
+	/// Same as `parent`, but return the error of an invalid selector.
+	pub fn try_parent(&self, selector: &str) -> Result<Elements<'a>, IError> {
 		// unique, keep the first sibling node
 		let uniques = self.unique_sibling_first();
-		uniques.select_with_comb("parent", selector, Combinator::Parent)
+		uniques.select_with_comb(selector, Combinator::Parent)
 	}
 
 	/// Get the ancestors of each element in Elements, when the selector is not empty, will filtered by the selector.
@@ -1633,11 +1996,16 @@ impl<'a> Elements<'a> {
 	/// }
 	/// ```
 	pub fn parents(&self, selector: &str) -> Elements<'a> {
+		self.trigger_method("parents", || self.try_parents(selector))
+	}
+
+	/// Same as `parents`, but return the error of an invalid selector.
+	pub fn try_parents(&self, selector: &str) -> Result<Elements<'a>, IError> {
 		// unique, keep the first sibling node
 		let uniques = self.unique_sibling_first();
-		let mut result = uniques.select_with_comb("parents", selector, Combinator::ParentAll);
+		let mut result = uniques.select_with_comb(selector, Combinator::ParentAll)?;
 		result.sort_and_unique();
-		result
+		Ok(result)
 	}
 
 	/// Get the ancestors of each element in Elements, until the ancestor matched the selector, when contains is true, the matched ancestor will be included, otherwise it will exclude; when the filter is not empty, will filtered by the selector;
@@ -1671,19 +2039,26 @@ impl<'a> Elements<'a> {
 	/// }
 	/// ```
 	pub fn parents_until(&self, selector: &str, filter: &str, contains: bool) -> Elements<'a> {
+		self.trigger_method("parents_until", || {
+			self.try_parents_until(selector, filter, contains)
+		})
+	}
+
+	/// Same as `parents_until`, but return the error of an invalid selector or filter.
+	pub fn try_parents_until(
+		&self,
+		selector: &str,
+		filter: &str,
+		contains: bool,
+	) -> Result<Elements<'a>, IError> {
 		// unique, keep the first sibling node
 		let uniques = self.unique_sibling_first();
-		let mut result = uniques.select_with_comb_until(
-			"parents_until",
-			selector,
-			filter,
-			contains,
-			Combinator::Parent,
-		);
+		let mut result =
+			uniques.select_with_comb_until(selector, filter, contains, Combinator::Parent)?;
 		// parents may not unique if has ancestor and childs
 		// if parents length > 1, the parents need reversed
 		result.sort_and_unique();
-		result
+		Ok(result)
 	}
 
 	/// Get the first matched element of each element in Elements, traversing from self to it's ancestors.
@@ -1717,104 +2092,149 @@ impl<'a> Elements<'a> {
 	/// }
 	/// ```
 	pub fn closest(&self, selector: &str) -> Elements<'a> {
+		const METHOD: &str = "closest";
+		self.trigger_method(METHOD, || self.try_closest(selector))
+	}
+
+	/// Same as `closest`, but return the error of an invalid selector.
+	pub fn try_closest(&self, selector: &str) -> Result<Elements<'a>, IError> {
 		// when selector is not provided
 		if selector.is_empty() {
-			return Elements::new();
+			return Ok(Elements::new());
 		}
+		let selector = CompiledSelector::cached(selector)?;
+		Ok(self.closest_compiled(&selector))
+	}
+
+	/// Get the first element that matches the compiled selector by testing the element itself and traversing up through its ancestors.
+	pub fn closest_compiled(&self, selector: &CompiledSelector) -> Elements<'a> {
 		// find the nearst node
-		const METHOD: &str = "closest";
-		let selector = selector.parse::<Selector>();
-		if let Ok(selector) = selector {
-			let total = self.length();
-			let mut result = Elements::with_capacity(total);
-			let mut propagations = Elements::with_capacity(total);
-			for ele in self.get_ref() {
-				let mut cur_eles = Elements::with_node(ele);
-				if cur_eles.filter_type_handle(&selector, &FilterType::Is).1 {
-					// check self
-					result.get_mut_ref().push(cur_eles.get_mut_ref().remove(0));
-				} else {
-					propagations
-						.get_mut_ref()
-						.push(cur_eles.get_mut_ref().remove(0));
-				}
+		let selector = &selector.selector;
+		let total = self.length();
+		let mut result = Elements::with_capacity(total);
+		if selector.is_positional() {
+			// like jquery, positional selectors never match, because there is no matched set
+			return result;
+		}
+		let mut propagations = Elements::with_capacity(total);
+		for ele in self.get_ref() {
+			let mut cur_eles = Elements::with_node(ele);
+			if cur_eles.filter_type_handle(selector, &FilterType::Is).1 {
+				// check self
+				result.get_mut_ref().push(cur_eles.get_mut_ref().remove(0));
+			} else {
+				propagations
+					.get_mut_ref()
+					.push(cur_eles.get_mut_ref().remove(0));
 			}
-			if !propagations.is_empty() {
-				let uniques = propagations.unique_sibling_first();
-				for ele in uniques.get_ref() {
-					let mut cur_eles = Elements::with_node(ele);
-					loop {
-						if cur_eles.filter_type_handle(&selector, &FilterType::Is).1 {
-							result.get_mut_ref().push(cur_eles.get_mut_ref().remove(0));
-							break;
-						}
-						if let Some(parent) = &cur_eles
-							.get(0)
-							.expect("Elements must have one node")
-							.parent()
-						{
-							if !parent.is_root_element() {
-								cur_eles = Elements::with_node(parent);
-							} else {
-								break;
-							}
+		}
+		if !propagations.is_empty() {
+			// the ':scope' alone is the element itself, so the ancestors only check the other processes
+			let is_unscoped = |p: &&QueryProcess| p.scope.is_none() || p.anchored.is_some();
+			let uniques = propagations.unique_sibling_first();
+			for ele in uniques.get_ref() {
+				let mut cur_eles = Elements::with_node(ele);
+				loop {
+					let processes = selector.process.iter().filter(is_unscoped);
+					if cur_eles
+						.filter_process_handle(processes, &FilterType::Is, false)
+						.1
+					{
+						result.get_mut_ref().push(cur_eles.get_mut_ref().remove(0));
+						break;
+					}
+					if let Some(parent) = &cur_eles
+						.get(0)
+						.expect("Elements must have one node")
+						.parent()
+					{
+						if !parent.is_root_element() {
+							cur_eles = Elements::with_node(parent);
 						} else {
 							break;
 						}
+					} else {
+						break;
 					}
 				}
-				// need sort and unique
-				result.sort_and_unique();
 			}
-			result
-		} else {
-			self.trigger_method_throw_error(METHOD, Box::new(selector.unwrap_err()));
-			Elements::new()
+			// need sort and unique
+			result.sort_and_unique();
 		}
+		result
 	}
 	// for `find` and `select_with_comb`
 	fn find_selector(&self, selector: &Selector) -> Elements<'a> {
 		let mut result = Elements::with_capacity(DEF_NODES_LEN);
 		if !self.is_empty() {
 			for p in &selector.process {
-				let QueryProcess { should_in, query } = p;
-				let first_query = &query[0];
-				let mut group: Elements = Elements::with_capacity(DEF_NODES_LEN);
-				if let Some(lookup) = should_in {
-					// find the first query elements
-					let finded = Elements::select(self, first_query, Some(&Combinator::ChildrenAll));
-					if !finded.is_empty() {
-						let first_comb = &first_query[0].1;
-						// check the elements if satisfied the lookup
-						for ele in finded.get_ref() {
-							if self.has_ele(ele, first_comb, Some(lookup)) {
-								group.push(ele.cloned());
-							}
-						}
-					}
-				} else {
-					// find the first query elements
-					group = Elements::select(self, first_query, None);
-				}
+				let group = self.find_process(p);
 				if !group.is_empty() {
-					let mut need_combine = true;
-					if query.len() > 1 {
-						for rules in &query[1..] {
-							group = Elements::select(&group, rules, None);
-							if group.is_empty() {
-								need_combine = false;
-								break;
-							}
-						}
-					}
-					if need_combine {
-						result = result.add(group);
-					}
+					result = result.add(group);
 				}
 			}
 		}
 		result
 	}
+	// find the elements matched a query process
+	fn find_process(&self, p: &QueryProcess) -> Elements<'a> {
+		if let Some(scope) = &p.scope {
+			// the query is relative to each element matched the scope, ':scope' itself is not a descendant
+			let mut group = Elements::with_capacity(DEF_NODES_LEN);
+			if !p.query.is_empty() {
+				let scopes = self.filter_type_handle(scope, &FilterType::Filter).0;
+				for ele in scopes.get_ref() {
+					group = group.add(Elements::with_node(ele).find_query(p));
+				}
+			}
+			return group;
+		}
+		self.find_query(p)
+	}
+	// plan the query by the statistics of the document when find from the root, otherwise by the fixed priorities
+	fn plan_query(&self, p: &QueryProcess) -> QueryPlan {
+		if p.query.len() > 1 && self.length() == 1 {
+			let root = &self.get_ref()[0];
+			if root.is_root_element() {
+				if let Some(stats) = root.owner_document().and_then(|doc| doc.stats()) {
+					return QueryPlan::with_stats(p, &stats);
+				}
+			}
+		}
+		QueryPlan::fixed(p)
+	}
+	// find the elements matched the query of a process
+	fn find_query(&self, p: &QueryProcess) -> Elements<'a> {
+		let plan = self.plan_query(p);
+		let (should_in, query) = p.should_in(&plan.strategy);
+		let first_query = &query[0];
+		let mut group: Elements = Elements::with_capacity(DEF_NODES_LEN);
+		if let Some(lookup) = should_in {
+			// find the first query elements
+			let finded = Elements::select(self, first_query, Some(&Combinator::ChildrenAll));
+			if !finded.is_empty() {
+				let first_comb = &first_query[0].1;
+				// check the elements if satisfied the lookup
+				for ele in finded.get_ref() {
+					if self.has_ele(ele, first_comb, Some(lookup)) {
+						group.push(ele.cloned());
+					}
+				}
+			}
+		} else {
+			// find the first query elements
+			group = Elements::select(self, first_query, None);
+		}
+		if !group.is_empty() && query.len() > 1 {
+			for rules in &query[1..] {
+				group = Elements::select(&group, rules, None);
+				if group.is_empty() {
+					break;
+				}
+			}
+		}
+		group
+	}
 
 	// select ele by rules
 	fn select(
@@ -1841,6 +2261,8 @@ impl<'a> Elements<'a> {
 			} else {
 				Elements::new()
 			}
+		} else if let Some(indexed) = Elements::select_by_index(elements, first_rule, comb) {
+			indexed
 		} else {
 			Elements::select_by_rule(elements, first_rule, Some(comb))
 		};
@@ -1949,6 +2371,7 @@ impl<'a> Elements<'a> {
 	}
 
 	/// Check at least one element in Elements is match the selector.
+	/// The positional pseudo selectors such as `:first` are relative to the document, same as `find`, not to the Elements.
 	///
 	/// ```
 	/// use visdom::Vis;
@@ -1982,9 +2405,20 @@ impl<'a> Elements<'a> {
 	/// ```
 	pub fn is(&self, selector: &str) -> bool {
 		const METHOD: &str = "is";
-		self.trigger_method(METHOD, selector, |selector| {
-			self.filter_type_handle(selector, &FilterType::Is).1
-		})
+		self.trigger_method(METHOD, || self.try_is(selector))
+	}
+
+	/// Same as `is`, but return the error of an invalid selector.
+	pub fn try_is(&self, selector: &str) -> Result<bool, IError> {
+		let selector = CompiledSelector::cached(selector)?;
+		Ok(self.is_compiled(&selector))
+	}
+
+	/// Check at least one element in Elements is match the compiled selector.
+	pub fn is_compiled(&self, selector: &CompiledSelector) -> bool {
+		self
+			.filter_type_handle(&selector.selector, &FilterType::Is)
+			.1
 	}
 
 	/// Check at least one element in Elements call the handle function return true.
@@ -2104,9 +2538,17 @@ impl<'a> Elements<'a> {
 	/// ```
 	pub fn is_all(&self, selector: &str) -> bool {
 		const METHOD: &str = "is_all";
-		self.trigger_method(METHOD, selector, |selector| {
-			self.filter_type_handle(selector, &FilterType::IsAll).1
-		})
+		self.trigger_method(METHOD, || self.try_is_all(selector))
+	}
+
+	/// Same as `is_all`, but return the error of an invalid selector.
+	pub fn try_is_all(&self, selector: &str) -> Result<bool, IError> {
+		let selector = CompiledSelector::cached(selector)?;
+		Ok(
+			self
+				.filter_type_handle(&selector.selector, &FilterType::IsAll)
+				.1,
+		)
 	}
 
 	/// Check if each element in Elements call the handle function are all returned true.
@@ -2198,6 +2640,7 @@ impl<'a> Elements<'a> {
 	}
 
 	/// Remove elements those that match the selector from the Elements set.
+	/// The positional pseudo selectors such as `:first` are relative to the document, same as `find`, not to the Elements.
 	///
 	/// ```
 	/// use visdom::Vis;
@@ -2229,9 +2672,17 @@ impl<'a> Elements<'a> {
 	/// ```
 	pub fn not(&self, selector: &str) -> Elements<'a> {
 		const METHOD: &str = "not";
-		self.trigger_method(METHOD, selector, |selector| {
-			self.filter_type_handle(selector, &FilterType::Not).0
-		})
+		self.trigger_method(METHOD, || self.try_not(selector))
+	}
+
+	/// Same as `not`, but return the error of an invalid selector.
+	pub fn try_not(&self, selector: &str) -> Result<Elements<'a>, IError> {
+		let selector = CompiledSelector::cached(selector)?;
+		Ok(
+			self
+				.filter_type_handle(&selector.selector, &FilterType::Not)
+				.0,
+		)
 	}
 
 	/// Remove elements those that pass the handle function test from the Elements set.
@@ -2342,24 +2793,41 @@ impl<'a> Elements<'a> {
 	/// ```
 	pub fn has(&self, selector: &str) -> Elements<'a> {
 		const METHOD: &str = "has";
-		fn loop_handle(ele: &BoxDynElement, selector: &Selector) -> bool {
+		self.trigger_method(METHOD, || self.try_has(selector))
+	}
+
+	/// Same as `has`, but return the error of an invalid selector.
+	pub fn try_has(&self, selector: &str) -> Result<Elements<'a>, IError> {
+		fn loop_handle(ele: &BoxDynElement, processes: &[&QueryProcess]) -> bool {
 			let childs = ele.children();
 			if !childs.is_empty() {
-				let (_, all_matched) = childs.filter_type_handle(selector, &FilterType::Is);
+				let (_, all_matched) =
+					childs.filter_process_handle(processes.iter().copied(), &FilterType::Is, false);
 				if all_matched {
 					return true;
 				}
 				for child in childs.get_ref() {
-					if loop_handle(child, selector) {
+					if loop_handle(child, processes) {
 						return true;
 					}
 				}
 			}
 			false
 		}
-		self.trigger_method(METHOD, selector, |selector| {
-			self.filter_by(|_, ele| loop_handle(ele, selector))
-		})
+		let selector = CompiledSelector::cached(selector)?;
+		// the relative processes are found from the element, the others are checked by the descendants
+		let (relatives, processes): (Vec<&QueryProcess>, Vec<&QueryProcess>) = selector
+			.selector
+			.process
+			.iter()
+			.partition(|p| p.scope.is_some() || p.is_relative());
+		Ok(self.filter_by(|_, ele| {
+			let cur_eles = Elements::with_node(ele);
+			relatives
+				.iter()
+				.any(|p| !cur_eles.find_process(p).is_empty())
+				|| (!processes.is_empty() && loop_handle(ele, &processes))
+		}))
 	}
 
 	/// Reduce Elements to those that have a descendant that matches the selector.
@@ -2786,7 +3254,7 @@ impl<'a> Elements<'a> {
 	}
 
 	/// check if the ele list contains some ele
-	fn includes(&self, ele: &BoxDynElement) -> bool {
+	pub(crate) fn includes(&self, ele: &BoxDynElement) -> bool {
 		self.get_ref().iter().any(|n| ele.is(n))
 	}
 
@@ -2890,12 +3358,12 @@ impl<'a> Elements<'a> {
 	///   Ok(())
 	/// }
 	/// ```
-	pub fn text(&self) -> &str {
+	pub fn text(&self) -> String {
 		let mut result = String::with_capacity(50);
 		for ele in self.get_ref() {
 			result.push_str(&ele.text_content());
 		}
-		to_static_str(result)
+		result
 	}
 
 	/// Set the Elements's text, the html entity in content will auto encoded.
@@ -0,0 +1,191 @@
+use super::{BoxDynElement, Elements, IAttrValue, INodeType};
+use crate::mesdoc::error::{Error as IError, SelectorErrorKind};
+use crate::mesdoc::utils::top_level_chars;
+
+// the value extracted from the matched elements, decided by the pseudo element
+enum Extractor {
+	// no pseudo element
+	OuterHtml,
+	// `::html`
+	Html,
+	// `::text`
+	Text,
+	// `::own-text`
+	OwnText,
+	// `::attr(name)`
+	Attr(String),
+}
+
+impl Extractor {
+	fn extract(&self, ele: &BoxDynElement) -> Option<String> {
+		match self {
+			Extractor::OuterHtml => Some(ele.outer_html()),
+			Extractor::Html => Some(ele.inner_html()),
+			Extractor::Text => Some(ele.text_content()),
+			Extractor::OwnText => Some(
+				ele
+					.child_nodes()
+					.iter()
+					.filter(|node| matches!(node.node_type(), INodeType::Text))
+					.map(|node| node.text_content())
+					.collect(),
+			),
+			// the attribute without value is extracted as an empty string
+			Extractor::Attr(name) => match ele.get_attribute(name)? {
+				IAttrValue::Value(value, _) => Some(value),
+				IAttrValue::True => Some(String::new()),
+			},
+		}
+	}
+}
+
+pub(super) fn make_error(context: &str, offset: usize, kind: SelectorErrorKind) -> IError {
+	let token = context[offset..]
+		.split(|ch: char| ch.is_ascii_whitespace() || ch == ',')
+		.next()
+		.unwrap_or_default();
+	IError::InvalidSelector {
+		kind,
+		context: String::from(context),
+		offset,
+		token: String::from(token),
+	}
+}
+
+// parse the pseudo element begin with '::' at the start offset, return the extractor and the end offset
+fn parse_pseudo_element(context: &str, start: usize) -> Result<(Extractor, usize), IError> {
+	let rest = &context[start + 2..];
+	let name_len = rest
+		.find(|ch: char| !(ch.is_ascii_alphanumeric() || ch == '-'))
+		.unwrap_or(rest.len());
+	let end = start + 2 + name_len;
+	let extractor = match &rest[..name_len] {
+		"text" => Extractor::Text,
+		"own-text" => Extractor::OwnText,
+		"html" => Extractor::Html,
+		"outer-html" => Extractor::OuterHtml,
+		"attr" => {
+			let args = rest[name_len..]
+				.strip_prefix('(')
+				.and_then(|args| args.find(')').map(|close| &args[..close]));
+			let name = args.map(|args| args.trim()).unwrap_or_default();
+			if name.is_empty() || name.contains(|ch: char| ch.is_ascii_whitespace()) {
+				let kind = if args.is_none() && context[end..].trim().is_empty() {
+					SelectorErrorKind::UnexpectedEnd
+				} else {
+					SelectorErrorKind::UnexpectedToken
+				};
+				return Err(make_error(context, start, kind));
+			}
+			let args_len = args.map(|args| args.len()).unwrap_or_default();
+			return Ok((Extractor::Attr(String::from(name)), end + args_len + 2));
+		}
+		_ => {
+			return Err(make_error(
+				context,
+				start,
+				SelectorErrorKind::UnexpectedToken,
+			))
+		}
+	};
+	Ok((extractor, end))
+}
+
+// split the selector list by the top level ',', each selector can end with a pseudo element
+// return the start offset of each selector, the selector and the extractor
+fn parse_extract(context: &str) -> Result<Vec<(usize, &str, Extractor)>, IError> {
+	let mut result = Vec::with_capacity(1);
+	let mut start = 0;
+	// the start offset of the pseudo element, the extractor and the end offset
+	let mut pseudo: Option<(usize, Extractor, usize)> = None;
+	let mut add_selector = |start: usize, end: usize, pseudo: Option<(usize, Extractor, usize)>| {
+		let (selector, extractor) = match pseudo {
+			Some((pseudo_start, extractor, _)) => {
+				let selector = &context[start..pseudo_start];
+				// the pseudo element must follow the selector, e.g. 'a ::text' is not allowed
+				if selector.ends_with(|ch: char| ch.is_ascii_whitespace()) && !selector.trim().is_empty() {
+					return Err(make_error(
+						context,
+						pseudo_start,
+						SelectorErrorKind::UnexpectedToken,
+					));
+				}
+				(selector, extractor)
+			}
+			None if context[start..end].trim().is_empty() => {
+				return Err(make_error(context, end, SelectorErrorKind::EmptySelector));
+			}
+			None => (&context[start..end], Extractor::OuterHtml),
+		};
+		result.push((start, selector, extractor));
+		Ok(())
+	};
+	for (index, ch) in top_level_chars(context) {
+		if let Some((_, _, end)) = &pseudo {
+			// only whitespaces are allowed after the pseudo element
+			if index < *end || ch.is_ascii_whitespace() {
+				continue;
+			}
+			if ch != ',' {
+				return Err(make_error(
+					context,
+					index,
+					SelectorErrorKind::UnexpectedToken,
+				));
+			}
+		}
+		match ch {
+			':' if context[index..].starts_with("::") => {
+				let (extractor, end) = parse_pseudo_element(context, index)?;
+				pseudo = Some((index, extractor, end));
+			}
+			',' => {
+				add_selector(start, index, pseudo.take())?;
+				start = index + 1;
+			}
+			_ => {}
+		}
+	}
+	add_selector(start, context.len(), pseudo)?;
+	Ok(result)
+}
+
+pub(crate) fn extract(eles: &Elements, context: &str) -> Result<Vec<String>, IError> {
+	let mut result = Vec::with_capacity(eles.length());
+	for (start, selector, extractor) in parse_extract(context)? {
+		// the pseudo element without selector extract the elements themselves, the document root node is not an element
+		let finded = if selector.trim().is_empty() {
+			Elements::with_nodes(
+				eles
+					.get_ref()
+					.iter()
+					.filter(|ele| !ele.is_root_element())
+					.map(|ele| ele.cloned())
+					.collect(),
+			)
+		} else {
+			// the error offset is relative to the whole selector list
+			eles.try_find(selector).map_err(|err| match err {
+				IError::InvalidSelector {
+					kind,
+					offset,
+					token,
+					..
+				} => IError::InvalidSelector {
+					kind,
+					context: String::from(context),
+					offset: start + offset,
+					token,
+				},
+				err => err,
+			})?
+		};
+		result.extend(
+			finded
+				.get_ref()
+				.iter()
+				.filter_map(|ele| extractor.extract(ele)),
+		);
+	}
+	Ok(result)
+}
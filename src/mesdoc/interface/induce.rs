@@ -0,0 +1,335 @@
+use super::unique::{get_attr_value, is_generated_name, quote_value, to_identity};
+use super::{BoxDynElement, Elements, IAttrValue};
+use crate::mesdoc::error::Error as IError;
+use crate::mesdoc::selector::ast::SelectorList;
+
+/// A selector learned from the example elements
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SelectorCandidate {
+	pub selector: String,
+	/// the count of the elements matched in the documents of the examples
+	pub matched: usize,
+	/// the count of the positive examples matched
+	pub positives: usize,
+	/// the count of the negative examples matched
+	pub negatives: usize,
+}
+
+// the attributes those are unstable or handled separately
+const SKIP_ATTRIBUTES: [&str; 3] = ["id", "class", "style"];
+
+// the features of the element can be used in selectors
+struct Features {
+	// empty if the tag names are different
+	tag: String,
+	classes: Vec<String>,
+	// the attribute value is `None` if the values are different
+	attrs: Vec<(String, Option<String>)>,
+	// the position in the children, begin with 1, `0` if the positions are different
+	position: usize,
+}
+
+impl Features {
+	fn from_element(ele: &BoxDynElement) -> Self {
+		let classes = get_attr_value(ele, "class")
+			.map(|class_list| {
+				class_list
+					.split_ascii_whitespace()
+					.filter(|class| !is_generated_name(class))
+					.filter_map(to_identity)
+					.collect()
+			})
+			.unwrap_or_default();
+		let attrs = ele
+			.get_attribute_names()
+			.into_iter()
+			.filter(|name| {
+				!SKIP_ATTRIBUTES.contains(&name.as_str())
+					&& to_identity(name).as_deref() == Some(name.as_str())
+			})
+			.map(|name| {
+				let value = match ele.get_attribute(&name) {
+					Some(IAttrValue::Value(value, _)) => Some(value),
+					_ => None,
+				};
+				(name, value)
+			})
+			.collect();
+		let position = ele
+			.parent()
+			.and_then(|parent| {
+				parent
+					.children()
+					.get_ref()
+					.iter()
+					.position(|child| child.is(ele))
+			})
+			.map(|index| index + 1)
+			.unwrap_or(0);
+		Features {
+			tag: ele.tag_names().iter().collect(),
+			classes,
+			attrs,
+			position,
+		}
+	}
+	// the features shared by all the elements
+	fn intersect(features: &[Features]) -> Self {
+		let first = &features[0];
+		let rest = &features[1..];
+		let tag = if rest.iter().all(|cur| cur.tag == first.tag) {
+			first.tag.clone()
+		} else {
+			String::new()
+		};
+		let classes = first
+			.classes
+			.iter()
+			.filter(|class| rest.iter().all(|cur| cur.classes.contains(class)))
+			.cloned()
+			.collect();
+		let attrs = first
+			.attrs
+			.iter()
+			.filter_map(|(name, value)| {
+				let mut is_same_value = true;
+				for cur in rest {
+					let (_, cur_value) = cur.attrs.iter().find(|(cur_name, _)| cur_name == name)?;
+					is_same_value = is_same_value && cur_value == value;
+				}
+				let value = if is_same_value { value.clone() } else { None };
+				Some((name.clone(), value))
+			})
+			.collect();
+		let position = if rest.iter().all(|cur| cur.position == first.position) {
+			first.position
+		} else {
+			0
+		};
+		Features {
+			tag,
+			classes,
+			attrs,
+			position,
+		}
+	}
+	// the compound selectors of the features
+	fn compounds(&self) -> Vec<String> {
+		let tag = &self.tag;
+		let mut result = Vec::with_capacity(5);
+		// the tag names are different, use `*` with the contexts
+		result.push(if tag.is_empty() {
+			String::from("*")
+		} else {
+			tag.clone()
+		});
+		for class in &self.classes {
+			result.push(format!("{}.{}", tag, class));
+		}
+		if self.classes.len() > 1 {
+			result.push(format!("{}.{}", tag, self.classes.join(".")));
+		}
+		for (name, value) in &self.attrs {
+			result.push(format!("{}[{}]", tag, name));
+			if let Some(value) = value.as_deref().and_then(quote_value) {
+				result.push(format!("{}[{}={}]", tag, name, value));
+			}
+		}
+		if self.position > 0 {
+			let tag = if tag.is_empty() { "*" } else { tag };
+			result.push(format!("{}:nth-child({})", tag, self.position));
+		}
+		result
+	}
+}
+
+// the nearest id shared by the ancestors of all the elements
+fn common_ancestor_id(eles: &Elements) -> Option<String> {
+	let ancestor_ids = |ele: &BoxDynElement| {
+		let mut ids = Vec::new();
+		let mut cur = ele.parent();
+		while let Some(parent) = cur.filter(|parent| !parent.is_root_element()) {
+			if let Some(id) = get_attr_value(&parent, "id") {
+				if !is_generated_name(&id) {
+					ids.extend(to_identity(&id));
+				}
+			}
+			cur = parent.parent();
+		}
+		ids
+	};
+	let mut all_ids = eles.get_ref().iter().map(ancestor_ids);
+	let first_ids = all_ids.next()?;
+	let rest_ids = all_ids.collect::<Vec<Vec<String>>>();
+	first_ids
+		.into_iter()
+		.find(|id| rest_ids.iter().all(|ids| ids.contains(id)))
+}
+
+// the count of the simple selectors, the less is the simpler
+fn get_complexity(selector: &str) -> usize {
+	selector
+		.parse::<SelectorList>()
+		.map(|list| {
+			list
+				.selectors
+				.iter()
+				.flat_map(|selector| selector.compounds.iter())
+				.map(|(_, compound)| compound.simples.len())
+				.sum()
+		})
+		.unwrap_or(usize::MAX)
+}
+
+// the elements without the document root nodes, the root nodes have no features
+fn without_roots<'a>(eles: &Elements<'a>) -> Elements<'a> {
+	Elements::with_nodes(
+		eles
+			.get_ref()
+			.iter()
+			.filter(|ele| !ele.is_root_element())
+			.map(|ele| ele.cloned())
+			.collect(),
+	)
+}
+
+// the examples in a document, the examples may be in different documents
+struct DocExamples<'a> {
+	root: Elements<'a>,
+	positives: Elements<'a>,
+	negatives: Elements<'a>,
+}
+
+// group the examples by the documents, the filters check the ancestors in the document of the first element
+fn group_by_docs<'a>(positives: &Elements<'a>, negatives: &Elements<'a>) -> Vec<DocExamples<'a>> {
+	let mut docs: Vec<DocExamples> = Vec::new();
+	for (eles, is_positive) in [(positives, true), (negatives, false)] {
+		for ele in eles.get_ref() {
+			let mut root = ele.cloned();
+			while let Some(parent) = root.parent() {
+				root = parent;
+			}
+			let index = match docs.iter().position(|doc| doc.root.get_ref()[0].is(&root)) {
+				Some(index) => index,
+				None => {
+					docs.push(DocExamples {
+						root: Elements::with_node(&root),
+						positives: Elements::new(),
+						negatives: Elements::new(),
+					});
+					docs.len() - 1
+				}
+			};
+			let doc = &mut docs[index];
+			let examples = if is_positive {
+				&mut doc.positives
+			} else {
+				&mut doc.negatives
+			};
+			examples.push(ele.cloned());
+		}
+	}
+	docs
+}
+
+// the elements matched in each document, the positives and negatives matched
+fn validate<'a>(
+	docs: &[DocExamples<'a>],
+	selector: &str,
+) -> Result<(Elements<'a>, usize, usize), IError> {
+	let mut matched = Elements::new();
+	let mut positives = 0;
+	let mut negatives = 0;
+	for doc in docs {
+		matched.get_mut_ref().extend(doc.root.try_find(selector)?);
+		positives += doc.positives.try_filter(selector)?.length();
+		negatives += doc.negatives.try_filter(selector)?.length();
+	}
+	Ok((matched, positives, negatives))
+}
+
+pub(crate) fn induce_selectors(
+	positives: &Elements,
+	negatives: &Elements,
+) -> Vec<SelectorCandidate> {
+	let positives = &without_roots(positives);
+	let negatives = &without_roots(negatives);
+	if positives.is_empty() {
+		return Vec::new();
+	}
+	// the selectors are matched from the root of each document
+	let docs = group_by_docs(positives, negatives);
+	let features = positives
+		.get_ref()
+		.iter()
+		.map(Features::from_element)
+		.collect::<Vec<Features>>();
+	let locals = Features::intersect(&features).compounds();
+	// the contexts of the compound selectors
+	let mut contexts = vec![String::new()];
+	let parents = positives
+		.get_ref()
+		.iter()
+		.map(|ele| ele.parent().filter(|parent| !parent.is_root_element()))
+		.collect::<Option<Vec<BoxDynElement>>>();
+	if let Some(parents) = parents {
+		let parent_features = parents
+			.iter()
+			.map(Features::from_element)
+			.collect::<Vec<Features>>();
+		let common = Features::intersect(&parent_features);
+		if !common.tag.is_empty() {
+			contexts.push(format!("{} > ", common.tag));
+			if !common.classes.is_empty() {
+				contexts.push(format!("{}.{} > ", common.tag, common.classes.join(".")));
+			}
+		}
+	}
+	if let Some(id) = common_ancestor_id(positives) {
+		contexts.push(format!("#{} ", id));
+	}
+	// validate the candidates, the candidates matched the same elements will keep the simplest one
+	let mut candidates: Vec<(SelectorCandidate, Elements, usize)> = Vec::new();
+	for context in &contexts {
+		for local in &locals {
+			let selector = format!("{}{}", context, local);
+			let (matched, positives, negatives) = match validate(&docs, &selector) {
+				Ok(validated) => validated,
+				_ => continue,
+			};
+			if positives == 0 {
+				continue;
+			}
+			let complexity = get_complexity(&selector);
+			let same_index = candidates
+				.iter()
+				.position(|(_, eles, _)| eles.length() == matched.length() && matched.is_all_in(eles));
+			if let Some(index) = same_index {
+				if candidates[index].2 <= complexity {
+					continue;
+				}
+				candidates.remove(index);
+			}
+			let candidate = SelectorCandidate {
+				selector,
+				matched: matched.length(),
+				positives,
+				negatives,
+			};
+			candidates.push((candidate, matched, complexity));
+		}
+	}
+	// rank by the matched examples, then the more precise and the simpler
+	candidates.sort_by(|(a, _, a_complexity), (b, _, b_complexity)| {
+		b.positives
+			.cmp(&a.positives)
+			.then(a.negatives.cmp(&b.negatives))
+			.then(a.matched.cmp(&b.matched))
+			.then(a_complexity.cmp(b_complexity))
+			.then(a.selector.cmp(&b.selector))
+	});
+	candidates
+		.into_iter()
+		.map(|(candidate, ..)| candidate)
+		.collect()
+}
@@ -12,7 +12,7 @@ mod text;
 pub use text::{BoxDynText, ITextTrait};
 // document trait
 mod document;
-pub use document::{IDocumentTrait, IErrorHandle, MaybeDoc};
+pub use document::{DocumentStats, IDocumentTrait, IErrorHandle, MaybeDoc, StatsItem};
 // uncare
 mod uncare;
 pub use uncare::{BoxDynUncareNode, IUncareNodeTrait};
@@ -24,3 +24,13 @@ cfg_feat_text! {
 // elements
 mod elements;
 pub use elements::Elements;
+// unique selector
+mod unique;
+pub use unique::UniqueSelectorOptions;
+// learn selectors from the examples
+mod induce;
+pub use induce::SelectorCandidate;
+// extract the values by the selectors with pseudo elements
+mod extract;
+// pierce into the nested documents
+mod nested;
@@ -0,0 +1,158 @@
+use super::extract::make_error;
+use super::Elements;
+use crate::mesdoc::error::{Error as IError, SelectorErrorKind};
+use crate::mesdoc::selector::CompiledSelector;
+use crate::mesdoc::utils::top_level_chars;
+use std::sync::Arc;
+
+// the combinator pierce into the nested documents
+const DEEP_COMBINATOR: &str = ">>>";
+
+// a selector in the selector list, splitted by the top level '>>>' with the start offsets
+pub(super) type DeepParts<'s> = Vec<(usize, &'s str)>;
+
+// split the selector list by the top level ',', then split each selector by the top level '>>>'
+// `None` if no deep combinator
+pub(super) fn split_deep(context: &str) -> Result<Option<Vec<DeepParts<'_>>>, IError> {
+	if !context.contains(DEEP_COMBINATOR) {
+		return Ok(None);
+	}
+	let mut selectors = Vec::with_capacity(1);
+	let mut parts = Vec::with_capacity(2);
+	let mut has_deep = false;
+	let mut start = 0;
+	for (index, ch) in top_level_chars(context) {
+		if index < start {
+			continue;
+		}
+		if ch == ',' {
+			end_selector(context, &mut selectors, &mut parts, start, index)?;
+			start = index + 1;
+			continue;
+		}
+		if !context[index..].starts_with(DEEP_COMBINATOR) {
+			continue;
+		}
+		let part = &context[start..index];
+		// the first selector can be empty, then pierce into the elements themselves
+		if !parts.is_empty() && part.trim().is_empty() {
+			return Err(make_error(
+				context,
+				index,
+				SelectorErrorKind::UnexpectedCombinator,
+			));
+		}
+		parts.push((start, part));
+		has_deep = true;
+		start = index + DEEP_COMBINATOR.len();
+	}
+	if !has_deep {
+		// all the '>>>' are in the quotes or the nested selectors
+		return Ok(None);
+	}
+	end_selector(context, &mut selectors, &mut parts, start, context.len())?;
+	Ok(Some(selectors))
+}
+
+// end the selector at the ',' or the end of the selector list
+fn end_selector<'s>(
+	context: &'s str,
+	selectors: &mut Vec<DeepParts<'s>>,
+	parts: &mut DeepParts<'s>,
+	start: usize,
+	end: usize,
+) -> Result<(), IError> {
+	let part = &context[start..end];
+	if part.trim().is_empty() {
+		// the selector after '>>>' is empty, or an empty selector in the list
+		let kind = match (parts.is_empty(), end == context.len()) {
+			(false, true) => SelectorErrorKind::UnexpectedEnd,
+			_ => SelectorErrorKind::EmptySelector,
+		};
+		return Err(make_error(context, end, kind));
+	}
+	parts.push((start, part));
+	selectors.push(std::mem::take(parts));
+	Ok(())
+}
+
+// compile the selectors pierce into the nested documents, the error offset is relative to the whole selector list
+fn compile_deep(
+	context: &str,
+	selectors: &[DeepParts],
+) -> Result<Vec<Vec<Option<Arc<CompiledSelector>>>>, IError> {
+	let mut result = Vec::with_capacity(selectors.len());
+	for parts in selectors {
+		let mut compiled = Vec::with_capacity(parts.len());
+		for &(start, selector) in parts {
+			if selector.trim().is_empty() {
+				compiled.push(None);
+				continue;
+			}
+			let selector = CompiledSelector::cached(selector).map_err(|err| match err {
+				IError::InvalidSelector {
+					kind,
+					offset,
+					token,
+					..
+				} => IError::InvalidSelector {
+					kind,
+					context: String::from(context),
+					offset: start + offset,
+					token,
+				},
+				err => err,
+			})?;
+			compiled.push(Some(selector));
+		}
+		result.push(compiled);
+	}
+	Ok(result)
+}
+
+// find the selector list in the elements and the nested documents
+// the elements in the current documents are in document order, then the elements in the nested documents in the order of the selectors
+// return the matched nodes and the nested documents
+pub(super) fn find_deep<'a>(
+	eles: &Elements<'a>,
+	context: &str,
+	selectors: &[DeepParts],
+) -> Result<(Elements<'a>, Vec<Elements<'a>>), IError> {
+	let selectors = compile_deep(context, selectors)?;
+	let mut finded = Elements::new();
+	let mut nested_finded = Elements::new();
+	let mut docs = Vec::new();
+	for compiled in &selectors {
+		let (first, rest) = compiled.split_first().expect("The parts must not empty");
+		// the first selector is empty, pierce into the elements themselves
+		let mut cur_finded = match first {
+			Some(selector) => eles.find_compiled(selector),
+			None => eles.cloned(),
+		};
+		if rest.is_empty() {
+			finded.get_mut_ref().extend(cur_finded);
+			continue;
+		}
+		for selector in rest.iter().flatten() {
+			let mut result = Elements::with_capacity(cur_finded.length());
+			for ele in cur_finded.get_ref() {
+				if let Some(doc) = ele.content_document() {
+					for ele in doc.find_compiled(selector).get_ref() {
+						result.push(ele.cloned());
+					}
+					docs.push(doc);
+				}
+			}
+			cur_finded = result;
+		}
+		// the nested documents may be shared by the selectors
+		for ele in cur_finded.get_ref() {
+			if !nested_finded.includes(ele) {
+				nested_finded.push(ele.cloned());
+			}
+		}
+	}
+	finded.sort_and_unique();
+	finded.get_mut_ref().extend(nested_finded);
+	Ok((finded, docs))
+}
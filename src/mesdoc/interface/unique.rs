@@ -0,0 +1,217 @@
+use super::{BoxDynElement, Elements, IAttrValue};
+use std::collections::VecDeque;
+
+/// The options of the `unique_selector` method
+#[derive(Debug, Clone)]
+pub struct UniqueSelectorOptions {
+	/// use the `id` of the elements first
+	pub prefer_id: bool,
+	/// use the classes of the elements
+	pub use_classes: bool,
+	/// the stable attributes will be tried in order, e.g. `data-testid`
+	pub attributes: Vec<String>,
+	/// skip the ids and classes those look generated by tools, e.g. `css-1x2y3z`, `jsx-2617892`
+	pub avoid_generated: bool,
+	/// the max count of the compound selectors
+	pub max_depth: usize,
+}
+
+impl Default for UniqueSelectorOptions {
+	fn default() -> Self {
+		UniqueSelectorOptions {
+			prefer_id: true,
+			use_classes: true,
+			attributes: vec![
+				String::from("data-testid"),
+				String::from("data-test"),
+				String::from("name"),
+			],
+			avoid_generated: true,
+			max_depth: 10,
+		}
+	}
+}
+
+// check if the id or class looks generated by tools
+pub(super) fn is_generated_name(name: &str) -> bool {
+	const PREFIXES: [&str; 5] = ["css-", "sc-", "jsx-", "svelte-", "ember"];
+	if PREFIXES.iter().any(|prefix| name.starts_with(prefix)) {
+		return true;
+	}
+	name.split(['-', '_']).any(|segment| {
+		let digits = segment.chars().filter(|ch| ch.is_ascii_digit()).count();
+		let letters = segment
+			.chars()
+			.filter(|ch| ch.is_ascii_alphabetic())
+			.count();
+		// a long number, or a hash mixed letters with digits
+		digits >= 3 || (digits >= 2 && letters >= 2 && segment.len() >= 5)
+	})
+}
+
+// translate the name to an identity of the selector, the special characters are escaped by '\'
+pub(super) fn to_identity(name: &str) -> Option<String> {
+	let first = name.chars().next()?;
+	if !(first.is_ascii_alphabetic() || first == '_') {
+		return None;
+	}
+	let mut identity = String::with_capacity(name.len());
+	for ch in name.chars() {
+		if !(ch.is_ascii_alphanumeric() || ch == '-' || ch == '_') {
+			identity.push('\\');
+		}
+		identity.push(ch);
+	}
+	Some(identity)
+}
+
+// quote the attribute value, the value with both quotes or escape character is not supported
+pub(super) fn quote_value(value: &str) -> Option<String> {
+	if value.contains('\\') {
+		return None;
+	}
+	match (value.contains('"'), value.contains('\'')) {
+		(false, _) => Some(format!("\"{}\"", value)),
+		(true, false) => Some(format!("'{}'", value)),
+		_ => None,
+	}
+}
+
+pub(super) fn get_attr_value(ele: &BoxDynElement, name: &str) -> Option<String> {
+	match ele.get_attribute(name) {
+		Some(IAttrValue::Value(value, _)) => Some(value),
+		_ => None,
+	}
+}
+
+pub(super) fn get_tag_name(ele: &BoxDynElement) -> String {
+	let tag_name = ele.tag_names().iter().collect::<String>();
+	to_identity(&tag_name).unwrap_or_else(|| String::from("*"))
+}
+
+// the selectors of the element itself, the preferred is in front
+fn local_candidates(ele: &BoxDynElement, options: &UniqueSelectorOptions) -> Vec<String> {
+	let tag_name = get_tag_name(ele);
+	let is_stable = |name: &str| !(options.avoid_generated && is_generated_name(name));
+	let mut candidates = Vec::with_capacity(5);
+	if options.prefer_id {
+		if let Some(id) = get_attr_value(ele, "id").filter(|id| !id.is_empty() && is_stable(id)) {
+			match to_identity(&id) {
+				Some(id) => candidates.push(format!("#{}", id)),
+				None => candidates.extend(quote_value(&id).map(|id| format!("[id={}]", id))),
+			}
+		}
+	}
+	for name in &options.attributes {
+		if let Some(value) = get_attr_value(ele, name).and_then(|value| quote_value(&value)) {
+			candidates.push(format!("{}[{}={}]", tag_name, name, value));
+		}
+	}
+	if options.use_classes {
+		if let Some(class_list) = get_attr_value(ele, "class") {
+			let classes = class_list
+				.split_ascii_whitespace()
+				.filter(|class| is_stable(class))
+				.filter_map(to_identity)
+				.collect::<Vec<String>>();
+			for class in &classes {
+				candidates.push(format!("{}.{}", tag_name, class));
+			}
+			if classes.len() > 1 {
+				candidates.push(format!("{}.{}", tag_name, classes.join(".")));
+			}
+		}
+	}
+	candidates.push(tag_name);
+	candidates
+}
+
+// the selector of the element unique in the siblings
+fn sibling_segment(ele: &BoxDynElement, parent: &BoxDynElement, candidates: &[String]) -> String {
+	let siblings = parent.children();
+	for candidate in candidates {
+		// skip the candidate can't be parsed, do not trigger the error handle of the document
+		if matches!(siblings.try_filter(candidate), Ok(matched) if matched.length() == 1) {
+			return candidate.clone();
+		}
+	}
+	let position = siblings
+		.get_ref()
+		.iter()
+		.position(|sibling| sibling.is(ele))
+		.expect("The element must be a child of the parent");
+	format!("{}:nth-child({})", get_tag_name(ele), position + 1)
+}
+
+pub(crate) fn unique_selector(
+	ele: &BoxDynElement,
+	options: &UniqueSelectorOptions,
+) -> Option<String> {
+	// the selector is checked from the root of the document
+	let mut root = ele.parent()?;
+	while let Some(parent) = root.parent() {
+		root = parent;
+	}
+	let root = Elements::with_node(&root);
+	let is_unique = |selector: &str| match root.try_find(selector) {
+		Ok(finded) => finded.length() == 1 && finded.get_ref()[0].is(ele),
+		Err(_) => false,
+	};
+	let mut segments: VecDeque<String> = VecDeque::with_capacity(options.max_depth);
+	let mut cur = ele.cloned();
+	while segments.len() < options.max_depth {
+		let parent = cur.parent()?;
+		let mut candidates = local_candidates(&cur, options);
+		// at last, the selector unique in the siblings
+		let segment = sibling_segment(&cur, &parent, &candidates);
+		if !candidates.contains(&segment) {
+			candidates.push(segment.clone());
+		}
+		for candidate in &candidates {
+			segments.push_front(candidate.clone());
+			let selector = segments
+				.iter()
+				.cloned()
+				.collect::<Vec<String>>()
+				.join(" > ");
+			segments.pop_front();
+			if is_unique(&selector) {
+				return Some(selector);
+			}
+		}
+		if parent.is_root_element() {
+			// the whole path from the root is not unique
+			return None;
+		}
+		segments.push_front(segment);
+		cur = parent;
+	}
+	None
+}
+
+pub(crate) fn absolute_xpath(ele: &BoxDynElement) -> String {
+	let mut steps: VecDeque<String> = VecDeque::with_capacity(5);
+	let mut cur = ele.cloned();
+	while let Some(parent) = cur.parent() {
+		let tag_names = cur.tag_names();
+		let tag_name = tag_names.iter().collect::<String>();
+		let same_names = parent
+			.children()
+			.get_ref()
+			.iter()
+			.filter(|sibling| sibling.tag_names() == tag_names)
+			.map(|sibling| sibling.cloned())
+			.collect::<Vec<BoxDynElement>>();
+		if same_names.len() > 1 {
+			let position = same_names
+				.iter()
+				.position(|sibling| sibling.is(&cur))
+				.expect("The element must be a child of the parent");
+			steps.push_front(format!("{}[{}]", tag_name, position + 1));
+		} else {
+			steps.push_front(tag_name);
+		}
+		cur = parent;
+	}
+	format!("/{}", steps.into_iter().collect::<Vec<String>>().join("/"))
+}
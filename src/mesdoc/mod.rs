@@ -6,6 +6,8 @@ pub mod rules;
 pub mod selector;
 // interface
 pub mod interface;
+// xpath
+pub mod xpath;
 // export error
 pub mod error;
 // utils for crate
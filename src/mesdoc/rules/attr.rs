@@ -1,89 +1,129 @@
 #![allow(clippy::or_fun_call)]
 
-use crate::mesdoc::constants::{NAME_SELECTOR_ATTR, PRIORITY_ATTR_SELECTOR};
+use crate::mesdoc::constants::{
+	NAME_SELECTOR_ATTR, NAME_SELECTOR_ATTR_NAMESPACE, NAME_SELECTOR_ATTR_REGEXP,
+	PRIORITY_ATTR_SELECTOR,
+};
 use crate::mesdoc::interface::IAttrValue;
-use crate::mesdoc::selector::rule::Matcher;
+use crate::mesdoc::selector::pattern::MatchedData;
+use crate::mesdoc::selector::pattern::RegExp;
+use crate::mesdoc::selector::rule::{
+	MatchAttrValueHandle, MatchEstimateHandle, MatchIndexHandle, Matcher,
+};
 use crate::mesdoc::selector::rule::{RuleDefItem, RuleItem};
 use crate::mesdoc::selector::MatchedQueue;
+use crate::mesdoc::utils::unescape_quoted;
+
+// the operator, value and flag of the attribute selector
+macro_rules! attr_value_pattern {
+	() => {
+		r##"{regexp#(?:([*^$~|!]?)=\s*(?:'((?:\\?+.)*?)'|([^\s\]'"<>/=`]+)|"((?:\\?+.)*?)")(?:\s*([iIsS])(?:\b|$))?)?#}"##
+	};
+}
+
 pub fn init(rules: &mut Vec<RuleItem>) {
+	// attribute value match the regex, e.g. [href=~/\/product\/\d+/i]
+	let rule = RuleDefItem(
+		NAME_SELECTOR_ATTR_REGEXP,
+		r##"[{spaces}{attr_key}{spaces}=~{spaces}{regexp_literal}{spaces}]"##,
+		PRIORITY_ATTR_SELECTOR,
+		Box::new(|data: MatchedQueue| {
+			let attr_key = data[2].chars.iter().collect::<String>();
+			let source = data[6]
+				.data
+				.get("source")
+				.expect("The regexp_literal pattern must have a source");
+			let rule = RegExp::get_regex(source).expect("The regexp_literal pattern must be valid");
+			let index_name = attr_key.clone();
+			let estimate_name = attr_key.to_ascii_lowercase();
+			Matcher {
+				one_handle: Some(Box::new(move |ele, _| match ele.get_attribute(&attr_key) {
+					Some(IAttrValue::Value(v, _)) => rule.is_match(&v),
+					_ => false,
+				})),
+				index_handle: Some(Box::new(move |doc| {
+					doc.get_elements_by_attribute_name(&index_name)
+				})),
+				estimate_handle: Some(Box::new(move |stats| {
+					stats.attrs.get(&estimate_name).copied().unwrap_or_default()
+				})),
+				..Default::default()
+			}
+		}),
+	);
+	rules.push(rule.into());
 	let rule = RuleDefItem(
 		NAME_SELECTOR_ATTR,
-		r##"[{spaces}{attr_key}{spaces}{regexp#(?:([*^$~|!]?)=\s*(?:'((?:\\?+.)*?)'|([^\s\]'"<>/=`]+)|"((?:\\?+.)*?)"))?#}{spaces}]"##,
+		concat!(
+			"[{spaces}{attr_key}{spaces}",
+			attr_value_pattern!(),
+			"{spaces}]"
+		),
 		PRIORITY_ATTR_SELECTOR,
 		Box::new(|data: MatchedQueue| {
 			let attr_key = data[2].chars.iter().collect::<String>();
-			let value_data = &data[4].data;
-			let attr_value = value_data
-				.get("2")
-				.or_else(|| value_data.get("3"))
-				.or_else(|| value_data.get("4"))
-				.copied();
-			let match_mode = value_data.get("1").copied().unwrap_or("");
-			let handle: Box<dyn Fn(&Option<IAttrValue>) -> bool> = if let Some(attr_value) = attr_value {
-				if attr_value.is_empty() && !matches!(match_mode, "" | "!" | "|") {
-					// empty attribute value, ^$*
-					Box::new(|_val: &Option<IAttrValue>| false)
+			let handle = make_value_handle(&data[4].data);
+			// the index can be used only if the elements without the attribute can't be matched
+			let (index_handle, estimate_handle): (Option<MatchIndexHandle>, Option<MatchEstimateHandle>) =
+				if handle(None) {
+					(None, None)
 				} else {
-					match match_mode {
-						// begin with value
-						"^" => Box::new(move |val: &Option<IAttrValue>| match val {
-							Some(IAttrValue::Value(v, _)) => v.starts_with(attr_value),
-							_ => false,
-						}),
-						// end with value
-						"$" => Box::new(move |val: &Option<IAttrValue>| match val {
-							Some(IAttrValue::Value(v, _)) => v.ends_with(attr_value),
-							_ => false,
-						}),
-						// contains value
-						"*" => Box::new(move |val: &Option<IAttrValue>| match val {
-							Some(IAttrValue::Value(v, _)) => v.contains(attr_value),
-							_ => false,
-						}),
-						// either equal to value or start with `value` and followed `-`
-						"|" => Box::new(move |val: &Option<IAttrValue>| match val {
-							Some(IAttrValue::Value(v, _)) => {
-								if v == attr_value {
-									return true;
-								}
-								let attr_value: String = format!("{}-", attr_value);
-								v.starts_with(&attr_value)
-							}
-							_ => attr_value.is_empty(),
-						}),
-						// in a value list that splitted by whitespaces
-						"~" => Box::new(move |val: &Option<IAttrValue>| match val {
-							Some(IAttrValue::Value(v, _)) => {
-								let split_v = v.split_ascii_whitespace();
-								for v in split_v {
-									if v == attr_value {
-										return true;
-									}
-								}
-								false
-							}
-							_ => false,
-						}),
-						// has a attribute and who's value not equal to setted value
-						"!" => Box::new(move |val: &Option<IAttrValue>| match val {
-							Some(IAttrValue::Value(v, _)) => attr_value != v,
-							_ => !attr_value.is_empty(),
-						}),
-						// equal to value
-						_ => Box::new(move |val: &Option<IAttrValue>| match val {
-							Some(IAttrValue::Value(v, _)) => v == attr_value,
-							_ => attr_value.is_empty(),
-						}),
-					}
-				}
-			} else {
-				// has the attribute name
-				Box::new(|val: &Option<IAttrValue>| val.is_some())
+					let index_name = attr_key.clone();
+					let estimate_name = attr_key.to_ascii_lowercase();
+					(
+						Some(Box::new(move |doc| {
+							doc.get_elements_by_attribute_name(&index_name)
+						})),
+						Some(Box::new(move |stats| {
+							stats.attrs.get(&estimate_name).copied().unwrap_or_default()
+						})),
+					)
+				};
+			Matcher {
+				one_handle: Some(Box::new(move |ele, _| handle(ele.get_attribute(&attr_key)))),
+				index_handle,
+				estimate_handle,
+				..Default::default()
+			}
+		}),
+	);
+	rules.push(rule.into());
+	// attribute with namespace, e.g. [xlink|href], [*|href], [|href]
+	let rule = RuleDefItem(
+		NAME_SELECTOR_ATTR_NAMESPACE,
+		concat!(
+			r##"[{spaces}{regexp#(?:(\*)|(xlink|xml|xmlns))?\|#}{attr_key}{spaces}"##,
+			attr_value_pattern!(),
+			"{spaces}]"
+		),
+		PRIORITY_ATTR_SELECTOR,
+		Box::new(|data: MatchedQueue| {
+			let local_name = data[3].chars.iter().collect::<String>();
+			let namespace_data = &data[2].data;
+			let any_namespace = namespace_data.get("1").is_some();
+			// the attributes in namespace are named as 'prefix:name' in html
+			let attr_key = match namespace_data.get("2") {
+				Some(prefix) => format!("{}:{}", prefix, local_name),
+				None => local_name.clone(),
 			};
+			let handle = make_value_handle(&data[5].data);
 			Matcher {
 				one_handle: Some(Box::new(move |ele, _| {
-					let val = ele.get_attribute(&attr_key);
-					handle(&val)
+					if !any_namespace {
+						return handle(ele.get_attribute(&attr_key));
+					}
+					// any namespace, check all the attributes who's local name is matched
+					let mut has_attr = false;
+					for name in ele.get_attribute_names() {
+						let cur_local_name = name.rsplit(':').next().unwrap_or("");
+						if cur_local_name.eq_ignore_ascii_case(&local_name) {
+							has_attr = true;
+							if handle(ele.get_attribute(&name)) {
+								return true;
+							}
+						}
+					}
+					!has_attr && handle(None)
 				})),
 				..Default::default()
 			}
@@ -91,3 +131,97 @@ pub fn init(rules: &mut Vec<RuleItem>) {
 	);
 	rules.push(rule.into());
 }
+
+type AttrValueHandle = Box<dyn Fn(Option<IAttrValue>) -> bool + Send + Sync>;
+
+// make the handle to check the attribute value by the operator and flag
+fn make_value_handle(value_data: &MatchedData) -> AttrValueHandle {
+	// the value in the quotes is unescaped
+	let attr_value = value_data
+		.get("2")
+		.or_else(|| value_data.get("4"))
+		.map(|value| unescape_quoted(value))
+		.or_else(|| value_data.get("3").cloned());
+	let match_mode = value_data.get("1").map(|mode| mode.as_str()).unwrap_or("");
+	// the flag 'i' means compare the value case-insensitively, 's' or no flag means case-sensitively
+	let ignore_case = matches!(
+		value_data.get("5").map(|flag| flag.as_str()),
+		Some("i") | Some("I")
+	);
+	let attr_value = attr_value.map(|value| {
+		if ignore_case {
+			value.to_ascii_lowercase()
+		} else {
+			value
+		}
+	});
+	let handle: MatchAttrValueHandle = if let Some(attr_value) = attr_value {
+		if attr_value.is_empty() && !matches!(match_mode, "" | "!" | "|") {
+			// empty attribute value, ^$*
+			Box::new(|_val: &Option<IAttrValue>| false)
+		} else {
+			match match_mode {
+				// begin with value
+				"^" => Box::new(move |val: &Option<IAttrValue>| match val {
+					Some(IAttrValue::Value(v, _)) => v.starts_with(&attr_value),
+					_ => false,
+				}),
+				// end with value
+				"$" => Box::new(move |val: &Option<IAttrValue>| match val {
+					Some(IAttrValue::Value(v, _)) => v.ends_with(&attr_value),
+					_ => false,
+				}),
+				// contains value
+				"*" => Box::new(move |val: &Option<IAttrValue>| match val {
+					Some(IAttrValue::Value(v, _)) => v.contains(&attr_value),
+					_ => false,
+				}),
+				// either equal to value or start with `value` and followed `-`
+				"|" => Box::new(move |val: &Option<IAttrValue>| match val {
+					Some(IAttrValue::Value(v, _)) => {
+						if *v == attr_value {
+							return true;
+						}
+						let attr_value: String = format!("{}-", attr_value);
+						v.starts_with(&attr_value)
+					}
+					_ => attr_value.is_empty(),
+				}),
+				// in a value list that splitted by whitespaces
+				"~" => Box::new(move |val: &Option<IAttrValue>| match val {
+					Some(IAttrValue::Value(v, _)) => {
+						let split_v = v.split_ascii_whitespace();
+						for v in split_v {
+							if v == attr_value {
+								return true;
+							}
+						}
+						false
+					}
+					_ => false,
+				}),
+				// has a attribute and who's value not equal to setted value
+				"!" => Box::new(move |val: &Option<IAttrValue>| match val {
+					Some(IAttrValue::Value(v, _)) => attr_value != *v,
+					_ => !attr_value.is_empty(),
+				}),
+				// equal to value
+				_ => Box::new(move |val: &Option<IAttrValue>| match val {
+					Some(IAttrValue::Value(v, _)) => *v == attr_value,
+					_ => attr_value.is_empty(),
+				}),
+			}
+		}
+	} else {
+		// has the attribute name
+		Box::new(|val: &Option<IAttrValue>| val.is_some())
+	};
+	Box::new(move |val: Option<IAttrValue>| {
+		if ignore_case {
+			if let Some(IAttrValue::Value(v, quote)) = val {
+				return handle(&Some(IAttrValue::Value(v.to_ascii_lowercase(), quote)));
+			}
+		}
+		handle(&val)
+	})
+}
@@ -13,6 +13,8 @@ pub fn init(rules: &mut Vec<RuleItem>) {
 		Box::new(|mut data: MatchedQueue| {
 			// class name parameter
 			let class_name = data.remove(1).chars;
+			let index_name = class_name.iter().collect::<String>();
+			let estimate_name = index_name.clone();
 			// matcher
 			Matcher {
 				one_handle: Some(Box::new(move |ele, _| -> bool {
@@ -22,6 +24,16 @@ pub fn init(rules: &mut Vec<RuleItem>) {
 					}
 					false
 				})),
+				index_handle: Some(Box::new(move |doc| {
+					doc.get_elements_by_class_name(&index_name)
+				})),
+				estimate_handle: Some(Box::new(move |stats| {
+					stats
+						.classes
+						.get(&estimate_name)
+						.copied()
+						.unwrap_or_default()
+				})),
 				..Default::default()
 			}
 		}),
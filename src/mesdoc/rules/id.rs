@@ -2,7 +2,7 @@ use crate::mesdoc::selector::rule::{Matcher, Rule, RuleItem};
 use crate::mesdoc::selector::MatchedQueue;
 use crate::mesdoc::{
 	constants::{NAME_SELECTOR_ID, PRIORITY_ID_SELECTOR},
-	interface::Elements,
+	interface::{Elements, StatsItem},
 };
 
 pub fn init(rules: &mut Vec<RuleItem>) {
@@ -42,6 +42,11 @@ pub fn init(rules: &mut Vec<RuleItem>) {
 						}
 						result
 					})),
+					// an id is unique in the document, but the count of its descendants is unknown
+					estimate_handle: Some(Box::new(|stats| StatsItem {
+						count: 1,
+						descendants: stats.elements,
+					})),
 					..Default::default()
 				}
 			}),
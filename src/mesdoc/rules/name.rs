@@ -1,19 +1,64 @@
-use crate::mesdoc::constants::{NAME_SELECTOR_NAME, PRIORITY_NAME_SELECTOR};
+use crate::mesdoc::constants::{
+	NAMESPACE_HTML, NAMESPACE_MATHML, NAMESPACE_SVG, NAME_SELECTOR_NAME,
+	NAME_SELECTOR_NAMESPACE_NAME, PRIORITY_NAME_SELECTOR,
+};
 use crate::mesdoc::selector::rule::{Matcher, RuleDefItem, RuleItem};
 use crate::mesdoc::selector::MatchedQueue;
 use crate::mesdoc::utils::is_equal_chars_ignore_case;
 
 pub fn init(rules: &mut Vec<RuleItem>) {
+	// name with namespace, e.g. `svg|rect`, `*|a`, must before the name rule
+	let rule = RuleDefItem(
+		NAME_SELECTOR_NAMESPACE_NAME,
+		r##"{regexp#(?:(\*)|(html|svg|math))\|(?:(\*)|([A-Za-z_][\w-]*))#}"##,
+		PRIORITY_NAME_SELECTOR,
+		Box::new(|data: MatchedQueue| {
+			let data = &data[0].data;
+			let any_namespace = data.get("1").is_some();
+			let namespace = data.get("2").map(|prefix| match prefix.as_str() {
+				"svg" => NAMESPACE_SVG,
+				"math" => NAMESPACE_MATHML,
+				_ => NAMESPACE_HTML,
+			});
+			let name = data.get("4").cloned();
+			Matcher {
+				one_handle: Some(Box::new(move |ele, _| {
+					let namespace_uri = ele.namespace_uri();
+					if !any_namespace && namespace != Some(namespace_uri) {
+						return false;
+					}
+					match &name {
+						// html elements ignore case, svg and mathml elements are case-sensitive
+						Some(name) if namespace_uri == NAMESPACE_HTML => {
+							name.eq_ignore_ascii_case(&ele.tag_names().iter().collect::<String>())
+						}
+						Some(name) => &ele.local_name() == name,
+						None => true,
+					}
+				})),
+				..Default::default()
+			}
+		}),
+	);
+	rules.push(rule.into());
 	let rule = RuleDefItem(
 		NAME_SELECTOR_NAME,
 		"{identity}",
 		PRIORITY_NAME_SELECTOR,
 		Box::new(|mut data: MatchedQueue| {
 			let name = data.remove(0).chars;
+			let index_name = name.iter().collect::<String>();
+			let estimate_name = index_name.to_ascii_lowercase();
 			Matcher {
 				one_handle: Some(Box::new(move |ele, _| {
 					is_equal_chars_ignore_case(&ele.tag_names(), &name)
 				})),
+				index_handle: Some(Box::new(move |doc| {
+					doc.get_elements_by_tag_name(&index_name)
+				})),
+				estimate_handle: Some(Box::new(move |stats| {
+					stats.tags.get(&estimate_name).copied().unwrap_or_default()
+				})),
 				..Default::default()
 			}
 		}),
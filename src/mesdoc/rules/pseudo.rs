@@ -1,13 +1,13 @@
 #![deny(clippy::print_stdout)]
 use crate::mesdoc::interface::{BoxDynElement, Elements, IAttrValue, IElementTrait, INodeType};
-use crate::mesdoc::selector::pattern::Nth;
+use crate::mesdoc::selector::pattern::{Nth, RegExp};
 use crate::mesdoc::selector::rule::{Matcher, Rule, RuleDefItem, RuleItem};
-use crate::mesdoc::selector::MatchedQueue;
+use crate::mesdoc::selector::{CompiledSelector, MatchedQueue};
 use crate::mesdoc::utils::{contains_chars, is_equal_chars};
 use crate::mesdoc::{
 	constants::{
-		DEF_NODES_LEN, PRIORITY_PSEUDO_SELECTOR, SELECTOR_ALIAS_NAME_HEADER, SELECTOR_ALIAS_NAME_INPUT,
-		SELECTOR_ALIAS_NAME_SUBMIT,
+		DEF_NODES_LEN, NAME_SELECTOR_SCOPE, PRIORITY_PSEUDO_SELECTOR, SELECTOR_ALIAS_NAME_HEADER,
+		SELECTOR_ALIAS_NAME_INPUT, SELECTOR_ALIAS_NAME_SUBMIT,
 	},
 	selector::rule::MatchSpecifiedHandle,
 };
@@ -15,7 +15,7 @@ use std::{cmp::Ordering, collections::VecDeque};
 use std::{collections::HashMap, ops::Range};
 const PRIORITY: u32 = PRIORITY_PSEUDO_SELECTOR;
 
-fn nth_index_to_number(index: &Option<&str>) -> isize {
+fn nth_index_to_number(index: Option<&str>) -> isize {
 	index
 		.expect("Nth's n and index must have one")
 		.parse::<isize>()
@@ -64,6 +64,24 @@ fn pseudo_root(rules: &mut Vec<RuleItem>) {
 	rules.push(rule.into());
 }
 
+/// pseudo selector ":scope"
+fn pseudo_scope(rules: &mut Vec<RuleItem>) {
+	let selector = NAME_SELECTOR_SCOPE;
+	let name = selector;
+	let rule = RuleDefItem(
+		name,
+		selector,
+		PRIORITY,
+		// the scope compound is splitted out by the selector parser and checked with the elements of the set,
+		// so only another ':scope' in the same selector reach here, it never matches
+		Box::new(|_| Matcher {
+			one_handle: Some(Box::new(|_, _| false)),
+			..Default::default()
+		}),
+	);
+	rules.push(rule.into());
+}
+
 /// pseudo selector ":empty"
 fn pseudo_empty(rules: &mut Vec<RuleItem>) {
 	// empty
@@ -332,8 +350,55 @@ fn handle_nth_child<'r>(
 	let finded = handle(eles, &data.range, allow_indexs, &childs);
 	result.get_mut_ref().extend(finded);
 }
+// make for 'nth-child','nth-last-child' with 'of S', only count the siblings matched the selector
+fn make_asc_or_desc_nth_child_of(
+	asc: bool,
+	n: Option<String>,
+	index: Option<String>,
+	selector: CompiledSelector,
+) -> Matcher {
+	let handle = make_asc_or_desc_nth_child_handle(asc);
+	Matcher {
+		all_handle: Some(Box::new(move |eles: &Elements, is_all| {
+			let mut result: Elements = Elements::with_capacity(DEF_NODES_LEN);
+			let eles = eles.filter_compiled(&selector);
+			if eles.is_empty() {
+				return result;
+			}
+			if is_all.is_none() {
+				group_siblings_then_done(
+					&eles,
+					|_| None,
+					|data: &mut SiblingsNodeData| {
+						let childs = data
+							.parent
+							.as_ref()
+							.expect("parent must set in callback")
+							.children()
+							.filter_compiled(&selector);
+						let allow_indexs =
+							Nth::get_allowed_indexs(n.as_deref(), index.as_deref(), childs.length());
+						if !allow_indexs.is_empty() {
+							let finded = handle(&eles, &data.range, &allow_indexs, &childs);
+							result.get_mut_ref().extend(finded);
+						}
+					},
+				);
+			} else {
+				let total = eles.length();
+				let range = 0..total;
+				let allow_indexs = Nth::get_allowed_indexs(n.as_deref(), index.as_deref(), total);
+				let finded = handle(&eles, &range, &allow_indexs, &eles);
+				result.get_mut_ref().extend(finded);
+			}
+			result
+		})),
+		..Default::default()
+	}
+}
+
 // make for 'nth-child','nth-last-child'
-fn make_asc_or_desc_nth_child(selector: &'static str, asc: bool) -> RuleDefItem {
+fn make_asc_or_desc_nth_child(selector: &'static str, asc: bool, has_of: bool) -> RuleDefItem {
 	let name = selector;
 	RuleDefItem(
 		name,
@@ -341,11 +406,18 @@ fn make_asc_or_desc_nth_child(selector: &'static str, asc: bool) -> RuleDefItem
 		PRIORITY,
 		Box::new(move |data: MatchedQueue| {
 			let nth_data = &data[2].data;
-			let n = nth_data.get("n").copied();
-			let index = nth_data.get("index").copied();
+			let n = nth_data.get("n").cloned();
+			let index = nth_data.get("index").cloned();
+			if has_of {
+				// the selector after 'of'
+				let selector = data[4].chars.iter().collect::<String>();
+				let selector = CompiledSelector::new(&selector)
+					.expect("The selector list after 'of' has been checked by the parser");
+				return make_asc_or_desc_nth_child_of(asc, n, index, selector);
+			}
 			let handle = make_asc_or_desc_nth_child_handle(asc);
 			let specified_handle = if n.is_none() {
-				let index = nth_index_to_number(&index);
+				let index = nth_index_to_number(index.as_deref());
 				Some(make_asc_or_desc_nth_child_specified(asc, index))
 			} else {
 				None
@@ -356,7 +428,13 @@ fn make_asc_or_desc_nth_child(selector: &'static str, asc: bool) -> RuleDefItem
 					if is_all.is_none() {
 						group_siblings_then_done(
 							eles,
-							|total: usize| Some(Nth::get_allowed_indexs(&n, &index, total)),
+							|total: usize| {
+								Some(Nth::get_allowed_indexs(
+									n.as_deref(),
+									index.as_deref(),
+									total,
+								))
+							},
 							|data: &mut SiblingsNodeData| {
 								handle_nth_child(data, eles, &mut result, &handle);
 							},
@@ -364,7 +442,7 @@ fn make_asc_or_desc_nth_child(selector: &'static str, asc: bool) -> RuleDefItem
 					} else {
 						let total = eles.length();
 						let range = 0..total;
-						let allow_indexs = Nth::get_allowed_indexs(&n, &index, total);
+						let allow_indexs = Nth::get_allowed_indexs(n.as_deref(), index.as_deref(), total);
 						let finded = handle(eles, &range, &allow_indexs, eles);
 						result.get_mut_ref().extend(finded);
 					}
@@ -378,13 +456,27 @@ fn make_asc_or_desc_nth_child(selector: &'static str, asc: bool) -> RuleDefItem
 }
 /// pseudo selector: `:nth-child`
 fn pseudo_nth_child(rules: &mut Vec<RuleItem>) {
-	let rule = make_asc_or_desc_nth_child(":nth-child({spaces}{nth}{spaces})", true);
+	let rule = make_asc_or_desc_nth_child(":nth-child({spaces}{nth}{spaces})", true, false);
+	rules.push(rule.into());
+	// `:nth-child(An+B of S)`
+	let rule = make_asc_or_desc_nth_child(
+		r##":nth-child({spaces}{nth}{regexp#\s+of\s+#}{selector}{spaces})"##,
+		true,
+		true,
+	);
 	rules.push(rule.into());
 }
 
 /// pseudo selector: `:nth-child`
 fn pseudo_nth_last_child(rules: &mut Vec<RuleItem>) {
-	let rule = make_asc_or_desc_nth_child(":nth-last-child({spaces}{nth}{spaces})", false);
+	let rule = make_asc_or_desc_nth_child(":nth-last-child({spaces}{nth}{spaces})", false, false);
+	rules.push(rule.into());
+	// `:nth-last-child(An+B of S)`
+	let rule = make_asc_or_desc_nth_child(
+		r##":nth-last-child({spaces}{nth}{regexp#\s+of\s+#}{selector}{spaces})"##,
+		false,
+		true,
+	);
 	rules.push(rule.into());
 }
 
@@ -604,10 +696,10 @@ fn make_asc_or_desc_nth_of_type(selector: &'static str, asc: bool) -> RuleDefIte
 		PRIORITY,
 		Box::new(move |mut data: MatchedQueue| {
 			let nth_data = data.remove(2).data;
-			let n = nth_data.get("n").copied();
-			let index = nth_data.get("index").copied();
+			let n = nth_data.get("n").cloned();
+			let index = nth_data.get("index").cloned();
 			let specified_handle = if n.is_none() {
-				let index = nth_index_to_number(&index);
+				let index = nth_index_to_number(index.as_deref());
 				Some(make_asc_or_desc_nth_of_type_specified(asc, index))
 			} else {
 				None
@@ -618,7 +710,13 @@ fn make_asc_or_desc_nth_of_type(selector: &'static str, asc: bool) -> RuleDefIte
 					if is_all.is_none() {
 						group_siblings_then_done(
 							eles,
-							|total: usize| Some(Nth::get_allowed_indexs(&n, &index, total)),
+							|total: usize| {
+								Some(Nth::get_allowed_indexs(
+									n.as_deref(),
+									index.as_deref(),
+									total,
+								))
+							},
 							|data: &mut SiblingsNodeData| {
 								handle_nth_of_type(asc, data, eles, &mut result);
 							},
@@ -626,7 +724,11 @@ fn make_asc_or_desc_nth_of_type(selector: &'static str, asc: bool) -> RuleDefIte
 					} else {
 						// is_all
 						let total = eles.length();
-						let allow_indexs = Some(Nth::get_allowed_indexs(&n, &index, total));
+						let allow_indexs = Some(Nth::get_allowed_indexs(
+							n.as_deref(),
+							index.as_deref(),
+							total,
+						));
 						let parent = if total > 0 {
 							eles.get(0).expect("length > 0").parent()
 						} else {
@@ -841,8 +943,40 @@ fn pseudo_not(rules: &mut Vec<RuleItem>) {
 		PRIORITY,
 		Box::new(|data: MatchedQueue| {
 			let selector = data[2].chars.iter().collect::<String>();
+			let selector =
+				CompiledSelector::new(&selector).expect("The selector list has been checked by the parser");
+			let positional = selector.selector.is_positional();
 			Matcher {
-				all_handle: Some(Box::new(move |eles: &Elements, _| eles.not(&selector))),
+				all_handle: Some(Box::new(move |eles: &Elements, _| {
+					eles.not_in_set(&selector)
+				})),
+				positional,
+				..Default::default()
+			}
+		}),
+	);
+	rules.push(rule.into());
+}
+
+/// pseudo selector: `:has`
+fn pseudo_has(rules: &mut Vec<RuleItem>) {
+	let name = ":has";
+	let selector = ":has({spaces}{selector}{spaces})";
+	let rule = RuleDefItem(
+		name,
+		selector,
+		PRIORITY,
+		Box::new(|data: MatchedQueue| {
+			// the relative selector, allow leading combinators such as '>', '+', '~'
+			let selector = data[2].chars.iter().collect::<String>();
+			let selector = CompiledSelector::new(&selector)
+				.expect("The relative selector list has been checked by the parser");
+			Matcher {
+				one_handle: Some(Box::new(move |ele, _| {
+					!Elements::with_node(&ele.cloned())
+						.find_compiled(&selector)
+						.is_empty()
+				})),
 				..Default::default()
 			}
 		}),
@@ -850,6 +984,40 @@ fn pseudo_not(rules: &mut Vec<RuleItem>) {
 	rules.push(rule.into());
 }
 
+// make a selector list matcher, `:is` and `:where` just differ in specificity
+fn make_matches_any(name: &'static str, selector: &'static str) -> RuleDefItem {
+	RuleDefItem(
+		name,
+		selector,
+		PRIORITY,
+		Box::new(|data: MatchedQueue| {
+			let selector = data[2].chars.iter().collect::<String>();
+			let selector =
+				CompiledSelector::new(&selector).expect("The selector list has been checked by the parser");
+			let positional = selector.selector.is_positional();
+			Matcher {
+				all_handle: Some(Box::new(move |eles: &Elements, _| {
+					eles.filter_in_set(&selector)
+				})),
+				positional,
+				..Default::default()
+			}
+		}),
+	)
+}
+
+/// pseudo selector: `:is`
+fn pseudo_is(rules: &mut Vec<RuleItem>) {
+	let rule = make_matches_any(":is", ":is({spaces}{selector}{spaces})");
+	rules.push(rule.into());
+}
+
+/// pseudo selector: `:where`
+fn pseudo_where(rules: &mut Vec<RuleItem>) {
+	let rule = make_matches_any(":where", ":where({spaces}{selector}{spaces})");
+	rules.push(rule.into());
+}
+
 /// pseudo selector: `:contains`
 fn pseudo_contains(rules: &mut Vec<RuleItem>) {
 	let name = ":contains";
@@ -859,15 +1027,8 @@ fn pseudo_contains(rules: &mut Vec<RuleItem>) {
 		selector,
 		PRIORITY,
 		Box::new(|mut data: MatchedQueue| {
-			let mut find_chars = data.remove(2).chars;
-			if !find_chars.is_empty() {
-				let first = find_chars[0];
-				let search = if first == '"' || first == '\'' {
-					find_chars.pop();
-					find_chars.split_off(1)
-				} else {
-					find_chars
-				};
+			let search = get_search_chars(data.remove(2).chars);
+			if !search.is_empty() {
 				Matcher {
 					one_handle: Some(Box::new(move |ele, _| {
 						let contents = ele.text_contents();
@@ -892,6 +1053,123 @@ fn pseudo_contains(rules: &mut Vec<RuleItem>) {
 	rules.push(rule.into());
 }
 
+// remove the quotes of the search text
+fn get_search_chars(mut find_chars: Vec<char>) -> Vec<char> {
+	match find_chars.first() {
+		Some('"') | Some('\'') => {
+			find_chars.pop();
+			find_chars.split_off(1)
+		}
+		_ => find_chars,
+	}
+}
+
+fn to_lowercase_chars(chars: &[char]) -> Vec<char> {
+	chars.iter().flat_map(|ch| ch.to_lowercase()).collect()
+}
+
+// the text of the direct text node children
+fn own_text_contents(ele: &dyn IElementTrait) -> Vec<char> {
+	let mut contents = Vec::new();
+	for node in ele.child_nodes() {
+		if matches!(node.node_type(), INodeType::Text) {
+			contents.extend(node.text_contents());
+		}
+	}
+	contents
+}
+
+/// pseudo selector: `:icontains`
+fn pseudo_icontains(rules: &mut Vec<RuleItem>) {
+	let name = ":icontains";
+	let selector = r##":icontains({spaces}{regexp#(?:'((?:\\?+.)*?)'|"((?:\\?+.)*?)"|([^)\s'"<>/=`]*))#}{spaces})"##;
+	let rule = RuleDefItem(
+		name,
+		selector,
+		PRIORITY,
+		Box::new(|mut data: MatchedQueue| {
+			let search = to_lowercase_chars(&get_search_chars(data.remove(2).chars));
+			Matcher {
+				one_handle: Some(Box::new(move |ele, _| {
+					search.is_empty() || contains_chars(&to_lowercase_chars(&ele.text_contents()), &search)
+				})),
+				..Default::default()
+			}
+		}),
+	);
+	rules.push(rule.into());
+}
+
+/// pseudo selector: `:text-equals`
+fn pseudo_text_equals(rules: &mut Vec<RuleItem>) {
+	let name = ":text-equals";
+	let selector = r##":text-equals({spaces}{regexp#(?:'((?:\\?+.)*?)'|"((?:\\?+.)*?)"|([^)\s'"<>/=`]*))#}{spaces})"##;
+	let rule = RuleDefItem(
+		name,
+		selector,
+		PRIORITY,
+		Box::new(|mut data: MatchedQueue| {
+			let search = get_search_chars(data.remove(2).chars)
+				.iter()
+				.collect::<String>();
+			Matcher {
+				one_handle: Some(Box::new(move |ele, _| {
+					// compare with the trimmed text
+					ele.text_contents().iter().collect::<String>().trim() == search.trim()
+				})),
+				..Default::default()
+			}
+		}),
+	);
+	rules.push(rule.into());
+}
+
+/// pseudo selector: `:text-matches`
+fn pseudo_text_matches(rules: &mut Vec<RuleItem>) {
+	let name = ":text-matches";
+	let selector = r##":text-matches({spaces}{regexp_literal}{spaces})"##;
+	let rule = RuleDefItem(
+		name,
+		selector,
+		PRIORITY,
+		Box::new(|data: MatchedQueue| {
+			let source = data[2]
+				.data
+				.get("source")
+				.expect("The regexp_literal pattern must have a source");
+			let rule = RegExp::get_regex(source).expect("The regexp_literal pattern must be valid");
+			Matcher {
+				one_handle: Some(Box::new(move |ele, _| {
+					rule.is_match(&ele.text_contents().iter().collect::<String>())
+				})),
+				..Default::default()
+			}
+		}),
+	);
+	rules.push(rule.into());
+}
+
+/// pseudo selector: `:own-text`
+fn pseudo_own_text(rules: &mut Vec<RuleItem>) {
+	let name = ":own-text";
+	let selector = r##":own-text({spaces}{regexp#(?:'((?:\\?+.)*?)'|"((?:\\?+.)*?)"|([^)\s'"<>/=`]*))#}{spaces})"##;
+	let rule = RuleDefItem(
+		name,
+		selector,
+		PRIORITY,
+		Box::new(|mut data: MatchedQueue| {
+			let search = get_search_chars(data.remove(2).chars);
+			Matcher {
+				one_handle: Some(Box::new(move |ele, _| {
+					search.is_empty() || contains_chars(&own_text_contents(ele), &search)
+				})),
+				..Default::default()
+			}
+		}),
+	);
+	rules.push(rule.into());
+}
+
 // -----------jquery selectors----------
 
 /// pseudo selector: `:checked`
@@ -992,6 +1270,460 @@ fn pseudo_checked(rules: &mut Vec<RuleItem>) {
 	rules.push(rule.into());
 }
 
+// the lowercase attribute value, a boolean attribute will be an empty string
+fn get_attribute_lower(ele: &dyn IElementTrait, name: &str) -> Option<String> {
+	ele.get_attribute(name).map(|value| match value {
+		IAttrValue::Value(v, _) => v.trim().to_ascii_lowercase(),
+		IAttrValue::True => String::new(),
+	})
+}
+
+// the input type, missing or invalid type will be 'text'
+fn get_input_type(ele: &dyn IElementTrait) -> String {
+	const INPUT_TYPES: [&str; 22] = [
+		"hidden",
+		"text",
+		"search",
+		"tel",
+		"url",
+		"email",
+		"password",
+		"date",
+		"month",
+		"week",
+		"time",
+		"datetime-local",
+		"number",
+		"range",
+		"color",
+		"checkbox",
+		"radio",
+		"file",
+		"submit",
+		"image",
+		"reset",
+		"button",
+	];
+	match get_attribute_lower(ele, "type") {
+		Some(input_type) if INPUT_TYPES.contains(&input_type.as_str()) => input_type,
+		_ => String::from("text"),
+	}
+}
+
+// check if the element is a submit button
+fn is_submit_button(ele: &dyn IElementTrait) -> bool {
+	match ele.tag_name().as_str() {
+		"INPUT" => matches!(get_input_type(ele).as_str(), "submit" | "image"),
+		"BUTTON" => !matches!(
+			get_attribute_lower(ele, "type").as_deref(),
+			Some("reset") | Some("button")
+		),
+		_ => false,
+	}
+}
+
+// the nearest ancestor form element
+fn get_form_owner<'b>(ele: &dyn IElementTrait) -> Option<BoxDynElement<'b>> {
+	let mut parent = ele.parent();
+	while let Some(cur) = parent {
+		if cur.tag_name() == "FORM" {
+			return Some(cur);
+		}
+		parent = cur.parent();
+	}
+	None
+}
+
+// check if the element is the same one
+fn is_same_element(ele: &dyn IElementTrait, other: &Option<BoxDynElement>) -> bool {
+	match other {
+		Some(other) => ele.is(other),
+		None => false,
+	}
+}
+
+// loop the descendants in tree order, stop when the handle return true
+fn some_descendant(
+	ele: &dyn IElementTrait,
+	handle: &mut dyn FnMut(&BoxDynElement) -> bool,
+) -> bool {
+	for child in ele.children().get_ref() {
+		if handle(child) || some_descendant(child.as_ref(), handle) {
+			return true;
+		}
+	}
+	false
+}
+
+// the fieldset's first legend child will not be disabled by the fieldset
+fn is_first_legend(ele: &dyn IElementTrait) -> bool {
+	if ele.tag_name() != "LEGEND" {
+		return false;
+	}
+	let mut prev = ele.previous_element_sibling();
+	while let Some(prev_ele) = prev {
+		if prev_ele.tag_name() == "LEGEND" {
+			return false;
+		}
+		prev = prev_ele.previous_element_sibling();
+	}
+	true
+}
+
+// check if the element is a descendant of a disabled fieldset
+fn is_in_disabled_fieldset(ele: &dyn IElementTrait) -> bool {
+	let mut parent = ele.parent();
+	let mut child = ele.cloned();
+	while let Some(cur) = parent {
+		if cur.tag_name() == "FIELDSET"
+			&& cur.has_attribute("disabled")
+			&& !is_first_legend(child.as_ref())
+		{
+			return true;
+		}
+		parent = cur.parent();
+		child = cur;
+	}
+	false
+}
+
+// check if the element is disabled
+fn is_disabled(ele: &dyn IElementTrait) -> bool {
+	match ele.tag_name().as_str() {
+		"BUTTON" | "INPUT" | "SELECT" | "TEXTAREA" | "FIELDSET" => {
+			ele.has_attribute("disabled") || is_in_disabled_fieldset(ele)
+		}
+		"OPTGROUP" => ele.has_attribute("disabled"),
+		"OPTION" => {
+			ele.has_attribute("disabled")
+				|| matches!(ele.parent(), Some(parent) if parent.tag_name() == "OPTGROUP" && parent.has_attribute("disabled"))
+		}
+		_ => false,
+	}
+}
+
+// check if the element is enabled
+fn is_enabled(ele: &dyn IElementTrait) -> bool {
+	matches!(
+		ele.tag_name().as_str(),
+		"BUTTON" | "INPUT" | "SELECT" | "TEXTAREA" | "FIELDSET" | "OPTGROUP" | "OPTION"
+	) && !is_disabled(ele)
+}
+
+// check if the element is required
+fn is_required(ele: &dyn IElementTrait) -> bool {
+	match ele.tag_name().as_str() {
+		"INPUT" => {
+			!matches!(
+				get_input_type(ele).as_str(),
+				"hidden" | "range" | "color" | "submit" | "image" | "reset" | "button"
+			) && ele.has_attribute("required")
+		}
+		"SELECT" | "TEXTAREA" => ele.has_attribute("required"),
+		_ => false,
+	}
+}
+
+// check if the element is optional
+fn is_optional(ele: &dyn IElementTrait) -> bool {
+	matches!(ele.tag_name().as_str(), "INPUT" | "SELECT" | "TEXTAREA") && !is_required(ele)
+}
+
+// check if the element is editable by the 'contenteditable' attribute
+fn is_content_editable(ele: &dyn IElementTrait) -> bool {
+	let mut cur = Some(ele.cloned());
+	while let Some(cur_ele) = cur {
+		if cur_ele.is_root_element() {
+			break;
+		}
+		match get_attribute_lower(cur_ele.as_ref(), "contenteditable").as_deref() {
+			Some("") | Some("true") | Some("plaintext-only") => return true,
+			Some("false") => return false,
+			// invalid value, inherit from the parent
+			_ => {}
+		}
+		cur = cur_ele.parent();
+	}
+	false
+}
+
+// check if the element is writable
+fn is_read_write(ele: &dyn IElementTrait) -> bool {
+	match ele.tag_name().as_str() {
+		"INPUT" => {
+			matches!(
+				get_input_type(ele).as_str(),
+				"text"
+					| "search"
+					| "tel"
+					| "url"
+					| "email"
+					| "password"
+					| "date"
+					| "month"
+					| "week"
+					| "time"
+					| "datetime-local"
+					| "number"
+			) && !ele.has_attribute("readonly")
+				&& !is_disabled(ele)
+		}
+		"TEXTAREA" => !ele.has_attribute("readonly") && !is_disabled(ele),
+		_ => is_content_editable(ele),
+	}
+}
+
+// check if the element is read only
+fn is_read_only(ele: &dyn IElementTrait) -> bool {
+	!is_read_write(ele)
+}
+
+// check if the element is showing the placeholder
+fn is_placeholder_shown(ele: &dyn IElementTrait) -> bool {
+	if !ele.has_attribute("placeholder") {
+		return false;
+	}
+	match ele.tag_name().as_str() {
+		"INPUT" => {
+			matches!(
+				get_input_type(ele).as_str(),
+				"text" | "search" | "tel" | "url" | "email" | "password" | "number"
+			) && match ele.get_attribute("value") {
+				Some(IAttrValue::Value(v, _)) => v.is_empty(),
+				_ => true,
+			}
+		}
+		"TEXTAREA" => ele.text_contents().is_empty(),
+		_ => false,
+	}
+}
+
+// check if the element is a default option
+fn is_default(ele: &dyn IElementTrait) -> bool {
+	match ele.tag_name().as_str() {
+		"INPUT" if matches!(get_input_type(ele).as_str(), "checkbox" | "radio") => {
+			ele.has_attribute("checked")
+		}
+		"OPTION" => ele.has_attribute("selected"),
+		_ => {
+			// the first submit button of the form
+			if !is_submit_button(ele) {
+				return false;
+			}
+			if let Some(form) = get_form_owner(ele) {
+				let mut default_button = None;
+				some_descendant(form.as_ref(), &mut |child| {
+					if is_submit_button(child.as_ref()) {
+						default_button = Some(child.cloned());
+						return true;
+					}
+					false
+				});
+				return is_same_element(ele, &default_button);
+			}
+			false
+		}
+	}
+}
+
+// check if the element is in indeterminate state
+fn is_indeterminate(ele: &dyn IElementTrait) -> bool {
+	match ele.tag_name().as_str() {
+		"INPUT" if get_input_type(ele) == "radio" => {
+			if ele.has_attribute("checked") {
+				return false;
+			}
+			let name = match ele.get_attribute("name") {
+				Some(IAttrValue::Value(name, _)) if !name.is_empty() => name,
+				// a radio without name is a group itself
+				_ => return true,
+			};
+			let form = get_form_owner(ele);
+			let scope = match &form {
+				Some(form) => Some(form.cloned()),
+				None => ele.root_element(),
+			};
+			if let Some(scope) = scope {
+				// check if any radio in the same group is checked
+				let has_checked = some_descendant(scope.as_ref(), &mut |child| {
+					child.tag_name() == "INPUT"
+						&& get_input_type(child.as_ref()) == "radio"
+						&& child.has_attribute("checked")
+						&& matches!(child.get_attribute("name"), Some(IAttrValue::Value(v, _)) if v == name)
+						&& match (&form, get_form_owner(child.as_ref())) {
+							(Some(form), Some(child_form)) => form.is(&child_form),
+							(None, None) => true,
+							_ => false,
+						}
+				});
+				return !has_checked;
+			}
+			true
+		}
+		"PROGRESS" => !ele.has_attribute("value"),
+		_ => false,
+	}
+}
+
+/// pseudo selectors for form states:
+/// `:enabled`,`:disabled`,`:required`,`:optional`,`:read-only`,`:read-write`,
+/// `:placeholder-shown`,`:default`,`:indeterminate`
+fn pseudo_form_states(rules: &mut Vec<RuleItem>) {
+	type FormStateHandle = fn(&dyn IElementTrait) -> bool;
+	let states: [(&'static str, FormStateHandle); 9] = [
+		(":enabled", is_enabled),
+		(":disabled", is_disabled),
+		(":required", is_required),
+		(":optional", is_optional),
+		(":read-only", is_read_only),
+		(":read-write", is_read_write),
+		(":placeholder-shown", is_placeholder_shown),
+		(":default", is_default),
+		(":indeterminate", is_indeterminate),
+	];
+	for (selector, handle) in states.iter().copied() {
+		let name = selector;
+		let rule = RuleDefItem(
+			name,
+			selector,
+			PRIORITY,
+			Box::new(move |_| Matcher {
+				one_handle: Some(Box::new(move |ele, _| handle(ele))),
+				..Default::default()
+			}),
+		);
+		rules.push(rule.into());
+	}
+}
+
+// the index of the positional selectors, the overflow index will be the max or min value
+fn get_positional_index(data: &MatchedQueue) -> isize {
+	let index = data[2]
+		.data
+		.get("1")
+		.expect("Positional selector must have an index");
+	index.parse::<isize>().unwrap_or(if index.starts_with('-') {
+		isize::MIN
+	} else {
+		isize::MAX
+	})
+}
+
+// make a positional matcher, the handle get the allowed indexs by the total of the matched set
+fn make_positional_matcher<F>(get_indexs: F) -> Matcher
+where
+	F: Fn(isize) -> Range<isize> + Send + Sync + 'static,
+{
+	Matcher {
+		all_handle: Some(Box::new(move |eles: &Elements, _| {
+			let total = eles.length();
+			let Range { start, end } = get_indexs(total as isize);
+			let start = start.max(0) as usize;
+			let end = end.min(total as isize);
+			let mut result = Elements::with_capacity(DEF_NODES_LEN);
+			if end > 0 {
+				for ele in eles.get_ref().iter().take(end as usize).skip(start) {
+					result.push(ele.cloned());
+				}
+			}
+			result
+		})),
+		positional: true,
+		..Default::default()
+	}
+}
+
+// like jquery, the negative index count from the end, and the index is limited to the total
+fn to_positional_index(index: isize, total: isize) -> isize {
+	if index < 0 {
+		index + total
+	} else {
+		index.min(total)
+	}
+}
+
+/// pseudo selector: `:first`, `:last`
+fn pseudo_first_last(rules: &mut Vec<RuleItem>) {
+	let rule = RuleDefItem(
+		":first",
+		":first",
+		PRIORITY,
+		Box::new(|_| make_positional_matcher(|_| 0..1)),
+	);
+	rules.push(rule.into());
+	let rule = RuleDefItem(
+		":last",
+		":last",
+		PRIORITY,
+		Box::new(|_| make_positional_matcher(|total| total - 1..total)),
+	);
+	rules.push(rule.into());
+}
+
+/// pseudo selector: `:even`, `:odd`
+fn pseudo_even_odd(rules: &mut Vec<RuleItem>) {
+	for (selector, start) in [(":even", 0), (":odd", 1)].iter().copied() {
+		let rule = RuleDefItem(
+			selector,
+			selector,
+			PRIORITY,
+			Box::new(move |_| Matcher {
+				all_handle: Some(Box::new(move |eles: &Elements, _| {
+					let mut result = Elements::with_capacity(eles.length() / 2 + 1);
+					for ele in eles.get_ref().iter().skip(start).step_by(2) {
+						result.push(ele.cloned());
+					}
+					result
+				})),
+				positional: true,
+				..Default::default()
+			}),
+		);
+		rules.push(rule.into());
+	}
+}
+
+/// pseudo selector: `:eq`, `:gt`, `:lt`
+fn pseudo_eq_gt_lt(rules: &mut Vec<RuleItem>) {
+	let rule = RuleDefItem(
+		":eq",
+		r##":eq({spaces}{regexp#([-+]?\d+)#}{spaces})"##,
+		PRIORITY,
+		Box::new(|data: MatchedQueue| {
+			let index = get_positional_index(&data);
+			make_positional_matcher(move |total| {
+				let index = if index < 0 { index + total } else { index };
+				if index < 0 {
+					0..0
+				} else {
+					index..index.saturating_add(1)
+				}
+			})
+		}),
+	);
+	rules.push(rule.into());
+	let rule = RuleDefItem(
+		":gt",
+		r##":gt({spaces}{regexp#([-+]?\d+)#}{spaces})"##,
+		PRIORITY,
+		Box::new(|data: MatchedQueue| {
+			let index = get_positional_index(&data);
+			make_positional_matcher(move |total| to_positional_index(index, total) + 1..total)
+		}),
+	);
+	rules.push(rule.into());
+	let rule = RuleDefItem(
+		":lt",
+		r##":lt({spaces}{regexp#([-+]?\d+)#}{spaces})"##,
+		PRIORITY,
+		Box::new(|data: MatchedQueue| {
+			let index = get_positional_index(&data);
+			make_positional_matcher(move |total| 0..to_positional_index(index, total))
+		}),
+	);
+	rules.push(rule.into());
+}
+
 /// pseudo selector: `:header`
 fn pseudo_alias_header(rules: &mut Vec<RuleItem>) {
 	let (selector, alias) = SELECTOR_ALIAS_NAME_HEADER;
@@ -1033,6 +1765,7 @@ fn pseudo_alias_submit(rules: &mut Vec<RuleItem>) {
 
 pub fn init(rules: &mut Vec<RuleItem>) {
 	pseudo_root(rules);
+	pseudo_scope(rules);
 	pseudo_empty(rules);
 	// :first-child, :last-child
 	pseudo_first_child(rules);
@@ -1052,11 +1785,29 @@ pub fn init(rules: &mut Vec<RuleItem>) {
 	pseudo_only_of_type(rules);
 	// :not
 	pseudo_not(rules);
+	// :has
+	pseudo_has(rules);
+	// :is, :where
+	pseudo_is(rules);
+	pseudo_where(rules);
 	// :contains
 	pseudo_contains(rules);
+	// :icontains, :text-equals, :text-matches, :own-text
+	pseudo_icontains(rules);
+	pseudo_text_equals(rules);
+	pseudo_text_matches(rules);
+	pseudo_own_text(rules);
 	// ---- jquery selectors -----
 	// :checked
 	pseudo_checked(rules);
+	// :enabled, :disabled, :required, :optional, :read-only, :read-write
+	// :placeholder-shown, :default, :indeterminate
+	pseudo_form_states(rules);
+	// :first, :last, :even, :odd, :eq, :gt, :lt
+	// must after the :first-child, :last-child...
+	pseudo_first_last(rules);
+	pseudo_even_odd(rules);
+	pseudo_eq_gt_lt(rules);
 	// :header alias
 	pseudo_alias_header(rules);
 	// :input alias
@@ -0,0 +1,372 @@
+use super::{Combinator, ParsedSegment, Selector};
+use crate::mesdoc::constants::{
+	NAME_SELECTOR_ALL, NAME_SELECTOR_ATTR, NAME_SELECTOR_ATTR_NAMESPACE, NAME_SELECTOR_ATTR_REGEXP,
+	NAME_SELECTOR_CLASS, NAME_SELECTOR_ID, NAME_SELECTOR_NAME, NAME_SELECTOR_NAMESPACE_NAME,
+};
+use crate::mesdoc::error::Error;
+use crate::mesdoc::utils::unescape_quoted;
+use std::{fmt, ops::Add, str::FromStr};
+
+/// The css specificity `(a, b, c)` of a selector.
+///
+/// `a` is the count of id selectors, `b` is the count of class, attribute and pseudo-class selectors, `c` is the count of type selectors.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct Specificity(pub u32, pub u32, pub u32);
+
+impl Add for Specificity {
+	type Output = Specificity;
+	fn add(self, other: Specificity) -> Specificity {
+		Specificity(self.0 + other.0, self.1 + other.1, self.2 + other.2)
+	}
+}
+
+/// A selector list splitted by ','
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct SelectorList {
+	pub selectors: Vec<ComplexSelector>,
+}
+
+/// Compound selectors joined by the combinators.
+///
+/// The combinator of the first compound selector is `ChildrenAll`, or the leading combinator of a relative selector such as `> li` in `:has(> li)`; the others are the combinators before the compound selectors.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct ComplexSelector {
+	pub compounds: Vec<(Combinator, CompoundSelector)>,
+}
+
+/// Simple selectors without combinators, e.g. `a.link[href]`
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct CompoundSelector {
+	pub simples: Vec<SimpleSelector>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub enum SimpleSelector {
+	/// `*`, `svg|*`, the namespace `*` means any namespace
+	Universal { namespace: Option<String> },
+	/// `div`, `svg|rect`
+	Type {
+		namespace: Option<String>,
+		name: String,
+	},
+	/// `#id`
+	Id(String),
+	/// `.class`
+	Class(String),
+	/// `[name]`, `[xlink|href^="#"]`, the namespace `""` means no namespace, e.g. `[|href]`
+	Attribute {
+		namespace: Option<String>,
+		name: String,
+		// the operator, e.g. `=`, `^=`, `=~`
+		operator: Option<String>,
+		// the value without quotes, or the regexp literal of the `=~` operator
+		value: Option<String>,
+		// the flag `i` or `s`
+		flag: Option<char>,
+	},
+	/// `:first-child`, `:not(a)`, the name is lowercase and without the colon
+	PseudoClass {
+		name: String,
+		argument: Option<PseudoArgument>,
+	},
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub enum PseudoArgument {
+	/// the selector list of `:not()`, `:is()`, `:has()` and so on
+	Selectors(SelectorList),
+	/// the `An+B` formula and the `of S` selector list of `:nth-child()` and so on
+	Nth {
+		formula: String,
+		of: Option<SelectorList>,
+	},
+	/// the other arguments as written, e.g. `'text'` of `:contains('text')`
+	Raw(String),
+}
+
+impl FromStr for SelectorList {
+	type Err = Error;
+	fn from_str(selector: &str) -> Result<Self, Self::Err> {
+		crate::mesdoc::init();
+		let groups = Selector::parse_groups(selector)?;
+		let mut selectors = Vec::with_capacity(groups.len());
+		for group in groups {
+			let mut compounds = Vec::with_capacity(group.len());
+			for rules in group {
+				let comb = rules[0].comb;
+				let mut simples = Vec::with_capacity(rules.len());
+				for segment in rules {
+					simples.push(SimpleSelector::from_segment(segment)?);
+				}
+				compounds.push((comb, CompoundSelector { simples }));
+			}
+			selectors.push(ComplexSelector { compounds });
+		}
+		Ok(SelectorList { selectors })
+	}
+}
+
+impl SelectorList {
+	/// The max specificity of the selectors, same as the `:is()` pseudo-class.
+	pub fn specificity(&self) -> Specificity {
+		self
+			.selectors
+			.iter()
+			.map(|selector| selector.specificity())
+			.max()
+			.unwrap_or_default()
+	}
+}
+
+impl ComplexSelector {
+	pub fn specificity(&self) -> Specificity {
+		self
+			.compounds
+			.iter()
+			.fold(Specificity::default(), |total, (_, compound)| {
+				total + compound.specificity()
+			})
+	}
+}
+
+impl CompoundSelector {
+	pub fn specificity(&self) -> Specificity {
+		self
+			.simples
+			.iter()
+			.fold(Specificity::default(), |total, simple| {
+				total + simple.specificity()
+			})
+	}
+}
+
+impl SimpleSelector {
+	fn from_segment(segment: ParsedSegment) -> Result<Self, Error> {
+		let ParsedSegment {
+			name,
+			matched,
+			chars,
+			..
+		} = segment;
+		let text = || chars[1..].iter().collect::<String>();
+		let get = |index: usize, key: &str| matched[index].data.get(key).cloned();
+		let namespace = |index: usize| {
+			let data = &matched[index].data;
+			Some(match (data.get("1"), data.get("2")) {
+				(Some(_), _) => String::from("*"),
+				(_, Some(prefix)) => prefix.clone(),
+				_ => String::new(),
+			})
+		};
+		Ok(match name {
+			NAME_SELECTOR_ALL => SimpleSelector::Universal { namespace: None },
+			// the html type names are case-insensitive, the names in the other namespaces are kept
+			NAME_SELECTOR_NAME => SimpleSelector::Type {
+				namespace: None,
+				name: chars.iter().collect::<String>().to_ascii_lowercase(),
+			},
+			NAME_SELECTOR_NAMESPACE_NAME => match get(0, "4") {
+				Some(name) => {
+					let namespace = namespace(0);
+					let name = match namespace.as_deref() {
+						Some("html") => name.to_ascii_lowercase(),
+						_ => name,
+					};
+					SimpleSelector::Type { namespace, name }
+				}
+				None => SimpleSelector::Universal {
+					namespace: namespace(0),
+				},
+			},
+			NAME_SELECTOR_ID => SimpleSelector::Id(text()),
+			NAME_SELECTOR_CLASS => SimpleSelector::Class(text()),
+			NAME_SELECTOR_ATTR_REGEXP => SimpleSelector::Attribute {
+				namespace: None,
+				name: matched[2].chars.iter().collect(),
+				operator: Some(String::from("=~")),
+				value: Some(matched[6].chars.iter().collect()),
+				flag: None,
+			},
+			NAME_SELECTOR_ATTR | NAME_SELECTOR_ATTR_NAMESPACE => {
+				let (namespace, key_index) = if name == NAME_SELECTOR_ATTR {
+					(None, 2)
+				} else {
+					(namespace(2), 3)
+				};
+				let value_index = key_index + 2;
+				SimpleSelector::Attribute {
+					namespace,
+					name: matched[key_index].chars.iter().collect(),
+					operator: get(value_index, "1").map(|op| format!("{}=", op)),
+					// the value in the quotes is unescaped
+					value: get(value_index, "2")
+						.or_else(|| get(value_index, "4"))
+						.map(|value| unescape_quoted(&value))
+						.or_else(|| get(value_index, "3")),
+					flag: get(value_index, "5")
+						.and_then(|flag| flag.chars().next())
+						.map(|flag| flag.to_ascii_lowercase()),
+				}
+			}
+			_ => {
+				// the pseudo selectors, include the registered
+				let context = chars.iter().collect::<String>();
+				let context = context.trim_start_matches(':');
+				let (name, raw) = match context.find('(') {
+					Some(index) => (
+						&context[..index],
+						Some(context[index + 1..context.len() - 1].trim()),
+					),
+					None => (context, None),
+				};
+				let selectors = matched
+					.iter()
+					.find(|m| m.name == "selector")
+					.map(|m| m.chars.iter().collect::<String>().parse::<SelectorList>())
+					.transpose()?;
+				let formula = matched.iter().find(|m| m.name == "nth").map(|m| {
+					m.chars
+						.iter()
+						.filter(|ch| !ch.is_ascii_whitespace())
+						.collect::<String>()
+						.to_ascii_lowercase()
+				});
+				let argument = match (formula, selectors) {
+					(Some(formula), of) => Some(PseudoArgument::Nth { formula, of }),
+					(None, Some(selectors)) => Some(PseudoArgument::Selectors(selectors)),
+					(None, None) => raw.map(|raw| PseudoArgument::Raw(String::from(raw))),
+				};
+				SimpleSelector::PseudoClass {
+					name: name.to_ascii_lowercase(),
+					argument,
+				}
+			}
+		})
+	}
+	pub fn specificity(&self) -> Specificity {
+		match self {
+			SimpleSelector::Universal { .. } => Specificity(0, 0, 0),
+			SimpleSelector::Type { .. } => Specificity(0, 0, 1),
+			SimpleSelector::Id(_) => Specificity(1, 0, 0),
+			SimpleSelector::Class(_) | SimpleSelector::Attribute { .. } => Specificity(0, 1, 0),
+			SimpleSelector::PseudoClass { name, argument } => match (name.as_str(), argument) {
+				("where", _) => Specificity(0, 0, 0),
+				// the specificity of the most specific selector in the argument
+				("is", Some(PseudoArgument::Selectors(selectors)))
+				| ("not", Some(PseudoArgument::Selectors(selectors)))
+				| ("has", Some(PseudoArgument::Selectors(selectors))) => selectors.specificity(),
+				(_, Some(PseudoArgument::Nth { of: Some(of), .. })) => {
+					Specificity(0, 1, 0) + of.specificity()
+				}
+				_ => Specificity(0, 1, 0),
+			},
+		}
+	}
+}
+
+impl fmt::Display for SelectorList {
+	fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+		for (index, selector) in self.selectors.iter().enumerate() {
+			if index > 0 {
+				f.write_str(", ")?;
+			}
+			selector.fmt(f)?;
+		}
+		Ok(())
+	}
+}
+
+impl fmt::Display for ComplexSelector {
+	fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+		for (index, (comb, compound)) in self.compounds.iter().enumerate() {
+			let comb = match comb {
+				Combinator::Children => ">",
+				Combinator::NextAll => "~",
+				Combinator::Next => "+",
+				_ => "",
+			};
+			match (index, comb) {
+				(0, "") => {}
+				(0, comb) => write!(f, "{} ", comb)?,
+				(_, "") => f.write_str(" ")?,
+				(_, comb) => write!(f, " {} ", comb)?,
+			}
+			compound.fmt(f)?;
+		}
+		Ok(())
+	}
+}
+
+impl fmt::Display for CompoundSelector {
+	fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+		for simple in &self.simples {
+			simple.fmt(f)?;
+		}
+		Ok(())
+	}
+}
+
+impl fmt::Display for SimpleSelector {
+	fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+		let write_namespace = |f: &mut fmt::Formatter<'_>, namespace: &Option<String>| match namespace {
+			Some(namespace) => write!(f, "{}|", namespace),
+			None => Ok(()),
+		};
+		match self {
+			SimpleSelector::Universal { namespace } => {
+				write_namespace(f, namespace)?;
+				f.write_str("*")
+			}
+			SimpleSelector::Type { namespace, name } => {
+				write_namespace(f, namespace)?;
+				f.write_str(name)
+			}
+			SimpleSelector::Id(id) => write!(f, "#{}", id),
+			SimpleSelector::Class(class) => write!(f, ".{}", class),
+			SimpleSelector::Attribute {
+				namespace,
+				name,
+				operator,
+				value,
+				flag,
+			} => {
+				f.write_str("[")?;
+				write_namespace(f, namespace)?;
+				f.write_str(name)?;
+				if let (Some(operator), Some(value)) = (operator, value) {
+					if operator == "=~" {
+						write!(f, "{}{}", operator, value)?;
+					} else {
+						// escape the quote and the backslashes in the value
+						let quote = if value.contains('"') { '\'' } else { '"' };
+						write!(f, "{}{}", operator, quote)?;
+						for ch in value.chars() {
+							if ch == quote || ch == '\\' {
+								f.write_str("\\")?;
+							}
+							write!(f, "{}", ch)?;
+						}
+						write!(f, "{}", quote)?;
+					}
+				}
+				if let Some(flag) = flag {
+					write!(f, " {}", flag)?;
+				}
+				f.write_str("]")
+			}
+			SimpleSelector::PseudoClass { name, argument } => {
+				write!(f, ":{}", name)?;
+				match argument {
+					Some(PseudoArgument::Selectors(selectors)) => write!(f, "({})", selectors),
+					Some(PseudoArgument::Nth {
+						formula,
+						of: Some(of),
+					}) => write!(f, "({} of {})", formula, of),
+					Some(PseudoArgument::Nth { formula, .. }) => write!(f, "({})", formula),
+					Some(PseudoArgument::Raw(raw)) => write!(f, "({})", raw),
+					None => Ok(()),
+				}
+			}
+		}
+	}
+}
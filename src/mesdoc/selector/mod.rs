@@ -1,20 +1,30 @@
+pub mod ast;
 pub mod pattern;
+mod plan;
 pub mod rule;
+pub use self::plan::{QueryPlan, QueryStrategy};
 use self::{pattern::BoxDynPattern, rule::Matcher};
-use crate::mesdoc::{constants::NAME_SELECTOR_ALL, error::Error};
+use crate::mesdoc::{
+	constants::{NAME_SELECTOR_ALL, NAME_SELECTOR_SCOPE},
+	error::{Error, SelectorErrorKind},
+};
 use lazy_static::lazy_static;
 pub use pattern::MatchedQueue;
 use pattern::{exec, Matched};
 use rule::{Rule, RULES};
 use std::{
+	collections::HashMap,
+	convert::TryFrom,
+	fmt,
 	str::FromStr,
 	sync::{Arc, Mutex},
 };
 
 lazy_static! {
-	static ref SPLITTER: Mutex<Vec<BoxDynPattern>> =
-		Mutex::new(Rule::get_queues(r##"{regexp#(\s*[>,~+]\s*|\s+)#}"##));
+	static ref SPLITTER: Vec<BoxDynPattern> =
+		Rule::get_queues(r##"{regexp#(\s*[>,~+]\s*|\s+)#}"##).expect("The splitter rule must be valid");
 	static ref ALL_RULE: Mutex<Option<Arc<Rule>>> = Mutex::new(None);
+	static ref SELECTOR_CACHE: Mutex<SelectorCache> = Mutex::new(SelectorCache::default());
 }
 #[derive(Debug, Clone, Copy, Eq, PartialEq, Hash)]
 pub enum Combinator {
@@ -41,16 +51,24 @@ pub enum Combinator {
 }
 
 // change string to combinator
-impl From<&str> for Combinator {
-	fn from(comb: &str) -> Self {
+impl TryFrom<&str> for Combinator {
+	type Error = Error;
+	fn try_from(comb: &str) -> Result<Self, Self::Error> {
 		use Combinator::*;
-		match comb {
+		Ok(match comb {
 			"" => ChildrenAll,
 			">" => Children,
 			"~" => NextAll,
 			"+" => Next,
-			_ => panic!("Not supported combinator string '{}'", comb),
-		}
+			_ => {
+				return Err(Error::InvalidSelector {
+					kind: SelectorErrorKind::UnexpectedCombinator,
+					context: String::from(comb),
+					offset: 0,
+					token: String::from(comb),
+				})
+			}
+		})
 	}
 }
 
@@ -62,17 +80,74 @@ impl Combinator {
 			Children => Parent,
 			NextAll => PrevAll,
 			Next => Prev,
+			ParentAll => ChildrenAll,
+			Parent => Children,
+			PrevAll => NextAll,
+			Prev => Next,
+			Siblings => Siblings,
 			Chain => Chain,
-			_ => panic!("Not supported combinator reverse for '{:?}'", self),
 		}
 	}
 }
 
+// the combinator pierce into the nested documents, supported by `find` and `extract`
+const DEEP_COMBINATOR: [char; 3] = ['>', '>', '>'];
+
+// the byte offset of the char index
+fn byte_offset(chars: &[char], index: usize) -> usize {
+	chars[..index].iter().map(|ch| ch.len_utf8()).sum()
+}
+
+// the token begin at the chars, a combinator or the chars before the whitespaces and the combinators
+fn get_token(chars: &[char]) -> String {
+	let is_combinator = |ch: &char| matches!(ch, ',' | '>' | '+' | '~');
+	match chars.first() {
+		_ if chars.starts_with(&DEEP_COMBINATOR) => DEEP_COMBINATOR.iter().collect(),
+		Some(ch) if is_combinator(ch) => ch.to_string(),
+		_ => chars
+			.iter()
+			.take_while(|ch| !(ch.is_ascii_whitespace() || is_combinator(ch)))
+			.collect(),
+	}
+}
+
 pub type SelectorSegment = (Matcher, Combinator);
 #[derive(Default, Debug)]
 pub struct QueryProcess {
-	pub should_in: Option<SelectorGroupsItem>,
+	// when lookup, the query before the index should be checked by the elements finded from the index
+	pub lookup_index: Option<usize>,
 	pub query: SelectorGroupsItem,
+	// the selector before ':scope' and the scope itself, the query is relative to the scope elements
+	pub scope: Option<Selector>,
+	// the selector used by the filter methods for the relative query, the query can begin from any element
+	pub anchored: Option<Selector>,
+	// the query planner can begin with any compound, only the `find` selectors without positional matchers
+	pub use_lookup: bool,
+}
+
+impl QueryProcess {
+	// split the query into lookup and query by the strategy
+	pub fn should_in(
+		&self,
+		strategy: &QueryStrategy,
+	) -> (Option<&[Vec<SelectorSegment>]>, &[Vec<SelectorSegment>]) {
+		if let QueryStrategy::BottomUp { start } = *strategy {
+			let (should_in, query) = self.query.split_at(start);
+			return (Some(should_in), query);
+		}
+		(None, &self.query)
+	}
+	// check if the query begin with a combinator, such as '> a', '+ a'
+	pub fn is_relative(&self) -> bool {
+		matches!(self.query.first(), Some(rules) if rules[0].1 != Combinator::ChildrenAll)
+	}
+	// check if the query has positional matchers
+	pub fn is_positional(&self) -> bool {
+		self
+			.query
+			.iter()
+			.any(|rules| rules.iter().any(|(matcher, _)| matcher.positional))
+	}
 }
 
 #[derive(Default, Debug)]
@@ -82,57 +157,214 @@ pub struct Selector {
 
 type SelectorGroupsItem = Vec<Vec<SelectorSegment>>;
 type SelectorGroups = Vec<SelectorGroupsItem>;
+
+// a segment of the selector matched by a rule
+#[derive(Clone)]
+pub(crate) struct ParsedSegment {
+	pub name: &'static str,
+	pub rule: Arc<Rule>,
+	pub matched: MatchedQueue,
+	pub comb: Combinator,
+	// the chars of the segment in the selector
+	pub chars: Vec<char>,
+}
+pub(crate) type ParsedGroups = Vec<Vec<Vec<ParsedSegment>>>;
 impl Selector {
 	pub fn new() -> Self {
 		Selector {
 			process: Vec::with_capacity(1),
 		}
 	}
+	// check if any query process has positional matchers
+	pub fn is_positional(&self) -> bool {
+		self.process.iter().any(|p| p.is_positional())
+	}
+	// make a parse error at the char index of the selector, the leading whitespaces are skipped
+	fn make_error(chars: &[char], index: usize, kind: SelectorErrorKind) -> Error {
+		let index = index
+			+ chars[index..]
+				.iter()
+				.take_while(|ch| ch.is_ascii_whitespace())
+				.count();
+		Error::InvalidSelector {
+			kind,
+			context: chars.iter().collect(),
+			offset: byte_offset(chars, index),
+			token: get_token(&chars[index..]),
+		}
+	}
+	// check the nested selector, the error offset will be relative to the whole selector
+	fn check_nested(chars: &[char], start: usize, nested: &[char]) -> Result<(), Error> {
+		if nested.iter().all(|ch| ch.is_ascii_whitespace()) {
+			return Err(Selector::make_error(
+				chars,
+				start,
+				SelectorErrorKind::EmptySelector,
+			));
+		}
+		let nested = nested.iter().collect::<String>();
+		match Selector::from_str(&nested, false) {
+			Err(Error::InvalidSelector {
+				kind,
+				offset,
+				token,
+				..
+			}) => Err(Error::InvalidSelector {
+				kind,
+				context: chars.iter().collect(),
+				offset: byte_offset(chars, start) + offset,
+				token,
+			}),
+			result => result.map(|_| ()),
+		}
+	}
 	pub fn from_str(context: &str, use_lookup: bool) -> Result<Self, Error> {
+		let mut selector = Selector::new();
+		let groups = Selector::parse_groups(context)?;
+		if !groups.is_empty() {
+			let mut scopes = Vec::with_capacity(groups.len());
+			let mut anchors = Vec::with_capacity(groups.len());
+			let groups: SelectorGroups = groups
+				.into_iter()
+				.map(|group| {
+					anchors.push(Selector::split_anchored(&group));
+					let (scope, group) = Selector::split_scope(group);
+					scopes.push(scope);
+					group
+				})
+				.collect();
+			// optimize groups to query process
+			selector.optimize(groups, use_lookup);
+			let to_selector = |group| {
+				let mut selector = Selector::new();
+				selector.optimize(vec![group], false);
+				selector
+			};
+			for ((process, scope), anchored) in selector.process.iter_mut().zip(scopes).zip(anchors) {
+				process.scope = scope.map(to_selector);
+				process.anchored = anchored.map(to_selector);
+			}
+		}
+		Ok(selector)
+	}
+	// make the matchers of the rules
+	fn make_rules(rules: Vec<ParsedSegment>) -> Vec<SelectorSegment> {
+		rules
+			.into_iter()
+			.map(|segment| (segment.rule.make(segment.matched), segment.comb))
+			.collect()
+	}
+	// split the selector at the first compound with ':scope', the ':scope' itself is removed
+	fn split_scope(
+		mut group: Vec<Vec<ParsedSegment>>,
+	) -> (Option<SelectorGroupsItem>, SelectorGroupsItem) {
+		let scope_index = group.iter().position(|rules| {
+			rules
+				.iter()
+				.any(|segment| segment.name == NAME_SELECTOR_SCOPE)
+		});
+		let scope_index = match scope_index {
+			Some(index) => index,
+			None => return (None, group.into_iter().map(Selector::make_rules).collect()),
+		};
+		let query = group.split_off(scope_index + 1);
+		let compound = group.pop().expect("The scope compound must exist");
+		let comb = compound[0].comb;
+		let compound = compound
+			.into_iter()
+			.filter(|segment| segment.name != NAME_SELECTOR_SCOPE)
+			.collect::<Vec<ParsedSegment>>();
+		let mut rules = Selector::make_rules(compound);
+		// the scope compound without other rules is same as '*'
+		match rules.first_mut() {
+			Some(first) => first.1 = comb,
+			None => rules.push(Selector::make_comb_all(comb)),
+		}
+		let mut scope: SelectorGroupsItem = group.into_iter().map(Selector::make_rules).collect();
+		scope.push(rules);
+		(
+			Some(scope),
+			query.into_iter().map(Selector::make_rules).collect(),
+		)
+	}
+	// join the scope and the relative query, an element matches if it can be found by the query from any element
+	// e.g. '> li' is same as '* > li', but ':scope' without the query after it is the element itself
+	fn split_anchored(group: &[Vec<ParsedSegment>]) -> Option<SelectorGroupsItem> {
+		let is_relative =
+			matches!(group.first(), Some(rules) if rules[0].comb != Combinator::ChildrenAll);
+		let mut anchored = match Selector::split_scope(group.to_vec()) {
+			(Some(_), query) if query.is_empty() => return None,
+			(Some(mut scope), query) => {
+				scope.extend(query);
+				scope
+			}
+			(None, query) if is_relative => query,
+			_ => return None,
+		};
+		if anchored[0][0].1 != Combinator::ChildrenAll {
+			anchored.insert(0, vec![Selector::make_comb_all(Combinator::ChildrenAll)]);
+		}
+		Some(anchored)
+	}
+	// parse the selector to the matched rule segments, splitted by ',' and the combinators
+	pub(crate) fn parse_groups(context: &str) -> Result<ParsedGroups, Error> {
 		let chars: Vec<char> = context.chars().collect();
 		let total_len = chars.len();
-		let mut selector = Selector::new();
+		let mut groups: ParsedGroups = Vec::new();
 		if total_len > 0 {
 			let mut index: usize = 0;
 			let mut comb = Combinator::ChildrenAll;
 			let mut prev_in = PrevInSelector::Begin;
 			let mut last_in = prev_in;
-			let mut groups: SelectorGroups = Vec::new();
-			let splitter = SPLITTER.lock().unwrap();
-			let rules = RULES.lock().unwrap();
+			let splitter = &SPLITTER;
+			// clone the rules so the lock is released, then the matcher factories can parse nested selectors
+			let rules = RULES.lock().unwrap().clone();
 			Selector::add_group(&mut groups);
 			while index < total_len {
 				let next_chars = &chars[index..];
+				// the deep combinator can only be used in the methods pierce into the nested documents
+				let spaces = next_chars
+					.iter()
+					.take_while(|ch| ch.is_ascii_whitespace())
+					.count();
+				if next_chars[spaces..].starts_with(&DEEP_COMBINATOR) {
+					return Err(Selector::make_error(
+						&chars,
+						index + spaces,
+						SelectorErrorKind::Unsupported,
+					));
+				}
 				// first check if combinator
-				if let Some((matched, len, _)) = Rule::exec_queues(&splitter, next_chars) {
+				if let Some((matched, len, _)) = Rule::exec_queues(splitter, next_chars) {
 					let op = matched[0].chars.iter().collect::<String>();
 					let op = op.trim();
 					if prev_in == PrevInSelector::Splitter {
 						// wrong multiple combinator
-						return Err(Error::InvalidSelector {
-							context: String::from(context),
-							reason: format!(
-								"Wrong combinator '{}' at index {}",
-								matched[0].chars.iter().collect::<String>(),
-								index
-							),
-						});
+						return Err(Selector::make_error(
+							&chars,
+							index,
+							SelectorErrorKind::UnexpectedCombinator,
+						));
 					}
-					// find the match
-					index += len;
 					// set combinator
 					if op == "," {
 						if prev_in != PrevInSelector::Selector {
-							return Err(Error::InvalidSelector {
-								context: String::from(context),
-								reason: format!("Wrong empty selector before ',' at index  {}", index),
-							});
+							return Err(Selector::make_error(
+								&chars,
+								index,
+								SelectorErrorKind::EmptySelector,
+							));
 						}
+						index += len;
 						Selector::add_group(&mut groups);
 						comb = Combinator::ChildrenAll;
-					} else {
-						comb = Combinator::from(op);
+						// a new selector begin, allow a leading combinator in relative selectors
+						prev_in = PrevInSelector::Begin;
+						last_in = PrevInSelector::Splitter;
+						continue;
 					}
+					comb = Combinator::try_from(op)?;
+					index += len;
 					// set prev is splitter
 					if op.is_empty() {
 						last_in = prev_in;
@@ -153,61 +385,69 @@ impl Selector {
 					last_in = prev_in;
 				}
 				let mut finded = false;
-				for (_, r) in rules.iter() {
+				let start = index;
+				for (name, r) in rules.iter() {
 					if let Some((mut matched, len, queue_num)) = r.exec(next_chars) {
-						// find the rule
-						index += len;
 						let queues = &r.queues;
 						if queue_num == queues.len() {
-							// push to selector
-							Selector::add_group_item(&mut groups, (r.make(matched), comb), is_new_item);
+							// find the rule, push to selector
+							index += len;
 							finded = true;
 						} else if queues[queue_num].is_nested() {
 							// nested selector
+							index += len;
 							let (len, nested_matched) = Selector::parse_until(
-								&chars[index..],
+								&chars,
+								index,
 								&queues[queue_num + 1..],
 								&rules,
-								&splitter,
+								splitter,
 								0,
 							)?;
+							Selector::check_nested(&chars, index, &nested_matched[0].chars)?;
 							index += len;
 							matched.extend(nested_matched);
-							Selector::add_group_item(&mut groups, (r.make(matched), comb), is_new_item);
 							finded = true;
 						}
-						break;
+						if finded {
+							let segment = ParsedSegment {
+								name,
+								rule: Arc::clone(r),
+								matched,
+								comb,
+								chars: chars[start..index].to_vec(),
+							};
+							Selector::add_group_item(&mut groups, segment, is_new_item);
+							break;
+						}
+						// only partial matched, try the next rule
 					}
 				}
 				if !finded {
 					// no splitter, no selector rule
-					return Err(Error::InvalidSelector {
-						context: String::from(context),
-						reason: format!(
-							"Unrecognized selector '{}' at index {}",
-							next_chars.iter().collect::<String>(),
-							index
-						),
-					});
+					return Err(Selector::make_error(
+						&chars,
+						index,
+						SelectorErrorKind::UnexpectedToken,
+					));
 				}
 			}
 			if last_in != PrevInSelector::Selector {
-				return Err(Error::InvalidSelector {
-					context: String::from(context),
-					reason: String::from("Wrong selector rule at last"),
-				});
+				return Err(Selector::make_error(
+					&chars,
+					total_len,
+					SelectorErrorKind::UnexpectedEnd,
+				));
 			}
-			// optimize groups to query process
-			selector.optimize(groups, use_lookup);
 		}
-		Ok(selector)
+		Ok(groups)
 	}
 	// add a selector group, splitted by ','
-	fn add_group(groups: &mut SelectorGroups) {
+	fn add_group(groups: &mut ParsedGroups) {
 		groups.push(Vec::with_capacity(2));
 	}
 	// add a selector group item
-	fn add_group_item(groups: &mut SelectorGroups, item: SelectorSegment, is_new: bool) {
+	fn add_group_item(groups: &mut ParsedGroups, item: ParsedSegment, is_new: bool) {
 		if let Some(last_group) = groups.last_mut() {
 			if is_new {
 				last_group.push(vec![item]);
@@ -223,9 +463,13 @@ impl Selector {
 			// first optimize the chain selectors, the rule who's priority is bigger will apply first
 			let mut max_index: usize = 0;
 			let mut max_priority: u32 = 0;
+			// positional matchers are relative to the matched set, so keep the order
+			let is_positional = group
+				.iter()
+				.any(|r| r.iter().any(|(matcher, _)| matcher.positional));
 			for (index, r) in group.iter_mut().enumerate() {
 				let mut total_priority = 0;
-				if r.len() > 1 {
+				if r.len() > 1 && !is_positional {
 					let chain_comb = r[0].1;
 					r.sort_by(|a, b| b.0.priority.partial_cmp(&a.0.priority).unwrap());
 					let mut now_first = &mut r[0];
@@ -238,7 +482,7 @@ impl Selector {
 						}
 					}
 				}
-				if use_lookup {
+				if use_lookup && !is_positional {
 					total_priority = r.iter().map(|p| p.0.priority).sum();
 					if total_priority > max_priority {
 						max_priority = total_priority;
@@ -253,15 +497,22 @@ impl Selector {
 					Combinator::Children | Combinator::ChildrenAll
 				);
 				if is_child {
-					let query = group.split_off(max_index);
-					let should_in = Some(group);
-					process.push(QueryProcess { should_in, query });
+					process.push(QueryProcess {
+						lookup_index: Some(max_index),
+						query: group,
+						scope: None,
+						anchored: None,
+						use_lookup: true,
+					});
 					continue;
 				}
 			}
 			process.push(QueryProcess {
-				should_in: None,
+				lookup_index: None,
 				query: group,
+				scope: None,
+				anchored: None,
+				use_lookup: use_lookup && !is_positional,
 			});
 		}
 		self.process = process;
@@ -269,11 +520,7 @@ impl Selector {
 	// change the combinator
 	pub fn head_combinator(&mut self, comb: Combinator) {
 		for p in &mut self.process {
-			let v = if let Some(should_in) = &mut p.should_in {
-				should_in
-			} else {
-				&mut p.query
-			};
+			let v = &mut p.query;
 			if let Some(rule) = v.get_mut(0) {
 				let first_comb = rule[0].1;
 				match first_comb {
@@ -281,6 +528,7 @@ impl Selector {
 					_ => {
 						let segment = Selector::make_comb_all(comb);
 						v.insert(0, vec![segment]);
+						p.lookup_index = p.lookup_index.map(|index| index + 1);
 					}
 				};
 			}
@@ -306,24 +554,29 @@ impl Selector {
 	pub fn from_segment(segment: SelectorSegment) -> Self {
 		let process = QueryProcess {
 			query: vec![vec![segment]],
-			should_in: None,
+			lookup_index: None,
+			scope: None,
+			anchored: None,
+			use_lookup: false,
 		};
 		Selector {
 			process: vec![process],
 		}
 	}
-	// parse until
+	// parse the nested selector begin at the start index, until the patterns matched
 	pub fn parse_until(
-		chars: &[char],
+		source: &[char],
+		start: usize,
 		until: &[BoxDynPattern],
 		rules: &[(&str, Arc<Rule>)],
 		splitter: &[BoxDynPattern],
 		level: usize,
 	) -> Result<(usize, MatchedQueue), Error> {
+		let chars = &source[start..];
 		let mut index = 0;
-		let total = chars.len();
 		let mut matched: MatchedQueue = Vec::with_capacity(until.len() + 1);
-		while index < total {
+		// also check the until patterns at the end, so an unclosed nested selector is an error
+		loop {
 			let next_chars = &chars[index..];
 			if let Some((_, len, _)) = Rule::exec_queues(splitter, next_chars) {
 				index += len;
@@ -333,22 +586,27 @@ impl Selector {
 			for (_, r) in rules.iter() {
 				if let Some((_, len, queue_num)) = r.exec(next_chars) {
 					let queues = &r.queues;
-					// find the rule
-					index += len;
 					if queue_num == queues.len() {
+						// find the rule
+						index += len;
 						// push to selector
 						finded = true;
-					} else {
+					} else if queues[queue_num].is_nested() {
+						index += len;
 						let (nest_count, _) = Selector::parse_until(
-							&chars[index..],
+							source,
+							start + index,
 							&queues[queue_num + 1..],
 							rules,
 							splitter,
 							level + 1,
 						)?;
 						index += nest_count;
+						finded = true;
+					}
+					if finded {
+						break;
 					}
-					break;
 				}
 			}
 			if !finded {
@@ -362,11 +620,13 @@ impl Selector {
 				if !until.is_empty() {
 					let (util_matched, count, queue_num, _) = exec(until, &chars[index..]);
 					if queue_num != until.len() {
-						let context = chars[index..].iter().collect::<String>();
-						return Err(Error::InvalidSelector {
-							context,
-							reason: format!("Nested selector parse error at index {}", index),
-						});
+						let is_end = chars[index..].iter().all(|ch| ch.is_ascii_whitespace());
+						let kind = if is_end {
+							SelectorErrorKind::UnexpectedEnd
+						} else {
+							SelectorErrorKind::UnexpectedToken
+						};
+						return Err(Selector::make_error(source, start + index, kind));
 					} else {
 						index += count;
 						if level == 0 {
@@ -395,20 +655,121 @@ impl FromStr for Selector {
 	}
 }
 
+/// A parsed selector that can be reused by the `*_compiled` methods.
+pub struct CompiledSelector {
+	context: String,
+	pub(crate) selector: Selector,
+}
+
+impl CompiledSelector {
+	/// parse the selector
+	pub fn new(context: &str) -> Result<Self, Error> {
+		let selector = Selector::from_str(context, true)?;
+		Ok(CompiledSelector {
+			context: String::from(context),
+			selector,
+		})
+	}
+	/// the selector string
+	pub fn as_str(&self) -> &str {
+		&self.context
+	}
+	// get the selector from the cache, or parse it when not cached
+	pub(crate) fn cached(context: &str) -> Result<Arc<CompiledSelector>, Error> {
+		if let Some(selector) = SELECTOR_CACHE.lock().unwrap().get(context) {
+			return Ok(selector);
+		}
+		let selector = Arc::new(CompiledSelector::new(context)?);
+		SELECTOR_CACHE
+			.lock()
+			.unwrap()
+			.insert(context, Arc::clone(&selector));
+		Ok(selector)
+	}
+}
+
+impl fmt::Debug for CompiledSelector {
+	fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+		f.write_str(format!("CompiledSelector{{ {} }}", self.context).as_str())
+	}
+}
+
+// a least recently used cache of the compiled selectors
+#[derive(Default)]
+struct SelectorCache {
+	capacity: usize,
+	tick: u64,
+	selectors: HashMap<String, (Arc<CompiledSelector>, u64)>,
+}
+
+impl SelectorCache {
+	fn get(&mut self, context: &str) -> Option<Arc<CompiledSelector>> {
+		self.tick += 1;
+		let tick = self.tick;
+		self.selectors.get_mut(context).map(|(selector, used_at)| {
+			*used_at = tick;
+			Arc::clone(selector)
+		})
+	}
+	fn insert(&mut self, context: &str, selector: Arc<CompiledSelector>) {
+		if self.capacity == 0 {
+			return;
+		}
+		if self.selectors.len() >= self.capacity && !self.selectors.contains_key(context) {
+			self.remove_oldest();
+		}
+		self.tick += 1;
+		self
+			.selectors
+			.insert(String::from(context), (selector, self.tick));
+	}
+	// remove the least recently used selector
+	fn remove_oldest(&mut self) {
+		let oldest = self
+			.selectors
+			.iter()
+			.min_by_key(|(_, (_, used_at))| *used_at)
+			.map(|(key, _)| key.clone());
+		if let Some(key) = oldest {
+			self.selectors.remove(&key);
+		}
+	}
+}
+
+// set the capacity of the selector cache, `0` will disable the cache
+pub fn set_cache_capacity(capacity: usize) {
+	let mut cache = SELECTOR_CACHE.lock().unwrap();
+	cache.capacity = capacity;
+	if capacity == 0 {
+		cache.selectors.clear();
+	} else {
+		while cache.selectors.len() > capacity {
+			cache.remove_oldest();
+		}
+	}
+}
+
+// clear the selector cache, the rules changed may cause the selector parsed differently
+pub fn clear_cache() {
+	SELECTOR_CACHE.lock().unwrap().selectors.clear();
+}
+
 #[cfg(test)]
 mod tests {
 	use super::{Combinator, QueryProcess, Selector};
+	use std::convert::TryFrom;
 	#[test]
 	fn test_default() {
 		let def_selector = Selector::default();
 		assert!(def_selector.process.is_empty());
 		let def_process = QueryProcess::default();
-		assert!(def_process.should_in.is_none());
+		assert!(def_process.lookup_index.is_none());
+		assert!(!def_process.use_lookup);
 		assert!(def_process.query.is_empty());
 	}
 	#[test]
 	fn test_combinator() {
-		let comb: Combinator = ">".into();
+		let comb = Combinator::try_from(">").unwrap();
 		assert_eq!(comb, Combinator::Children);
 		assert_eq!(comb.reverse(), Combinator::Parent);
 	}
@@ -416,17 +777,19 @@ mod tests {
 	#[test]
 	fn test_combinator_reverse() {
 		assert_eq!(Combinator::Chain.reverse(), Combinator::Chain);
+		assert_eq!(Combinator::Siblings.reverse(), Combinator::Siblings);
+		for comb in &[
+			Combinator::ChildrenAll,
+			Combinator::Children,
+			Combinator::NextAll,
+			Combinator::Next,
+		] {
+			assert_eq!(comb.reverse().reverse(), *comb);
+		}
 	}
 
 	#[test]
-	#[should_panic]
-	fn test_combinator_unable_reverse() {
-		let _ = Combinator::Parent.reverse();
-	}
-
-	#[test]
-	#[should_panic]
 	fn test_wrong_combinator_string() {
-		let _: Combinator = "<".into();
+		assert!(Combinator::try_from("<").is_err());
 	}
 }
@@ -1,22 +1,60 @@
-use crate::mesdoc::utils::{divide_isize, is_char_available_in_key, to_static_str, RoundType};
+use crate::mesdoc::error::Error;
+use crate::mesdoc::utils::{divide_isize, is_char_available_in_key, RoundType};
 use lazy_static::lazy_static;
 use regex::Regex;
+use std::borrow::Cow;
 use std::sync::{Arc, Mutex};
 use std::{collections::HashMap, fmt::Debug, usize};
 
 pub type FromParamsFn = Box<dyn Fn(&str, &str) -> Result<BoxDynPattern, String> + Send + 'static>;
 lazy_static! {
-	static ref REGEXS: Mutex<HashMap<&'static str, Arc<Regex>>> = Mutex::new(HashMap::new());
+	static ref REGEXS: Mutex<RegexCache> = Mutex::new(RegexCache::default());
 	static ref PATTERNS: Mutex<HashMap<&'static str, FromParamsFn>> = Mutex::new(HashMap::new());
 }
 
 pub type BoxDynPattern = Box<dyn Pattern>;
 
-fn no_implemented(name: &str) -> ! {
-	panic!("No supported pattern '{}' was found", name);
+// the max count of the compiled regexs in the cache
+const REGEX_CACHE_CAPACITY: usize = 256;
+
+// a least recently used cache of the compiled regexs, the regexs in the selectors made dynamically should not be kept forever
+#[derive(Default)]
+struct RegexCache {
+	tick: u64,
+	regexs: HashMap<String, (Arc<Regex>, u64)>,
 }
 
-pub type MatchedData = HashMap<&'static str, &'static str>;
+impl RegexCache {
+	fn get(&mut self, source: &str) -> Option<Arc<Regex>> {
+		self.tick += 1;
+		let tick = self.tick;
+		self.regexs.get_mut(source).map(|(rule, used_at)| {
+			*used_at = tick;
+			Arc::clone(rule)
+		})
+	}
+	fn insert(&mut self, source: &str, rule: Arc<Regex>) {
+		if self.regexs.len() >= REGEX_CACHE_CAPACITY {
+			// remove the least recently used regex
+			let oldest = self
+				.regexs
+				.iter()
+				.min_by_key(|(_, (_, used_at))| *used_at)
+				.map(|(key, _)| key.clone());
+			if let Some(key) = oldest {
+				self.regexs.remove(&key);
+			}
+		}
+		self.tick += 1;
+		self.regexs.insert(String::from(source), (rule, self.tick));
+	}
+}
+
+fn no_implemented(name: &str) -> String {
+	format!("No supported pattern '{}' was found", name)
+}
+
+pub type MatchedData = HashMap<String, String>;
 pub type MatchedQueue = Vec<Matched>;
 #[derive(Debug, Default, Clone)]
 pub struct Matched {
@@ -37,13 +75,13 @@ pub trait Pattern: Send + Sync + Debug {
 	where
 		Self: Sized + Send + 'static,
 	{
-		no_implemented(s);
+		Err(no_implemented(s))
 	}
 }
 
 impl Pattern for char {
 	fn matched(&self, chars: &[char]) -> Option<Matched> {
-		let ch = chars[0];
+		let ch = *chars.first()?;
 		if *self == ch {
 			return Some(Matched {
 				chars: vec![ch],
@@ -91,7 +129,7 @@ pub struct Identity;
 impl Pattern for Identity {
 	fn matched(&self, chars: &[char]) -> Option<Matched> {
 		let mut result: Vec<char> = Vec::with_capacity(5);
-		let first = chars[0];
+		let first = *chars.first()?;
 		let name: &str = "identity";
 		if !(first.is_ascii_alphabetic() || first == '_') {
 			return None;
@@ -193,23 +231,32 @@ pub struct Nth;
 impl Pattern for Nth {
 	fn matched(&self, chars: &[char]) -> Option<Matched> {
 		let rule: RegExp = RegExp {
-			context: r#"^(?:([-+])?([1-9]\d+|[0-9])?n(?:\s*([+-])\s*([1-9]\d+|[0-9]))?|([-+])?([1-9]\d+|[0-9]))"#,
+			context: Cow::Borrowed(
+				r#"^(?:([-+])?([1-9]\d+|[0-9])?n(?:\s*([+-])\s*([1-9]\d+|[0-9]))?|([-+])?([1-9]\d+|[0-9]))"#,
+			),
 		};
 		let mut data = HashMap::with_capacity(2);
 		let mut matched_chars: Vec<char> = Vec::new();
 		if let Some(v) = Pattern::matched(&rule, chars) {
 			let rule_data = v.data;
+			// too large numbers are not allowed
+			if rule_data
+				.values()
+				.any(|value| value.chars().all(|ch| ch.is_ascii_digit()) && value.parse::<i32>().is_err())
+			{
+				return None;
+			}
 			// when the group index 6,
 			let only_index = rule_data.get("6").is_some();
 			let index_keys = if only_index { ("6", "5") } else { ("4", "3") };
 			// set index
 			if let Some(index) = Nth::get_number(&rule_data, index_keys, None) {
-				data.insert("index", index);
+				data.insert(String::from("index"), index);
 			}
 			// also has `n`
 			if !only_index {
 				if let Some(n) = Nth::get_number(&rule_data, ("2", "1"), Some("1")) {
-					data.insert("n", n);
+					data.insert(String::from("n"), n);
 				}
 			}
 			matched_chars = v.chars;
@@ -218,12 +265,12 @@ impl Pattern for Nth {
 			let even = vec!['e', 'v', 'e', 'n'];
 			let odd = vec!['o', 'd', 'd'];
 			if Pattern::matched(&even, chars).is_some() {
-				data.insert("n", "2");
-				data.insert("index", "0");
+				data.insert(String::from("n"), String::from("2"));
+				data.insert(String::from("index"), String::from("0"));
 				matched_chars = even;
 			} else if Pattern::matched(&odd, chars).is_some() {
-				data.insert("n", "2");
-				data.insert("index", "1");
+				data.insert(String::from("n"), String::from("2"));
+				data.insert(String::from("index"), String::from("1"));
 				matched_chars = odd;
 			}
 		}
@@ -244,21 +291,16 @@ impl Pattern for Nth {
 }
 
 impl Nth {
-	fn get_number(data: &MatchedData, keys: (&str, &str), def: Option<&str>) -> Option<&'static str> {
+	fn get_number(data: &MatchedData, keys: (&str, &str), def: Option<&str>) -> Option<String> {
 		const MINUS: &str = "-";
-		if let Some(&idx) = data.get(keys.0).or_else(|| def.as_ref()) {
-			let mut index = idx.to_owned();
-			if let Some(&op) = data.get(keys.1) {
-				if op == MINUS {
-					index = String::from(op) + &index;
-				}
-			}
-			return Some(to_static_str(index));
+		let idx = data.get(keys.0).map(|idx| idx.as_str()).or(def)?;
+		if data.get(keys.1).map(|op| op.as_str()) == Some(MINUS) {
+			return Some(String::from(MINUS) + idx);
 		}
-		None
+		Some(String::from(idx))
 	}
 	// get indexs allowed
-	pub fn get_allowed_indexs(n: &Option<&str>, index: &Option<&str>, total: usize) -> Vec<usize> {
+	pub fn get_allowed_indexs(n: Option<&str>, index: Option<&str>, total: usize) -> Vec<usize> {
 		// has n
 		if let Some(n) = n {
 			let n = n.parse::<isize>().unwrap();
@@ -337,21 +379,21 @@ impl Nth {
 /// RegExp
 #[derive(Debug)]
 pub struct RegExp<'a> {
-	pub context: &'a str,
+	pub context: Cow<'a, str>,
 }
 
 impl<'a> Pattern for RegExp<'a> {
 	/// impl `matched`
 	fn matched(&self, chars: &[char]) -> Option<Matched> {
-		let Self { context } = *self;
 		let content = chars.iter().collect::<String>();
-		let rule = RegExp::get_rule(context);
-		if let Some(caps) = rule.captures(to_static_str(content)) {
-			let total_len = caps[0].len();
+		let rule = RegExp::get_rule(&self.context);
+		if let Some(caps) = rule.captures(&content) {
+			// the matched length in chars, not bytes
+			let total_len = caps[0].chars().count();
 			let mut data = HashMap::with_capacity(caps.len() - 1);
 			for (index, m) in caps.iter().skip(1).enumerate() {
 				if let Some(m) = m {
-					data.insert(to_static_str((index + 1).to_string()), m.as_str());
+					data.insert((index + 1).to_string(), String::from(m.as_str()));
 				}
 			}
 			let result = chars[..total_len].to_vec();
@@ -366,9 +408,11 @@ impl<'a> Pattern for RegExp<'a> {
 	}
 	/// impl `from_params`
 	fn from_params(s: &str, p: &str) -> Result<BoxDynPattern, String> {
+		// make sure the regex is valid
+		RegExp::get_regex(&(String::from("^") + p)).map_err(|e| e.to_string())?;
 		check_params_return(&[s], || {
 			Box::new(RegExp {
-				context: to_static_str(p.to_string()),
+				context: Cow::Owned(String::from(p)),
 			})
 		})
 	}
@@ -378,17 +422,82 @@ impl<'a> RegExp<'a> {
 	pub fn get_rule(context: &str) -> Arc<Regex> {
 		let wrong_regex = format!("Wrong regex context '{}'", context);
 		let last_context = String::from("^") + context;
+		RegExp::get_regex(&last_context).expect(&wrong_regex)
+	}
+	// get the compiled regex of the whole source from the cache
+	pub fn get_regex(source: &str) -> Result<Arc<Regex>, regex::Error> {
 		let mut regexs = REGEXS.lock().unwrap();
-		if let Some(rule) = regexs.get(&last_context[..]) {
-			Arc::clone(rule)
+		if let Some(rule) = regexs.get(source) {
+			Ok(rule)
 		} else {
-			let key = &to_static_str(last_context);
-			let rule = Regex::new(key).expect(&wrong_regex);
-			let value = Arc::new(rule);
-			let result = Arc::clone(&value);
-			regexs.insert(key, value);
-			result
+			let value = Arc::new(Regex::new(source)?);
+			regexs.insert(source, Arc::clone(&value));
+			Ok(value)
+		}
+	}
+}
+
+/// RegExpLiteral
+/// a regex literal like `/\/product\/\d+/i`, the flags can be `i`,`m`,`s`,`x`
+#[derive(Debug, Default)]
+pub struct RegExpLiteral;
+
+impl Pattern for RegExpLiteral {
+	fn matched(&self, chars: &[char]) -> Option<Matched> {
+		if chars.first() != Some(&'/') {
+			return None;
 		}
+		let mut body = String::with_capacity(chars.len());
+		let mut is_in_translate = false;
+		let mut end_index = None;
+		for (index, &c) in chars.iter().enumerate().skip(1) {
+			if is_in_translate {
+				// '\/' is only used to escape the delimiter
+				if c != '/' {
+					body.push('\\');
+				}
+				body.push(c);
+				is_in_translate = false;
+			} else if c == '\\' {
+				is_in_translate = true;
+			} else if c == '/' {
+				end_index = Some(index);
+				break;
+			} else {
+				body.push(c);
+			}
+		}
+		let end_index = end_index?;
+		if body.is_empty() {
+			return None;
+		}
+		let mut flags = String::with_capacity(2);
+		for &c in &chars[end_index + 1..] {
+			match c {
+				'i' | 'm' | 's' | 'x' => flags.push(c),
+				c if c.is_ascii_alphanumeric() => return None,
+				_ => break,
+			}
+		}
+		let source = if flags.is_empty() {
+			body
+		} else {
+			format!("(?{}){}", flags, body)
+		};
+		// make sure the regex is valid
+		RegExp::get_regex(&source).ok()?;
+		let mut data = HashMap::with_capacity(1);
+		data.insert(String::from("source"), source);
+		Some(Matched {
+			chars: chars[..end_index + 1 + flags.len()].to_vec(),
+			name: "regexp_literal",
+			data,
+			ignore_chars: None,
+		})
+	}
+	// from params to pattern
+	fn from_params(s: &str, p: &str) -> Result<BoxDynPattern, String> {
+		check_params_return(&[s, p], || Box::new(RegExpLiteral))
 	}
 }
 
@@ -411,12 +520,22 @@ impl Pattern for NestedSelector {
 }
 
 pub fn add_pattern(name: &'static str, from_handle: FromParamsFn) {
+	if let Err(e) = try_add_pattern(name, from_handle) {
+		panic!("{}", e);
+	}
+}
+
+// add a pattern, return an error if the name is already used
+pub fn try_add_pattern(name: &'static str, from_handle: FromParamsFn) -> Result<(), Error> {
 	let mut patterns = PATTERNS.lock().unwrap();
 	if patterns.get(name).is_some() {
-		panic!("The pattern '{}' is already exist.", name);
-	} else {
-		patterns.insert(name, from_handle);
+		return Err(Error::InvalidRegistration {
+			name: String::from(name),
+			reason: String::from("the pattern is already exist"),
+		});
 	}
+	patterns.insert(name, from_handle);
+	Ok(())
 }
 
 pub(crate) fn init() {
@@ -426,6 +545,7 @@ pub(crate) fn init() {
 	add_pattern("attr_key", Box::new(AttrKey::from_params));
 	add_pattern("nth", Box::new(Nth::from_params));
 	add_pattern("regexp", Box::new(RegExp::from_params));
+	add_pattern("regexp_literal", Box::new(RegExpLiteral::from_params));
 	add_pattern("selector", Box::new(NestedSelector::from_params));
 }
 
@@ -434,7 +554,7 @@ pub fn to_pattern(name: &str, s: &str, p: &str) -> Result<BoxDynPattern, String>
 	if let Some(cb) = patterns.get(name) {
 		return cb(s, p);
 	}
-	no_implemented(name);
+	Err(no_implemented(name))
 }
 
 pub fn exec(queues: &[BoxDynPattern], chars: &[char]) -> (MatchedQueue, usize, usize, bool) {
@@ -473,28 +593,29 @@ pub fn check_params_return<F: Fn() -> BoxDynPattern>(
 mod tests {
 	use super::{
 		add_pattern, check_params_return, AttrKey, BoxDynPattern, Matched, Nth, Pattern, RegExp,
+		REGEXS, REGEX_CACHE_CAPACITY,
 	};
 	#[test]
 	fn test_allow_indexs() {
 		assert_eq!(
-			Nth::get_allowed_indexs(&Some("-2"), &Some("3"), 9),
+			Nth::get_allowed_indexs(Some("-2"), Some("3"), 9),
 			vec![0, 2]
 		);
 		assert_eq!(
-			Nth::get_allowed_indexs(&Some("2"), &Some("3"), 9),
+			Nth::get_allowed_indexs(Some("2"), Some("3"), 9),
 			vec![2, 4, 6, 8]
 		);
-		assert_eq!(Nth::get_allowed_indexs(&None, &Some("3"), 9), vec![2]);
-		assert_eq!(Nth::get_allowed_indexs(&None, &Some("3"), 2), vec![]);
-		assert_eq!(Nth::get_allowed_indexs(&Some("0"), &Some("3"), 9), vec![2]);
-		assert_eq!(Nth::get_allowed_indexs(&Some("0"), &Some("-3"), 9), vec![]);
-		assert_eq!(Nth::get_allowed_indexs(&Some("1"), &Some("6"), 5), vec![]);
+		assert_eq!(Nth::get_allowed_indexs(None, Some("3"), 9), vec![2]);
+		assert_eq!(Nth::get_allowed_indexs(None, Some("3"), 2), vec![]);
+		assert_eq!(Nth::get_allowed_indexs(Some("0"), Some("3"), 9), vec![2]);
+		assert_eq!(Nth::get_allowed_indexs(Some("0"), Some("-3"), 9), vec![]);
+		assert_eq!(Nth::get_allowed_indexs(Some("1"), Some("6"), 5), vec![]);
 		assert_eq!(
-			Nth::get_allowed_indexs(&Some("2"), &None, 9),
+			Nth::get_allowed_indexs(Some("2"), None, 9),
 			vec![1, 3, 5, 7]
 		);
-		assert_eq!(Nth::get_allowed_indexs(&Some("-2"), &None, 9), vec![]);
-		assert_eq!(Nth::get_allowed_indexs(&Some("-4"), &Some("3"), 2), vec![]);
+		assert_eq!(Nth::get_allowed_indexs(Some("-2"), None, 9), vec![]);
+		assert_eq!(Nth::get_allowed_indexs(Some("-4"), Some("3"), 2), vec![]);
 	}
 
 	#[test]
@@ -529,9 +650,8 @@ mod tests {
 	}
 
 	#[test]
-	#[should_panic]
 	fn test_from_params() {
-		let _ = char::from_params("", "");
+		assert!(char::from_params("", "").is_err());
 	}
 
 	#[test]
@@ -548,7 +668,17 @@ mod tests {
 		assert!(attr_key.matched(&[' ']).is_none());
 		assert!(attr_key.matched(&['\u{0000}']).is_none());
 		// regexp
-		let reg_exp: BoxDynPattern = Box::new(RegExp { context: "abc" });
+		let reg_exp: BoxDynPattern = Box::new(RegExp {
+			context: "abc".into(),
+		});
 		assert!(format!("{:?}", reg_exp).contains("abc"));
 	}
+	#[test]
+	fn test_regex_cache() {
+		for index in 0..REGEX_CACHE_CAPACITY + 10 {
+			assert!(RegExp::get_regex(&format!("^cache-{}$", index)).is_ok());
+		}
+		assert!(REGEXS.lock().unwrap().regexs.len() <= REGEX_CACHE_CAPACITY);
+		assert!(RegExp::get_regex("(").is_err());
+	}
 }
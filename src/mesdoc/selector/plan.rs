@@ -0,0 +1,127 @@
+use super::{Combinator, QueryProcess, SelectorSegment};
+use crate::mesdoc::interface::{DocumentStats, StatsItem};
+
+/// The evaluation order of a query in the selector list.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum QueryStrategy {
+	/// Match the compounds from left to right, begin with the descendants of the elements.
+	TopDown,
+	/// Find the elements matched the compound at `start` first, check the compounds before it from right to left through the ancestors and siblings,
+	/// then match the compounds after it from left to right.
+	BottomUp { start: usize },
+}
+
+/// The plan of a query in the selector list, see `Elements::explain`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct QueryPlan {
+	/// the chosen evaluation order
+	pub strategy: QueryStrategy,
+	/// the estimated count of the elements matched each compound, `None` if the plan is made by the fixed priorities of the rules
+	pub estimates: Option<Vec<usize>>,
+	/// the estimated count of the elements checked by the strategy, `None` if the plan is made by the fixed priorities of the rules
+	pub cost: Option<usize>,
+}
+
+impl QueryPlan {
+	// the plan made by the priorities of the rules when the selector is parsed
+	pub(crate) fn fixed(p: &QueryProcess) -> Self {
+		QueryPlan {
+			strategy: match p.lookup_index {
+				Some(start) => QueryStrategy::BottomUp { start },
+				None => QueryStrategy::TopDown,
+			},
+			estimates: None,
+			cost: None,
+		}
+	}
+	// choose the strategy with the lowest cost by the statistics, the query is from the root of the document
+	pub(crate) fn with_stats(p: &QueryProcess, stats: &DocumentStats) -> Self {
+		let total = stats.elements;
+		let depth = stats.average_depth();
+		let siblings = stats.average_siblings();
+		// the elements reached from an element through the combinator
+		let reached = |comb: &Combinator| match comb {
+			Combinator::Next | Combinator::Prev | Combinator::Parent | Combinator::Chain => 1,
+			Combinator::NextAll | Combinator::PrevAll | Combinator::Siblings | Combinator::Children => {
+				siblings
+			}
+			Combinator::ChildrenAll | Combinator::ParentAll => depth,
+		};
+		// the rules without estimate handle may match all the elements
+		let estimate_rule = |(matcher, _): &SelectorSegment| {
+			matcher.estimate_handle.as_ref().map_or(
+				StatsItem {
+					count: total,
+					descendants: total,
+				},
+				|handle| handle(stats),
+			)
+		};
+		// the elements matched the compound can't be more than matched any rule of it
+		let items = p
+			.query
+			.iter()
+			.map(|rules| {
+				rules.iter().map(estimate_rule).fold(
+					StatsItem {
+						count: total,
+						descendants: total,
+					},
+					|prev, item| StatsItem {
+						count: prev.count.min(item.count),
+						descendants: prev.descendants.min(item.descendants),
+					},
+				)
+			})
+			.collect::<Vec<StatsItem>>();
+		let estimates = items.iter().map(|item| item.count).collect::<Vec<usize>>();
+		// the elements checked when find the compound first, the first rule of the compound can get the candidates from the indexes
+		let fetch = |index: usize| {
+			let first = &p.query[index][0];
+			if first.0.in_cache || first.0.index_handle.is_some() {
+				estimate_rule(first).count
+			} else {
+				total
+			}
+		};
+		// the elements checked when match the compounds after the index from the previous matched elements
+		let follow = |index: usize| {
+			(index + 1..items.len())
+				.map(|next| {
+					let prev = &items[next - 1];
+					let checked = match &p.query[next][0].1 {
+						Combinator::Children | Combinator::ChildrenAll => prev.descendants,
+						comb => prev.count.saturating_mul(reached(comb)),
+					};
+					total.min(checked)
+				})
+				.fold(0usize, usize::saturating_add)
+		};
+		let mut strategy = QueryStrategy::TopDown;
+		let mut cost = fetch(0).saturating_add(follow(0));
+		if p.use_lookup
+			&& matches!(
+				p.query[0][0].1,
+				Combinator::Children | Combinator::ChildrenAll
+			) {
+			for (start, &count) in estimates.iter().enumerate().skip(1) {
+				// each finded element check the compounds before it through the ancestors and siblings
+				let check = (1..=start)
+					.map(|index| count.saturating_mul(reached(&p.query[index][0].1.reverse())))
+					.fold(0usize, usize::saturating_add);
+				let start_cost = fetch(start)
+					.saturating_add(check)
+					.saturating_add(follow(start));
+				if start_cost < cost {
+					cost = start_cost;
+					strategy = QueryStrategy::BottomUp { start };
+				}
+			}
+		}
+		QueryPlan {
+			strategy,
+			estimates: Some(estimates),
+			cost: Some(cost),
+		}
+	}
+}
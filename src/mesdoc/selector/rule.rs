@@ -1,8 +1,10 @@
 use super::pattern::{self, exec, to_pattern, BoxDynPattern, MatchedQueue, Pattern};
+use super::{clear_cache, CompiledSelector};
 use crate::mesdoc::utils::vec_char_to_clean_str;
 use crate::mesdoc::{
 	constants::PRIORITY_PSEUDO_SELECTOR,
-	interface::{Elements, IElementTrait},
+	error::Error,
+	interface::{DocumentStats, Elements, IAttrValue, IDocumentTrait, IElementTrait, StatsItem},
 };
 use lazy_static::lazy_static;
 use std::fmt;
@@ -11,10 +13,21 @@ lazy_static! {
 	pub static ref RULES: Mutex<Vec<(&'static str, Arc<Rule>)>> = Mutex::new(Vec::with_capacity(20));
 }
 // matcher handles
-pub type MatchAllHandle = Box<dyn (for<'a, 'r> Fn(&'a Elements<'r>, Option<bool>) -> Elements<'r>)>;
-pub type MatchOneHandle = Box<dyn (Fn(&dyn IElementTrait, Option<bool>) -> bool)>;
-pub type MatchSpecifiedHandle =
-	Box<dyn for<'a> Fn(&'a dyn IElementTrait, Box<dyn FnMut(&dyn IElementTrait, bool, bool) + 'a>)>;
+pub type MatchAllHandle =
+	Box<dyn (for<'a, 'r> Fn(&'a Elements<'r>, Option<bool>) -> Elements<'r>) + Send + Sync>;
+pub type MatchOneHandle = Box<dyn (Fn(&dyn IElementTrait, Option<bool>) -> bool) + Send + Sync>;
+pub type MatchSpecifiedHandle = Box<
+	dyn for<'a> Fn(&'a dyn IElementTrait, Box<dyn FnMut(&dyn IElementTrait, bool, bool) + 'a>)
+		+ Send
+		+ Sync,
+>;
+// get the candidate elements from the indexes of the document, `None` if the document has no index
+pub type MatchIndexHandle =
+	Box<dyn (Fn(&dyn IDocumentTrait) -> Option<Elements<'static>>) + Send + Sync>;
+// estimate the count of the elements matched the rule and their descendants in the document by the statistics
+pub type MatchEstimateHandle = Box<dyn (Fn(&DocumentStats) -> StatsItem) + Send + Sync>;
+// check the value of the attribute by the operator of the attribute selector, `None` if the attribute not exists
+pub type MatchAttrValueHandle = Box<dyn (Fn(&Option<IAttrValue>) -> bool) + Send + Sync>;
 // matcher factory
 pub type MatcherFactory = Box<dyn (Fn(MatchedQueue) -> Matcher) + Send + Sync>;
 
@@ -23,18 +36,27 @@ pub struct Matcher {
 	pub all_handle: Option<MatchAllHandle>,
 	pub one_handle: Option<MatchOneHandle>,
 	pub specified_handle: Option<MatchSpecifiedHandle>,
+	// the elements matched the handles must be in the candidates got from the index
+	pub index_handle: Option<MatchIndexHandle>,
+	// the query planner use the estimated count to choose the most selective compound
+	pub estimate_handle: Option<MatchEstimateHandle>,
 	pub priority: u32,
 	pub in_cache: bool,
+	// jquery positional selectors such as `:eq`,`:first`, the index is relative to the matched set
+	pub positional: bool,
 }
 
 impl fmt::Debug for Matcher {
 	fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
 		f.write_str(
 			format!(
-				"Matcher{{ all_handle: {}, one_handle: {}, specified_handle: {} }}",
+				"Matcher{{ all_handle: {}, one_handle: {}, specified_handle: {}, index_handle: {}, estimate_handle: {}, positional: {} }}",
 				self.all_handle.is_some(),
 				self.one_handle.is_some(),
 				self.specified_handle.is_some(),
+				self.index_handle.is_some(),
+				self.estimate_handle.is_some(),
+				self.positional,
 			)
 			.as_str(),
 		)
@@ -70,8 +92,8 @@ fn get_char_vec() -> Vec<char> {
 }
 
 // unmatched start or end
-fn panic_unmatched(ch: char, index: usize) -> ! {
-	panic!(
+fn unmatched_error(ch: char, index: usize) -> String {
+	format!(
 		"Unmatched '{ch}' at index {index},you can escape it using both {ch}{ch}",
 		ch = ch,
 		index = index
@@ -107,7 +129,7 @@ impl MatchedStore {
 		let name = vec_char_to_clean_str(&mut self.names);
 		let s = vec_char_to_clean_str(&mut self.suf_params);
 		let r = vec_char_to_clean_str(&mut self.raw_params);
-		to_pattern(name, s, r)
+		to_pattern(&name, &s, &r)
 	}
 }
 
@@ -127,7 +149,7 @@ impl fmt::Debug for Rule {
 // Rule methods
 impl Rule {
 	// translate string to queues
-	pub(crate) fn get_queues(content: &str) -> Vec<Box<dyn Pattern>> {
+	pub(crate) fn get_queues(content: &str) -> Result<Vec<Box<dyn Pattern>>, String> {
 		const ANCHOR_CHAR: char = '\0';
 		const START_CHAR: char = '{';
 		const END_CHAR: char = '}';
@@ -152,13 +174,13 @@ impl Rule {
 				if ch == END_CHAR {
 					is_matched_finish = true;
 				} else {
-					panic!(
+					return Err(format!(
 						"Unexpected end of the pattern '{}' at index {}, expect '{}' but found '{}'",
 						vec_char_to_clean_str(&mut store.names),
 						index - 1,
 						END_CHAR,
 						ch
-					);
+					));
 				}
 			} else if !store.is_in_matched {
 				// when not in matched
@@ -186,8 +208,8 @@ impl Rule {
 						prev_char = ANCHOR_CHAR;
 						continue;
 					} else {
-						// panic no matched
-						panic_unmatched(END_CHAR, index - 2);
+						// no matched
+						return Err(unmatched_error(END_CHAR, index - 2));
 					}
 				} else {
 					raw_chars.push(ch);
@@ -223,7 +245,7 @@ impl Rule {
 				// in suf_params or names
 				if ch == '}' {
 					if store.hashs_num > 0 {
-						panic!("Uncomplete raw params: ''");
+						return Err(String::from("Uncomplete raw params: ''"));
 					}
 					is_matched_finish = true;
 				} else if ch == '#' {
@@ -245,22 +267,19 @@ impl Rule {
 				}
 			}
 			if is_matched_finish {
-				match store.next() {
-					Ok(queue) => queues.push(queue),
-					Err(reason) => panic!("{}", reason),
-				};
+				queues.push(store.next()?);
 			}
 			prev_char = ch;
 		}
 		// not end
 		if store.is_wait_end || store.is_in_matched {
-			panic!(
+			return Err(format!(
 				"The Mathed type '{}' is not complete",
 				store.names.iter().collect::<String>()
-			);
+			));
 		}
 		if prev_char == START_CHAR || (prev_char == END_CHAR && !is_matched_finish) {
-			panic_unmatched(prev_char, index - 1);
+			return Err(unmatched_error(prev_char, index - 1));
 		}
 		if !raw_chars.is_empty() {
 			if raw_chars.len() == 1 {
@@ -269,7 +288,7 @@ impl Rule {
 				queues.push(Box::new(raw_chars));
 			}
 		}
-		queues
+		Ok(queues)
 	}
 
 	pub fn exec(&self, chars: &[char]) -> Option<(MatchedQueue, usize, usize)> {
@@ -298,11 +317,11 @@ impl Rule {
 
 	/// make a matcher by alias
 	pub fn make_alias(selector: &'static str) -> Matcher {
-		// if parse the selector string into Selector and save to the closure
-		// the mutex rules will trigger a dead lock
-		// so there give up, just lost some performance
+		let selector = CompiledSelector::new(selector).expect("The alias selector must be valid");
 		Matcher {
-			all_handle: Some(Box::new(move |eles: &Elements, _| eles.filter(selector))),
+			all_handle: Some(Box::new(move |eles: &Elements, _| {
+				eles.filter_compiled(&selector)
+			})),
 			// priority
 			priority: PRIORITY_PSEUDO_SELECTOR,
 			..Default::default()
@@ -310,9 +329,9 @@ impl Rule {
 	}
 
 	/// add a rule
-	pub fn add(context: &str, mut rule: Rule) -> Self {
-		rule.queues = Rule::get_queues(context);
-		rule
+	pub fn add(context: &str, mut rule: Rule) -> Result<Self, String> {
+		rule.queues = Rule::get_queues(context)?;
+		Ok(rule)
 	}
 }
 
@@ -352,11 +371,52 @@ pub fn add_rules(rules: Vec<RuleItem>) {
 		rule,
 	} in rules
 	{
-		let cur_rule = Rule::add(context, rule);
+		let cur_rule = Rule::add(context, rule).expect("The lib rules must be valid");
 		all_rules.push((name, Arc::new(cur_rule)));
 	}
 }
 
+// get the stem of a rule context, e.g. ':nth-child' of ':nth-child({spaces}{nth}{spaces})'
+pub(crate) fn rule_stem(context: &str) -> &str {
+	let end = context.find(['(', '{']).unwrap_or(context.len());
+	&context[..end]
+}
+
+// add a custom pseudo rule, the rule's stem can't be same as the exist rules
+pub fn add_pseudo_rule(
+	context: &'static str,
+	priority: u32,
+	handle: MatcherFactory,
+) -> Result<(), Error> {
+	let stem = rule_stem(context);
+	let make_error = |reason: &str| Error::InvalidRegistration {
+		name: String::from(context),
+		reason: String::from(reason),
+	};
+	if !stem.starts_with(':') || stem.len() < 2 || stem.starts_with("::") {
+		return Err(make_error(
+			"the pseudo selector must start with a single ':'",
+		));
+	}
+	let mut all_rules = RULES.lock().unwrap();
+	let mut insert_index = all_rules.len();
+	for (index, (name, _)) in all_rules.iter().enumerate() {
+		let cur_stem = rule_stem(name);
+		if cur_stem == stem {
+			return Err(make_error("the pseudo selector is already exist"));
+		}
+		// the rule matched first will be used, so a longer stem must insert before its prefix
+		if insert_index == all_rules.len() && cur_stem.starts_with(':') && stem.starts_with(cur_stem) {
+			insert_index = index;
+		}
+	}
+	let rule = RuleItem::from(RuleDefItem(context, context, priority, handle)).rule;
+	let rule = Rule::add(context, rule).map_err(|reason| make_error(&reason))?;
+	all_rules.insert(insert_index, (context, Arc::new(rule)));
+	clear_cache();
+	Ok(())
+}
+
 pub(crate) fn init() {
 	pattern::init();
 }
@@ -379,86 +439,75 @@ mod tests {
 
 	#[test]
 	fn test_rule_escape_start() {
-		let _ = Rule::get_queues("{{nth");
+		assert!(Rule::get_queues("{{nth").is_ok());
 	}
 
 	#[test]
 	fn test_rule_escape_end() {
-		let _ = Rule::get_queues("nth}}");
+		assert!(Rule::get_queues("nth}}").is_ok());
 	}
 
 	#[test]
-	#[should_panic]
 	fn test_rule_escape_end_repeat() {
-		let _ = Rule::get_queues("nth}}}");
+		assert!(Rule::get_queues("nth}}}").is_err());
 	}
 
 	#[test]
 	fn test_rule_escape_both() {
-		let _ = Rule::get_queues("{{nth}}");
+		assert!(Rule::get_queues("{{nth}}").is_ok());
 	}
 
 	#[test]
-	#[should_panic]
 	fn test_rule_escape_no_start() {
-		let _ = Rule::get_queues("{{nth}");
+		assert!(Rule::get_queues("{{nth}").is_err());
 	}
 
 	#[test]
-	#[should_panic]
 	fn test_rule_not_end_1() {
-		let _ = Rule::get_queues("{nth");
+		assert!(Rule::get_queues("{nth").is_err());
 	}
 
 	#[test]
-	#[should_panic]
 	fn test_rule_not_end_2() {
-		let _ = Rule::get_queues("{nth#");
+		assert!(Rule::get_queues("{nth#").is_err());
 	}
 
 	#[test]
-	#[should_panic]
 	fn test_rule_not_end_3() {
-		let _ = Rule::get_queues("{nth#a#");
+		assert!(Rule::get_queues("{nth#a#").is_err());
 	}
 
 	#[test]
-	#[should_panic]
 	fn test_rule_not_end_4() {
-		let _ = Rule::get_queues("{nth#a##}");
+		assert!(Rule::get_queues("{nth#a##}").is_err());
 	}
 
 	#[test]
-	#[should_panic]
 	fn test_wrong_escape_no_end_at_end() {
-		let _ = Rule::get_queues("{nth}{");
+		assert!(Rule::get_queues("{nth}{").is_err());
 	}
 
 	#[test]
-	#[should_panic]
 	fn test_rule_params_not_end() {
-		// panic because no pattern register
-		let _ = Rule::get_queues("{abc!#abc}");
+		// error because no pattern register
+		assert!(Rule::get_queues("{abc!#abc}").is_err());
 	}
 
 	#[test]
-	#[should_panic]
 	fn test_rule_params() {
-		// panic because no pattern register
-		let _ = Rule::get_queues("{abc!#abc#}");
+		// error because no pattern register
+		assert!(Rule::get_queues("{abc!#abc#}").is_err());
 	}
 
 	#[test]
-	#[should_panic]
 	fn test_rule_wrong_params() {
-		// panic because the params not end correctly
-		let _ = Rule::get_queues("{abc!#a#bc#}");
+		// error because the params not end correctly
+		assert!(Rule::get_queues("{abc!#a#bc#}").is_err());
 	}
 
 	#[test]
-	#[should_panic]
 	fn test_rule_params_hash() {
-		// panic because no pattern register
-		let _ = Rule::get_queues("{abc!##a#bc##}");
+		// error because no pattern register
+		assert!(Rule::get_queues("{abc!##a#bc##}").is_err());
 	}
 }
@@ -1,10 +1,6 @@
 use std::cmp::Ordering;
-pub fn to_static_str(content: String) -> &'static str {
-	Box::leak(content.into_boxed_str())
-}
-
-pub fn vec_char_to_clean_str(v: &mut Vec<char>) -> &'static str {
-	to_static_str(v.drain(..).collect::<String>())
+pub fn vec_char_to_clean_str(v: &mut Vec<char>) -> String {
+	v.drain(..).collect::<String>()
 }
 
 /**
@@ -245,6 +241,48 @@ pub fn contains_chars(target: &[char], search: &[char]) -> bool {
 	contains_chars_nocheck(target, search, t_len, s_len)
 }
 
+// the chars of the selector not in the quotes, brackets or parentheses, with the byte offsets
+// the opening quotes and brackets are included, the escaped chars are skipped
+pub fn top_level_chars(context: &str) -> Vec<(usize, char)> {
+	let mut result = Vec::with_capacity(context.len());
+	let mut quote: Option<char> = None;
+	let mut depth = 0;
+	let mut is_escape = false;
+	for (index, ch) in context.char_indices() {
+		if is_escape {
+			is_escape = false;
+			continue;
+		}
+		let is_top_level = quote.is_none() && depth == 0;
+		match (quote, ch) {
+			(_, '\\') => is_escape = true,
+			(Some(cur), ch) if cur == ch => quote = None,
+			(Some(_), _) => {}
+			(None, '\'' | '"') => quote = Some(ch),
+			(None, '(' | '[') => depth += 1,
+			(None, ')' | ']') => depth -= 1,
+			_ => {}
+		}
+		if is_top_level && !is_escape {
+			result.push((index, ch));
+		}
+	}
+	result
+}
+
+// unescape the value in the quotes of the selector, e.g. `a\"b` to `a"b`
+pub fn unescape_quoted(value: &str) -> String {
+	let mut result = String::with_capacity(value.len());
+	let mut chars = value.chars();
+	while let Some(ch) = chars.next() {
+		match ch {
+			'\\' => result.push(chars.next().unwrap_or(ch)),
+			_ => result.push(ch),
+		}
+	}
+	result
+}
+
 #[cfg(test)]
 mod tests {
 	use super::{divide_isize, RoundType};
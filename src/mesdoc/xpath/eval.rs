@@ -0,0 +1,727 @@
+use super::parser::{ArithOp, Axis, CompareOp, Expr, NodeTest, PathStart};
+use super::XPathNode;
+use crate::mesdoc::constants::{NAMESPACE_HTML, NAMESPACE_XLINK, NAMESPACE_XML, NAMESPACE_XMLNS};
+use crate::mesdoc::interface::{BoxDynElement, IAttrValue, INodeType};
+
+// the document order of a node, the child indexes from the root node
+// the attributes of an element are after the element and before its children
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord)]
+pub struct NodeKey {
+	path: Vec<usize>,
+	attr: Option<usize>,
+}
+
+#[derive(Clone)]
+pub struct Item<'a> {
+	key: NodeKey,
+	pub(crate) node: XPathNode<'a>,
+}
+
+impl<'a> Item<'a> {
+	pub fn with_element(ele: &BoxDynElement) -> Self {
+		let mut path = Vec::new();
+		let mut cur = ele.cloned();
+		while let Some(parent) = cur.parent() {
+			path.push(cur.index());
+			cur = parent;
+		}
+		path.reverse();
+		Item {
+			key: NodeKey { path, attr: None },
+			node: XPathNode::Element(ele.cloned()),
+		}
+	}
+}
+
+pub enum Value<'a> {
+	NodeSet(Vec<Item<'a>>),
+	String(String),
+	Number(f64),
+	Boolean(bool),
+}
+
+impl<'a> Value<'a> {
+	fn string(&self) -> String {
+		match self {
+			Value::NodeSet(items) => items
+				.first()
+				.map(|item| item.node.string_value())
+				.unwrap_or_default(),
+			Value::String(value) => value.clone(),
+			Value::Number(value) => number_to_string(*value),
+			Value::Boolean(value) => value.to_string(),
+		}
+	}
+	fn number(&self) -> f64 {
+		match self {
+			Value::Number(value) => *value,
+			Value::Boolean(value) => bool_to_number(*value),
+			_ => string_to_number(&self.string()),
+		}
+	}
+	fn boolean(&self) -> bool {
+		match self {
+			Value::NodeSet(items) => !items.is_empty(),
+			Value::String(value) => !value.is_empty(),
+			Value::Number(value) => number_to_bool(*value),
+			Value::Boolean(value) => *value,
+		}
+	}
+	fn into_node_set(self, desc: &str) -> Result<Vec<Item<'a>>, String> {
+		match self {
+			Value::NodeSet(items) => Ok(items),
+			_ => Err(format!("{} must be a node-set", desc)),
+		}
+	}
+}
+
+pub(crate) fn number_to_string(value: f64) -> String {
+	if value.is_nan() {
+		String::from("NaN")
+	} else if value.is_infinite() {
+		String::from(if value > 0.0 { "Infinity" } else { "-Infinity" })
+	} else if value == 0.0 {
+		// also for negative zero
+		String::from("0")
+	} else {
+		value.to_string()
+	}
+}
+
+pub(crate) fn string_to_number(value: &str) -> f64 {
+	let value = value.trim_matches(|c: char| c.is_ascii_whitespace());
+	let digits = value.strip_prefix('-').unwrap_or(value);
+	let mut has_digit = false;
+	let mut has_dot = false;
+	for ch in digits.chars() {
+		match ch {
+			'0'..='9' => has_digit = true,
+			'.' if !has_dot => has_dot = true,
+			_ => return f64::NAN,
+		}
+	}
+	if !has_digit {
+		return f64::NAN;
+	}
+	value.parse::<f64>().unwrap_or(f64::NAN)
+}
+
+pub(crate) fn number_to_bool(value: f64) -> bool {
+	value != 0.0 && !value.is_nan()
+}
+
+pub(crate) fn bool_to_number(value: bool) -> f64 {
+	if value {
+		1.0
+	} else {
+		0.0
+	}
+}
+
+// sort the nodes in document order and remove the duplicates
+pub fn sort_unique(items: &mut Vec<Item>) {
+	items.sort_by(|a, b| a.key.cmp(&b.key));
+	items.dedup_by(|a, b| a.key == b.key);
+}
+
+/*
+** axes
+*/
+fn children<'a>(item: &Item) -> Vec<Item<'a>> {
+	match &item.node {
+		XPathNode::Element(ele) => ele
+			.child_nodes()
+			.into_iter()
+			.map(|node| {
+				let mut path = item.key.path.clone();
+				path.push(node.index());
+				Item {
+					key: NodeKey { path, attr: None },
+					node: XPathNode::from_node(node),
+				}
+			})
+			.collect(),
+		_ => Vec::new(),
+	}
+}
+
+fn parent<'a>(item: &Item) -> Option<Item<'a>> {
+	let mut path = item.key.path.clone();
+	let parent = match &item.node {
+		XPathNode::Attribute { owner, .. } => owner.cloned(),
+		node => {
+			let parent = node.parent()?;
+			path.pop();
+			parent
+		}
+	};
+	Some(Item {
+		key: NodeKey { path, attr: None },
+		node: XPathNode::Element(parent),
+	})
+}
+
+fn root<'a>(item: &Item<'a>) -> Item<'a> {
+	let mut cur = item.clone();
+	while let Some(parent) = parent(&cur) {
+		cur = parent;
+	}
+	cur
+}
+
+fn push_descendants<'a>(item: &Item, result: &mut Vec<Item<'a>>) {
+	let mut stack: Vec<Item> = children(item).into_iter().rev().collect();
+	while let Some(cur) = stack.pop() {
+		stack.extend(children(&cur).into_iter().rev());
+		result.push(cur);
+	}
+}
+
+// the siblings in the axis order
+fn siblings<'a>(item: &Item, following: bool) -> Vec<Item<'a>> {
+	if matches!(item.node, XPathNode::Attribute { .. }) {
+		return Vec::new();
+	}
+	let index = match item.key.path.last() {
+		Some(&index) => index,
+		None => return Vec::new(),
+	};
+	let mut siblings = parent(item).map(|p| children(&p)).unwrap_or_default();
+	if following {
+		siblings.retain(|sibling| sibling.key.path.last() > Some(&index));
+	} else {
+		siblings.retain(|sibling| sibling.key.path.last() < Some(&index));
+		siblings.reverse();
+	}
+	siblings
+}
+
+fn attributes<'a>(item: &Item) -> Vec<Item<'a>> {
+	match &item.node {
+		XPathNode::Element(ele) if !ele.is_root_element() => ele
+			.get_attribute_names()
+			.into_iter()
+			.enumerate()
+			.map(|(index, name)| {
+				let value = match ele.get_attribute(&name) {
+					Some(IAttrValue::Value(value, _)) => value,
+					_ => String::new(),
+				};
+				Item {
+					key: NodeKey {
+						path: item.key.path.clone(),
+						attr: Some(index),
+					},
+					node: XPathNode::Attribute {
+						owner: ele.cloned(),
+						name,
+						value,
+					},
+				}
+			})
+			.collect(),
+		_ => Vec::new(),
+	}
+}
+
+fn axis_items<'a>(item: &Item<'a>, axis: Axis) -> Vec<Item<'a>> {
+	let mut result = Vec::new();
+	match axis {
+		Axis::SelfNode => result.push(item.clone()),
+		Axis::Child => result = children(item),
+		Axis::Attribute => result = attributes(item),
+		Axis::Parent => result.extend(parent(item)),
+		Axis::Ancestor | Axis::AncestorOrSelf => {
+			if axis == Axis::AncestorOrSelf {
+				result.push(item.clone());
+			}
+			let mut cur = parent(item);
+			while let Some(item) = cur {
+				cur = parent(&item);
+				result.push(item);
+			}
+		}
+		Axis::Descendant => push_descendants(item, &mut result),
+		Axis::DescendantOrSelf => {
+			result.push(item.clone());
+			push_descendants(item, &mut result);
+		}
+		Axis::FollowingSibling => result = siblings(item, true),
+		Axis::PrecedingSibling => result = siblings(item, false),
+		Axis::Following => {
+			let mut cur = item.clone();
+			if matches!(item.node, XPathNode::Attribute { .. }) {
+				// the descendants of the owner element are following the attribute
+				cur = parent(item).expect("An attribute always has an owner element");
+				push_descendants(&cur, &mut result);
+			}
+			loop {
+				for sibling in siblings(&cur, true) {
+					push_descendants(&sibling, &mut result);
+					result.push(sibling);
+				}
+				match parent(&cur) {
+					Some(parent) => cur = parent,
+					None => break,
+				}
+			}
+			// keep the document order
+			result.sort_by(|a, b| a.key.cmp(&b.key));
+		}
+		Axis::Preceding => {
+			let mut cur = item.clone();
+			if matches!(item.node, XPathNode::Attribute { .. }) {
+				cur = parent(item).expect("An attribute always has an owner element");
+			}
+			loop {
+				for sibling in siblings(&cur, false) {
+					let mut nodes = Vec::new();
+					push_descendants(&sibling, &mut nodes);
+					result.extend(nodes.into_iter().rev());
+					result.push(sibling);
+				}
+				match parent(&cur) {
+					Some(parent) => cur = parent,
+					None => break,
+				}
+			}
+		}
+		// the namespace nodes are not supported in html documents
+		Axis::Namespace => {}
+	}
+	result
+}
+
+fn attribute_prefix(name: &str) -> Option<&str> {
+	name.rfind(':').map(|index| &name[..index])
+}
+
+fn is_match_node_test(item: &Item, test: &NodeTest, axis: Axis) -> bool {
+	// the principal node type of the attribute axis is attribute, otherwise element
+	let is_attr_axis = axis == Axis::Attribute;
+	match (test, &item.node) {
+		(NodeTest::Node, _) => true,
+		(NodeTest::Text, XPathNode::Text(_)) => true,
+		(NodeTest::Comment, XPathNode::Other(node)) => matches!(node.node_type(), INodeType::Comment),
+		(NodeTest::Any, XPathNode::Attribute { .. }) => is_attr_axis,
+		(NodeTest::Any, XPathNode::Element(ele)) => !is_attr_axis && !ele.is_root_element(),
+		(NodeTest::NamespaceAny(prefix, _), XPathNode::Attribute { name, .. }) => {
+			is_attr_axis && attribute_prefix(name) == Some(prefix)
+		}
+		(NodeTest::NamespaceAny(_, namespace), XPathNode::Element(ele)) => {
+			!is_attr_axis && !ele.is_root_element() && ele.namespace_uri() == *namespace
+		}
+		(NodeTest::Name(prefix, local_name), XPathNode::Attribute { name, .. }) => {
+			is_attr_axis
+				&& match prefix {
+					Some((prefix, _)) => name.eq_ignore_ascii_case(&format!("{}:{}", prefix, local_name)),
+					None => name.eq_ignore_ascii_case(local_name),
+				}
+		}
+		(NodeTest::Name(prefix, local_name), XPathNode::Element(ele)) => {
+			if is_attr_axis || ele.is_root_element() {
+				return false;
+			}
+			match prefix {
+				// html elements ignore case, svg and mathml elements are case-sensitive
+				Some((_, namespace)) => {
+					let namespace_uri = ele.namespace_uri();
+					namespace_uri == *namespace
+						&& if namespace_uri == NAMESPACE_HTML {
+							ele.local_name().eq_ignore_ascii_case(local_name)
+						} else {
+							ele.local_name() == *local_name
+						}
+				}
+				// a name without prefix matches the elements in any namespace
+				None => ele.local_name().eq_ignore_ascii_case(local_name),
+			}
+		}
+		_ => false,
+	}
+}
+
+/*
+** evaluation
+*/
+struct Context<'c, 'a> {
+	// the context node, `None` when the expression is evaluated without elements
+	item: Option<&'c Item<'a>>,
+	position: usize,
+	size: usize,
+}
+
+type EvalResult<'a> = Result<Value<'a>, String>;
+
+fn apply_predicates<'a>(
+	mut items: Vec<Item<'a>>,
+	predicates: &[Expr],
+) -> Result<Vec<Item<'a>>, String> {
+	for predicate in predicates {
+		let size = items.len();
+		let mut matched_items = Vec::with_capacity(size);
+		for (index, item) in items.into_iter().enumerate() {
+			let position = index + 1;
+			let ctx = Context {
+				item: Some(&item),
+				position,
+				size,
+			};
+			// a number predicate is true if it equals to the context position
+			let is_matched = match eval(predicate, &ctx)? {
+				Value::Number(value) => value == position as f64,
+				value => value.boolean(),
+			};
+			if is_matched {
+				matched_items.push(item);
+			}
+		}
+		items = matched_items;
+	}
+	Ok(items)
+}
+
+fn compare_atom(op: CompareOp, left: &Value, right: &Value) -> bool {
+	match op {
+		CompareOp::Equal | CompareOp::NotEqual => {
+			let is_equal = match (left, right) {
+				(Value::Boolean(_), _) | (_, Value::Boolean(_)) => left.boolean() == right.boolean(),
+				(Value::Number(_), _) | (_, Value::Number(_)) => left.number() == right.number(),
+				_ => left.string() == right.string(),
+			};
+			is_equal == (op == CompareOp::Equal)
+		}
+		_ => {
+			let (left, right) = (left.number(), right.number());
+			match op {
+				CompareOp::Less => left < right,
+				CompareOp::LessEqual => left <= right,
+				CompareOp::Greater => left > right,
+				_ => left >= right,
+			}
+		}
+	}
+}
+
+fn compare(op: CompareOp, left: &Value, right: &Value) -> bool {
+	let string_values = |items: &[Item]| -> Vec<Value> {
+		items
+			.iter()
+			.map(|item| Value::String(item.node.string_value()))
+			.collect()
+	};
+	match (left, right) {
+		(Value::NodeSet(left), Value::NodeSet(right)) => {
+			let right = string_values(right);
+			string_values(left)
+				.iter()
+				.any(|left| right.iter().any(|right| compare_atom(op, left, right)))
+		}
+		// compare a node-set with a boolean by converting the node-set to a boolean
+		(Value::NodeSet(items), Value::Boolean(_)) => {
+			compare_atom(op, &Value::Boolean(!items.is_empty()), right)
+		}
+		(Value::Boolean(_), Value::NodeSet(items)) => {
+			compare_atom(op, left, &Value::Boolean(!items.is_empty()))
+		}
+		(Value::NodeSet(items), _) => string_values(items)
+			.iter()
+			.any(|left| compare_atom(op, left, right)),
+		(_, Value::NodeSet(items)) => string_values(items)
+			.iter()
+			.any(|right| compare_atom(op, left, right)),
+		_ => compare_atom(op, left, right),
+	}
+}
+
+fn eval<'a>(expr: &Expr, ctx: &Context<'_, 'a>) -> EvalResult<'a> {
+	Ok(match expr {
+		Expr::Or(left, right) => {
+			Value::Boolean(eval(left, ctx)?.boolean() || eval(right, ctx)?.boolean())
+		}
+		Expr::And(left, right) => {
+			Value::Boolean(eval(left, ctx)?.boolean() && eval(right, ctx)?.boolean())
+		}
+		Expr::Compare(op, left, right) => {
+			Value::Boolean(compare(*op, &eval(left, ctx)?, &eval(right, ctx)?))
+		}
+		Expr::Arith(op, left, right) => {
+			let left = eval(left, ctx)?.number();
+			let right = eval(right, ctx)?.number();
+			Value::Number(match op {
+				ArithOp::Plus => left + right,
+				ArithOp::Minus => left - right,
+				ArithOp::Multiply => left * right,
+				ArithOp::Div => left / right,
+				ArithOp::Mod => left % right,
+			})
+		}
+		Expr::Negate(expr) => Value::Number(-eval(expr, ctx)?.number()),
+		Expr::Union(left, right) => {
+			let mut items = eval(left, ctx)?.into_node_set("the operand of '|'")?;
+			items.extend(eval(right, ctx)?.into_node_set("the operand of '|'")?);
+			sort_unique(&mut items);
+			Value::NodeSet(items)
+		}
+		Expr::Literal(value) => Value::String(value.clone()),
+		Expr::Number(value) => Value::Number(*value),
+		Expr::Function(name, args) => call_function(name, args, ctx)?,
+		Expr::Filter(expr, predicates) => {
+			let items = eval(expr, ctx)?.into_node_set("the expression before a predicate")?;
+			Value::NodeSet(apply_predicates(items, predicates)?)
+		}
+		Expr::Path(start, steps) => {
+			let mut items = match start {
+				PathStart::Context => ctx.item.cloned().into_iter().collect(),
+				PathStart::Root => ctx.item.map(root).into_iter().collect(),
+				PathStart::Filter(expr) => eval(expr, ctx)?.into_node_set("the expression before '/'")?,
+			};
+			for step in steps {
+				let mut result = Vec::new();
+				for item in &items {
+					let matched_items = axis_items(item, step.axis)
+						.into_iter()
+						.filter(|item| is_match_node_test(item, &step.test, step.axis))
+						.collect();
+					result.extend(apply_predicates(matched_items, &step.predicates)?);
+				}
+				sort_unique(&mut result);
+				items = result;
+			}
+			Value::NodeSet(items)
+		}
+	})
+}
+
+/*
+** the core function library
+*/
+fn eval_string<'a>(args: &[Expr], index: usize, ctx: &Context<'_, 'a>) -> Result<String, String> {
+	Ok(eval(&args[index], ctx)?.string())
+}
+
+fn eval_number<'a>(args: &[Expr], index: usize, ctx: &Context<'_, 'a>) -> Result<f64, String> {
+	Ok(eval(&args[index], ctx)?.number())
+}
+
+// the optional node-set argument, use the context node by default
+fn eval_optional_node<'a>(
+	name: &str,
+	args: &[Expr],
+	ctx: &Context<'_, 'a>,
+) -> Result<Option<Item<'a>>, String> {
+	if args.is_empty() {
+		return Ok(ctx.item.cloned());
+	}
+	let items = eval(&args[0], ctx)?.into_node_set(&format!("the argument of '{}()'", name))?;
+	Ok(items.into_iter().next())
+}
+
+// the optional string argument, use the string value of the context node by default
+fn eval_optional_string<'a>(args: &[Expr], ctx: &Context<'_, 'a>) -> Result<String, String> {
+	if args.is_empty() {
+		return Ok(
+			ctx
+				.item
+				.map(|item| item.node.string_value())
+				.unwrap_or_default(),
+		);
+	}
+	eval_string(args, 0, ctx)
+}
+
+fn round(value: f64) -> f64 {
+	if value.is_nan() || value.is_infinite() {
+		return value;
+	}
+	(value + 0.5).floor()
+}
+
+fn call_function<'a>(name: &str, args: &[Expr], ctx: &Context<'_, 'a>) -> EvalResult<'a> {
+	Ok(match name {
+		"last" => Value::Number(ctx.size as f64),
+		"position" => Value::Number(ctx.position as f64),
+		"count" => {
+			let items = eval(&args[0], ctx)?.into_node_set("the argument of 'count()'")?;
+			Value::Number(items.len() as f64)
+		}
+		"id" => {
+			let ids = match eval(&args[0], ctx)? {
+				Value::NodeSet(items) => items
+					.iter()
+					.map(|item| item.node.string_value())
+					.collect::<Vec<String>>()
+					.join(" "),
+				value => value.string(),
+			};
+			let ids: Vec<&str> = ids.split_ascii_whitespace().collect();
+			let mut elements = Vec::new();
+			if let Some(item) = ctx.item {
+				push_descendants(&root(item), &mut elements);
+			}
+			elements.retain(|item| match &item.node {
+				XPathNode::Element(ele) => matches!(
+					ele.get_attribute("id"),
+					Some(IAttrValue::Value(id, _)) if ids.contains(&id.as_str())
+				),
+				_ => false,
+			});
+			Value::NodeSet(elements)
+		}
+		"local-name" | "namespace-uri" | "name" => {
+			let value = match eval_optional_node(name, args, ctx)?.map(|item| item.node) {
+				Some(XPathNode::Element(ele)) if !ele.is_root_element() => match name {
+					"namespace-uri" => String::from(ele.namespace_uri()),
+					_ => ele.local_name(),
+				},
+				Some(XPathNode::Attribute {
+					name: attr_name, ..
+				}) => match name {
+					"namespace-uri" => String::from(match attribute_prefix(&attr_name) {
+						Some("xlink") => NAMESPACE_XLINK,
+						Some("xml") => NAMESPACE_XML,
+						Some("xmlns") => NAMESPACE_XMLNS,
+						_ => "",
+					}),
+					"local-name" => match attr_name.rfind(':') {
+						Some(index) => String::from(&attr_name[index + 1..]),
+						None => attr_name,
+					},
+					_ => attr_name,
+				},
+				_ => String::new(),
+			};
+			Value::String(value)
+		}
+		"string" => Value::String(eval_optional_string(args, ctx)?),
+		"concat" => {
+			let mut result = String::new();
+			for index in 0..args.len() {
+				result.push_str(&eval_string(args, index, ctx)?);
+			}
+			Value::String(result)
+		}
+		"starts-with" | "contains" | "substring-before" | "substring-after" => {
+			let haystack = eval_string(args, 0, ctx)?;
+			let needle = eval_string(args, 1, ctx)?;
+			match name {
+				"starts-with" => Value::Boolean(haystack.starts_with(&needle)),
+				"contains" => Value::Boolean(haystack.contains(&needle)),
+				_ => Value::String(
+					haystack
+						.find(&needle)
+						.map(|index| {
+							if name == "substring-before" {
+								String::from(&haystack[..index])
+							} else {
+								String::from(&haystack[index + needle.len()..])
+							}
+						})
+						.unwrap_or_default(),
+				),
+			}
+		}
+		"substring" => {
+			let value = eval_string(args, 0, ctx)?;
+			let start = round(eval_number(args, 1, ctx)?);
+			let end = if args.len() > 2 {
+				start + round(eval_number(args, 2, ctx)?)
+			} else {
+				f64::INFINITY
+			};
+			// the position of the first character is 1
+			Value::String(
+				value
+					.chars()
+					.enumerate()
+					.filter(|(index, _)| {
+						let position = (index + 1) as f64;
+						position >= start && position < end
+					})
+					.map(|(_, ch)| ch)
+					.collect(),
+			)
+		}
+		"string-length" => Value::Number(eval_optional_string(args, ctx)?.chars().count() as f64),
+		"normalize-space" => Value::String(
+			eval_optional_string(args, ctx)?
+				.split_ascii_whitespace()
+				.collect::<Vec<&str>>()
+				.join(" "),
+		),
+		"translate" => {
+			let value = eval_string(args, 0, ctx)?;
+			let from: Vec<char> = eval_string(args, 1, ctx)?.chars().collect();
+			let to: Vec<char> = eval_string(args, 2, ctx)?.chars().collect();
+			Value::String(
+				value
+					.chars()
+					.filter_map(|ch| match from.iter().position(|&c| c == ch) {
+						// remove the character if there is no replacement
+						Some(index) => to.get(index).copied(),
+						None => Some(ch),
+					})
+					.collect(),
+			)
+		}
+		"boolean" => Value::Boolean(eval(&args[0], ctx)?.boolean()),
+		"not" => Value::Boolean(!eval(&args[0], ctx)?.boolean()),
+		"true" => Value::Boolean(true),
+		"false" => Value::Boolean(false),
+		"lang" => {
+			let lang = eval_string(args, 0, ctx)?.to_ascii_lowercase();
+			let mut cur = ctx.item.and_then(|item| match &item.node {
+				XPathNode::Element(ele) => Some(ele.cloned()),
+				node => node.parent(),
+			});
+			let mut is_matched = false;
+			while let Some(ele) = cur {
+				if ele.is_root_element() {
+					break;
+				}
+				let value = ele
+					.get_attribute("xml:lang")
+					.or_else(|| ele.get_attribute("lang"));
+				if let Some(value) = value {
+					if let IAttrValue::Value(value, _) = value {
+						let value = value.to_ascii_lowercase();
+						is_matched = value == lang || value.starts_with(&format!("{}-", lang));
+					}
+					break;
+				}
+				cur = ele.parent();
+			}
+			Value::Boolean(is_matched)
+		}
+		"number" => Value::Number(if args.is_empty() {
+			string_to_number(&eval_optional_string(args, ctx)?)
+		} else {
+			eval_number(args, 0, ctx)?
+		}),
+		"sum" => {
+			let items = eval(&args[0], ctx)?.into_node_set("the argument of 'sum()'")?;
+			Value::Number(
+				items
+					.iter()
+					.map(|item| string_to_number(&item.node.string_value()))
+					// the sum of an empty node-set is positive zero
+					.fold(0.0, |sum, value| sum + value),
+			)
+		}
+		"floor" => Value::Number(eval_number(args, 0, ctx)?.floor()),
+		"ceiling" => Value::Number(eval_number(args, 0, ctx)?.ceil()),
+		"round" => Value::Number(round(eval_number(args, 0, ctx)?)),
+		_ => return Err(format!("unknown function '{}'", name)),
+	})
+}
+
+// evaluate the expression with the element as the context node, or without context node
+pub fn evaluate<'a>(expr: &Expr, ele: Option<&BoxDynElement>) -> EvalResult<'a> {
+	let item = ele.map(Item::with_element);
+	let ctx = Context {
+		item: item.as_ref(),
+		position: 1,
+		size: 1,
+	};
+	eval(expr, &ctx)
+}
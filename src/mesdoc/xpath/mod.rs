@@ -0,0 +1,198 @@
+mod eval;
+mod parser;
+use self::eval::{Item, Value};
+use self::parser::Expr;
+use crate::mesdoc::error::Error;
+use crate::mesdoc::interface::{
+	BoxDynElement, BoxDynNode, BoxDynText, BoxDynUncareNode, Elements, IEnumTyped, INodeType,
+	MaybeElement,
+};
+
+// a node in the xpath data model
+pub enum XPathNode<'a> {
+	// element node, the root node is an element too
+	Element(BoxDynElement<'a>),
+	Text(BoxDynText<'a>),
+	// the attribute node of the owner element
+	Attribute {
+		owner: BoxDynElement<'a>,
+		name: String,
+		value: String,
+	},
+	// comment, doctype and other nodes
+	Other(BoxDynUncareNode<'a>),
+}
+
+impl<'a> XPathNode<'a> {
+	pub(crate) fn from_node(node: BoxDynNode<'a>) -> Self {
+		match node.typed() {
+			IEnumTyped::Element(ele) => XPathNode::Element(ele),
+			IEnumTyped::Text(text) => XPathNode::Text(text),
+			IEnumTyped::UncareNode(node) => XPathNode::Other(node),
+		}
+	}
+	pub(crate) fn parent<'b>(&self) -> MaybeElement<'b> {
+		match self {
+			XPathNode::Element(ele) => ele.parent(),
+			XPathNode::Text(text) => text.parent(),
+			XPathNode::Attribute { owner, .. } => Some(owner.cloned()),
+			XPathNode::Other(node) => node.parent(),
+		}
+	}
+	/// The string-value of the node
+	pub fn string_value(&self) -> String {
+		match self {
+			XPathNode::Element(ele) => {
+				if ele.is_root_element() {
+					// the root node's string-value is the text of its children
+					return ele
+						.child_nodes()
+						.into_iter()
+						.map(|node| XPathNode::from_node(node).string_value())
+						.collect();
+				}
+				ele.text_content()
+			}
+			XPathNode::Text(text) => text.text_content(),
+			XPathNode::Attribute { value, .. } => value.clone(),
+			XPathNode::Other(node) => match node.node_type() {
+				INodeType::Comment => {
+					let content = node.text_content();
+					let content = content.strip_prefix("<!--").unwrap_or(&content);
+					String::from(content.strip_suffix("-->").unwrap_or(content))
+				}
+				_ => String::new(),
+			},
+		}
+	}
+}
+
+impl<'a> Clone for XPathNode<'a> {
+	fn clone(&self) -> Self {
+		match self {
+			XPathNode::Element(ele) => XPathNode::Element(ele.cloned()),
+			XPathNode::Text(text) => XPathNode::from_node(text.clone_node()),
+			XPathNode::Attribute { owner, name, value } => XPathNode::Attribute {
+				owner: owner.cloned(),
+				name: name.clone(),
+				value: value.clone(),
+			},
+			XPathNode::Other(node) => XPathNode::from_node(node.clone_node()),
+		}
+	}
+}
+
+// the result of an xpath expression
+pub enum XPathValue<'a> {
+	// the nodes in document order
+	NodeSet(Vec<XPathNode<'a>>),
+	String(String),
+	Number(f64),
+	Boolean(bool),
+}
+
+impl<'a> From<Value<'a>> for XPathValue<'a> {
+	fn from(value: Value<'a>) -> Self {
+		match value {
+			Value::NodeSet(items) => {
+				XPathValue::NodeSet(items.into_iter().map(|item| item.node).collect())
+			}
+			Value::String(value) => XPathValue::String(value),
+			Value::Number(value) => XPathValue::Number(value),
+			Value::Boolean(value) => XPathValue::Boolean(value),
+		}
+	}
+}
+
+impl<'a> XPathValue<'a> {
+	/// Check if the value is a node-set
+	pub fn is_node_set(&self) -> bool {
+		matches!(self, XPathValue::NodeSet(_))
+	}
+	/// Get the nodes of a node-set, other values will return an empty list.
+	pub fn into_nodes(self) -> Vec<XPathNode<'a>> {
+		match self {
+			XPathValue::NodeSet(nodes) => nodes,
+			_ => Vec::new(),
+		}
+	}
+	/// Get the element nodes of a node-set as `Elements`, so the jquery like methods can be used.
+	pub fn into_elements(self) -> Elements<'a> {
+		Elements::with_nodes(
+			self
+				.into_nodes()
+				.into_iter()
+				.filter_map(|node| match node {
+					XPathNode::Element(ele) => Some(ele),
+					_ => None,
+				})
+				.collect(),
+		)
+	}
+	/// Convert the value to a string, same as the xpath `string()` function.
+	pub fn string(&self) -> String {
+		match self {
+			XPathValue::NodeSet(nodes) => nodes
+				.first()
+				.map(|node| node.string_value())
+				.unwrap_or_default(),
+			XPathValue::String(value) => value.clone(),
+			XPathValue::Number(value) => eval::number_to_string(*value),
+			XPathValue::Boolean(value) => value.to_string(),
+		}
+	}
+	/// Convert the value to a number, same as the xpath `number()` function.
+	pub fn number(&self) -> f64 {
+		match self {
+			XPathValue::Number(value) => *value,
+			XPathValue::Boolean(value) => eval::bool_to_number(*value),
+			_ => eval::string_to_number(&self.string()),
+		}
+	}
+	/// Convert the value to a boolean, same as the xpath `boolean()` function.
+	pub fn boolean(&self) -> bool {
+		match self {
+			XPathValue::NodeSet(nodes) => !nodes.is_empty(),
+			XPathValue::String(value) => !value.is_empty(),
+			XPathValue::Number(value) => eval::number_to_bool(*value),
+			XPathValue::Boolean(value) => *value,
+		}
+	}
+}
+
+#[derive(Debug)]
+pub struct XPath {
+	expression: String,
+	expr: Expr,
+}
+
+impl XPath {
+	pub fn from_str(expression: &str) -> Result<Self, Error> {
+		Ok(XPath {
+			expression: String::from(expression),
+			expr: parser::parse(expression)?,
+		})
+	}
+	// evaluate the expression with each element as the context node
+	// the node-sets will be merged, other values are evaluated with the first element
+	// without elements, the expression is evaluated without context node, so the paths are empty node-sets
+	pub fn evaluate<'a>(&self, eles: &Elements<'a>) -> Result<XPathValue<'a>, Error> {
+		let to_error = |reason| Error::InvalidXPath {
+			context: self.expression.clone(),
+			reason,
+		};
+		if eles.is_empty() {
+			return Ok(eval::evaluate(&self.expr, None).map_err(to_error)?.into());
+		}
+		let mut items: Vec<Item> = Vec::new();
+		for ele in eles.get_ref() {
+			let value = eval::evaluate(&self.expr, Some(ele)).map_err(to_error)?;
+			match value {
+				Value::NodeSet(cur_items) => items.extend(cur_items),
+				value => return Ok(value.into()),
+			}
+		}
+		eval::sort_unique(&mut items);
+		Ok(Value::NodeSet(items).into())
+	}
+}
@@ -0,0 +1,716 @@
+use crate::mesdoc::constants::{
+	NAMESPACE_HTML, NAMESPACE_MATHML, NAMESPACE_SVG, NAMESPACE_XLINK, NAMESPACE_XML, NAMESPACE_XMLNS,
+};
+use crate::mesdoc::error::Error;
+
+#[derive(Debug, Clone, PartialEq)]
+enum Token {
+	LeftParen,
+	RightParen,
+	LeftBracket,
+	RightBracket,
+	Dot,
+	DotDot,
+	At,
+	Comma,
+	ColonColon,
+	// operators
+	Slash,
+	DoubleSlash,
+	Pipe,
+	Plus,
+	Minus,
+	Equal,
+	NotEqual,
+	Less,
+	LessEqual,
+	Greater,
+	GreaterEqual,
+	Multiply,
+	And,
+	Or,
+	Mod,
+	Div,
+	// name test `*`
+	Star,
+	Literal(String),
+	Number(f64),
+	Variable(String),
+	// name test, `name`, `prefix:name`, `prefix:*`
+	Name(Option<String>, String),
+	FunctionName(String),
+	NodeType(String),
+	AxisName(String),
+}
+
+impl Token {
+	fn is_operator(&self) -> bool {
+		use Token::*;
+		matches!(
+			self,
+			Slash
+				| DoubleSlash
+				| Pipe
+				| Plus
+				| Minus
+				| Equal
+				| NotEqual
+				| Less
+				| LessEqual
+				| Greater
+				| GreaterEqual
+				| Multiply
+				| And
+				| Or | Mod
+				| Div
+		)
+	}
+	// https://www.w3.org/TR/xpath-10/#exprlex
+	// `*` is a multiply operator and a name is an operator name after these tokens
+	fn is_operand_end(&self) -> bool {
+		use Token::*;
+		!(self.is_operator() || matches!(self, At | ColonColon | LeftParen | LeftBracket | Comma))
+	}
+	// tokens that can start a location step
+	fn is_step_start(&self) -> bool {
+		use Token::*;
+		matches!(
+			self,
+			Dot | DotDot | At | AxisName(_) | Name(_, _) | Star | NodeType(_)
+		)
+	}
+}
+
+fn is_name_start_char(ch: char) -> bool {
+	ch == '_' || ch.is_alphabetic()
+}
+
+fn is_name_char(ch: char) -> bool {
+	is_name_start_char(ch) || ch.is_numeric() || ch == '-' || ch == '.'
+}
+
+fn tokenize(chars: &[char]) -> Result<Vec<Token>, String> {
+	let total = chars.len();
+	let mut tokens: Vec<Token> = Vec::new();
+	let mut index = 0;
+	let skip_whitespace = |mut index: usize| {
+		while index < total && chars[index].is_ascii_whitespace() {
+			index += 1;
+		}
+		index
+	};
+	let read_name = |mut index: usize| {
+		let start = index;
+		while index < total && is_name_char(chars[index]) {
+			index += 1;
+		}
+		(chars[start..index].iter().collect::<String>(), index)
+	};
+	while index < total {
+		let ch = chars[index];
+		if ch.is_ascii_whitespace() {
+			index += 1;
+			continue;
+		}
+		let next = chars.get(index + 1).copied();
+		let is_operand_end = tokens.last().map(|t| t.is_operand_end()).unwrap_or(false);
+		let token = match ch {
+			'(' => Token::LeftParen,
+			')' => Token::RightParen,
+			'[' => Token::LeftBracket,
+			']' => Token::RightBracket,
+			'@' => Token::At,
+			',' => Token::Comma,
+			'|' => Token::Pipe,
+			'+' => Token::Plus,
+			'-' => Token::Minus,
+			'=' => Token::Equal,
+			'*' if is_operand_end => Token::Multiply,
+			'*' => Token::Star,
+			':' if next == Some(':') => {
+				index += 1;
+				Token::ColonColon
+			}
+			'/' if next == Some('/') => {
+				index += 1;
+				Token::DoubleSlash
+			}
+			'/' => Token::Slash,
+			'!' if next == Some('=') => {
+				index += 1;
+				Token::NotEqual
+			}
+			'<' if next == Some('=') => {
+				index += 1;
+				Token::LessEqual
+			}
+			'<' => Token::Less,
+			'>' if next == Some('=') => {
+				index += 1;
+				Token::GreaterEqual
+			}
+			'>' => Token::Greater,
+			'.' if next == Some('.') => {
+				index += 1;
+				Token::DotDot
+			}
+			'.' if !matches!(next, Some(c) if c.is_ascii_digit()) => Token::Dot,
+			'"' | '\'' => {
+				let start = index + 1;
+				let end = chars[start..]
+					.iter()
+					.position(|&c| c == ch)
+					.ok_or_else(|| String::from("unclosed string literal"))?;
+				index = start + end;
+				Token::Literal(chars[start..index].iter().collect())
+			}
+			'0'..='9' | '.' => {
+				let start = index;
+				while index < total && chars[index].is_ascii_digit() {
+					index += 1;
+				}
+				if index < total && chars[index] == '.' {
+					index += 1;
+					while index < total && chars[index].is_ascii_digit() {
+						index += 1;
+					}
+				}
+				let number = chars[start..index].iter().collect::<String>();
+				tokens.push(Token::Number(
+					number.parse::<f64>().map_err(|e| e.to_string())?,
+				));
+				continue;
+			}
+			'$' => {
+				let (name, end) = read_name(index + 1);
+				if name.is_empty() {
+					return Err(String::from("expect a variable name after '$'"));
+				}
+				tokens.push(Token::Variable(name));
+				index = end;
+				continue;
+			}
+			_ if is_name_start_char(ch) => {
+				let (name, mut end) = read_name(index);
+				if is_operand_end {
+					tokens.push(match name.as_str() {
+						"and" => Token::And,
+						"or" => Token::Or,
+						"mod" => Token::Mod,
+						"div" => Token::Div,
+						_ => return Err(format!("unexpected name '{}', expect an operator", name)),
+					});
+					index = end;
+					continue;
+				}
+				// qualified name, `prefix:name` or `prefix:*`
+				let mut prefix = None;
+				let mut local_name = name;
+				if chars.get(end) == Some(&':') && chars.get(end + 1) != Some(&':') {
+					match chars.get(end + 1) {
+						Some('*') => {
+							prefix = Some(local_name);
+							local_name = String::from("*");
+							end += 2;
+						}
+						Some(&c) if is_name_start_char(c) => {
+							let (name, name_end) = read_name(end + 1);
+							prefix = Some(local_name);
+							local_name = name;
+							end = name_end;
+						}
+						_ => return Err(format!("unexpected ':' after name '{}'", local_name)),
+					}
+				}
+				let after = skip_whitespace(end);
+				let token = match chars.get(after) {
+					Some('(') if prefix.is_none() => match local_name.as_str() {
+						"comment" | "text" | "processing-instruction" | "node" => Token::NodeType(local_name),
+						_ => Token::FunctionName(local_name),
+					},
+					Some(':') if prefix.is_none() && chars.get(after + 1) == Some(&':') => {
+						Token::AxisName(local_name)
+					}
+					Some('(') if local_name != "*" => {
+						return Err(format!(
+							"unsupported function '{}:{}'",
+							prefix.unwrap_or_default(),
+							local_name
+						));
+					}
+					_ => Token::Name(prefix, local_name),
+				};
+				tokens.push(token);
+				index = end;
+				continue;
+			}
+			_ => return Err(format!("unexpected character '{}'", ch)),
+		};
+		tokens.push(token);
+		index += 1;
+	}
+	Ok(tokens)
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Axis {
+	Ancestor,
+	AncestorOrSelf,
+	Attribute,
+	Child,
+	Descendant,
+	DescendantOrSelf,
+	Following,
+	FollowingSibling,
+	Namespace,
+	Parent,
+	Preceding,
+	PrecedingSibling,
+	SelfNode,
+}
+
+impl Axis {
+	fn from_name(name: &str) -> Option<Self> {
+		use Axis::*;
+		Some(match name {
+			"ancestor" => Ancestor,
+			"ancestor-or-self" => AncestorOrSelf,
+			"attribute" => Attribute,
+			"child" => Child,
+			"descendant" => Descendant,
+			"descendant-or-self" => DescendantOrSelf,
+			"following" => Following,
+			"following-sibling" => FollowingSibling,
+			"namespace" => Namespace,
+			"parent" => Parent,
+			"preceding" => Preceding,
+			"preceding-sibling" => PrecedingSibling,
+			"self" => SelfNode,
+			_ => return None,
+		})
+	}
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum NodeTest {
+	// `*`
+	Any,
+	// `prefix:*`
+	NamespaceAny(String, &'static str),
+	// `name`, `prefix:name`
+	Name(Option<(String, &'static str)>, String),
+	Node,
+	Text,
+	Comment,
+	ProcessingInstruction(Option<String>),
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Step {
+	pub axis: Axis,
+	pub test: NodeTest,
+	pub predicates: Vec<Expr>,
+}
+
+impl Step {
+	fn descendant_or_self() -> Self {
+		Step {
+			axis: Axis::DescendantOrSelf,
+			test: NodeTest::Node,
+			predicates: Vec::new(),
+		}
+	}
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum PathStart {
+	Context,
+	Root,
+	Filter(Box<Expr>),
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CompareOp {
+	Equal,
+	NotEqual,
+	Less,
+	LessEqual,
+	Greater,
+	GreaterEqual,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ArithOp {
+	Plus,
+	Minus,
+	Multiply,
+	Div,
+	Mod,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum Expr {
+	Or(Box<Expr>, Box<Expr>),
+	And(Box<Expr>, Box<Expr>),
+	Compare(CompareOp, Box<Expr>, Box<Expr>),
+	Arith(ArithOp, Box<Expr>, Box<Expr>),
+	Negate(Box<Expr>),
+	Union(Box<Expr>, Box<Expr>),
+	Literal(String),
+	Number(f64),
+	Function(&'static str, Vec<Expr>),
+	Filter(Box<Expr>, Vec<Expr>),
+	Path(PathStart, Vec<Step>),
+}
+
+// the core function library, (name, min arguments, max arguments)
+const FUNCTIONS: [(&str, usize, usize); 27] = [
+	("last", 0, 0),
+	("position", 0, 0),
+	("count", 1, 1),
+	("id", 1, 1),
+	("local-name", 0, 1),
+	("namespace-uri", 0, 1),
+	("name", 0, 1),
+	("string", 0, 1),
+	("concat", 2, usize::MAX),
+	("starts-with", 2, 2),
+	("contains", 2, 2),
+	("substring-before", 2, 2),
+	("substring-after", 2, 2),
+	("substring", 2, 3),
+	("string-length", 0, 1),
+	("normalize-space", 0, 1),
+	("translate", 3, 3),
+	("boolean", 1, 1),
+	("not", 1, 1),
+	("true", 0, 0),
+	("false", 0, 0),
+	("lang", 1, 1),
+	("number", 0, 1),
+	("sum", 1, 1),
+	("floor", 1, 1),
+	("ceiling", 1, 1),
+	("round", 1, 1),
+];
+
+// the prefixes can be used in html documents
+fn namespace_of_prefix(prefix: &str) -> Option<&'static str> {
+	Some(match prefix {
+		"html" => NAMESPACE_HTML,
+		"svg" => NAMESPACE_SVG,
+		"math" => NAMESPACE_MATHML,
+		"xlink" => NAMESPACE_XLINK,
+		"xml" => NAMESPACE_XML,
+		"xmlns" => NAMESPACE_XMLNS,
+		_ => return None,
+	})
+}
+
+struct Parser {
+	tokens: Vec<Token>,
+	index: usize,
+}
+
+impl Parser {
+	fn peek(&self) -> Option<&Token> {
+		self.tokens.get(self.index)
+	}
+	fn next(&mut self) -> Option<Token> {
+		let token = self.tokens.get(self.index).cloned();
+		self.index += 1;
+		token
+	}
+	fn eat(&mut self, token: &Token) -> bool {
+		if self.peek() == Some(token) {
+			self.index += 1;
+			return true;
+		}
+		false
+	}
+	fn expect(&mut self, token: &Token, desc: &str) -> Result<(), String> {
+		if self.eat(token) {
+			return Ok(());
+		}
+		Err(match self.peek() {
+			Some(cur) => format!("expect {}, found {:?}", desc, cur),
+			None => format!("expect {}, found the end", desc),
+		})
+	}
+	fn parse_expr(&mut self) -> Result<Expr, String> {
+		let mut expr = self.parse_and()?;
+		while self.eat(&Token::Or) {
+			expr = Expr::Or(Box::new(expr), Box::new(self.parse_and()?));
+		}
+		Ok(expr)
+	}
+	fn parse_and(&mut self) -> Result<Expr, String> {
+		let mut expr = self.parse_equality()?;
+		while self.eat(&Token::And) {
+			expr = Expr::And(Box::new(expr), Box::new(self.parse_equality()?));
+		}
+		Ok(expr)
+	}
+	fn parse_equality(&mut self) -> Result<Expr, String> {
+		let mut expr = self.parse_relational()?;
+		loop {
+			let op = match self.peek() {
+				Some(Token::Equal) => CompareOp::Equal,
+				Some(Token::NotEqual) => CompareOp::NotEqual,
+				_ => break,
+			};
+			self.index += 1;
+			expr = Expr::Compare(op, Box::new(expr), Box::new(self.parse_relational()?));
+		}
+		Ok(expr)
+	}
+	fn parse_relational(&mut self) -> Result<Expr, String> {
+		let mut expr = self.parse_additive()?;
+		loop {
+			let op = match self.peek() {
+				Some(Token::Less) => CompareOp::Less,
+				Some(Token::LessEqual) => CompareOp::LessEqual,
+				Some(Token::Greater) => CompareOp::Greater,
+				Some(Token::GreaterEqual) => CompareOp::GreaterEqual,
+				_ => break,
+			};
+			self.index += 1;
+			expr = Expr::Compare(op, Box::new(expr), Box::new(self.parse_additive()?));
+		}
+		Ok(expr)
+	}
+	fn parse_additive(&mut self) -> Result<Expr, String> {
+		let mut expr = self.parse_multiplicative()?;
+		loop {
+			let op = match self.peek() {
+				Some(Token::Plus) => ArithOp::Plus,
+				Some(Token::Minus) => ArithOp::Minus,
+				_ => break,
+			};
+			self.index += 1;
+			expr = Expr::Arith(op, Box::new(expr), Box::new(self.parse_multiplicative()?));
+		}
+		Ok(expr)
+	}
+	fn parse_multiplicative(&mut self) -> Result<Expr, String> {
+		let mut expr = self.parse_unary()?;
+		loop {
+			let op = match self.peek() {
+				Some(Token::Multiply) => ArithOp::Multiply,
+				Some(Token::Div) => ArithOp::Div,
+				Some(Token::Mod) => ArithOp::Mod,
+				_ => break,
+			};
+			self.index += 1;
+			expr = Expr::Arith(op, Box::new(expr), Box::new(self.parse_unary()?));
+		}
+		Ok(expr)
+	}
+	fn parse_unary(&mut self) -> Result<Expr, String> {
+		if self.eat(&Token::Minus) {
+			return Ok(Expr::Negate(Box::new(self.parse_unary()?)));
+		}
+		self.parse_union()
+	}
+	fn parse_union(&mut self) -> Result<Expr, String> {
+		let mut expr = self.parse_path()?;
+		while self.eat(&Token::Pipe) {
+			expr = Expr::Union(Box::new(expr), Box::new(self.parse_path()?));
+		}
+		Ok(expr)
+	}
+	fn parse_path(&mut self) -> Result<Expr, String> {
+		match self.peek() {
+			Some(Token::Slash) => {
+				self.index += 1;
+				let mut steps = Vec::new();
+				// a single `/` selects the root node
+				if self.peek().map(|t| t.is_step_start()).unwrap_or(false) {
+					self.parse_relative_path(&mut steps)?;
+				}
+				Ok(Expr::Path(PathStart::Root, steps))
+			}
+			Some(Token::DoubleSlash) => {
+				self.index += 1;
+				let mut steps = vec![Step::descendant_or_self()];
+				self.parse_relative_path(&mut steps)?;
+				Ok(Expr::Path(PathStart::Root, steps))
+			}
+			Some(token) if token.is_step_start() => {
+				let mut steps = Vec::new();
+				self.parse_relative_path(&mut steps)?;
+				Ok(Expr::Path(PathStart::Context, steps))
+			}
+			_ => {
+				let primary = self.parse_primary()?;
+				let predicates = self.parse_predicates()?;
+				let filter = if predicates.is_empty() {
+					primary
+				} else {
+					Expr::Filter(Box::new(primary), predicates)
+				};
+				let mut steps = Vec::new();
+				match self.peek() {
+					Some(Token::Slash) => {
+						self.index += 1;
+					}
+					Some(Token::DoubleSlash) => {
+						self.index += 1;
+						steps.push(Step::descendant_or_self());
+					}
+					_ => return Ok(filter),
+				}
+				self.parse_relative_path(&mut steps)?;
+				Ok(Expr::Path(PathStart::Filter(Box::new(filter)), steps))
+			}
+		}
+	}
+	fn parse_relative_path(&mut self, steps: &mut Vec<Step>) -> Result<(), String> {
+		loop {
+			steps.push(self.parse_step()?);
+			match self.peek() {
+				Some(Token::Slash) => {
+					self.index += 1;
+				}
+				Some(Token::DoubleSlash) => {
+					self.index += 1;
+					steps.push(Step::descendant_or_self());
+				}
+				_ => return Ok(()),
+			}
+		}
+	}
+	fn parse_step(&mut self) -> Result<Step, String> {
+		let axis = match self.next() {
+			Some(Token::Dot) => {
+				return Ok(Step {
+					axis: Axis::SelfNode,
+					test: NodeTest::Node,
+					predicates: Vec::new(),
+				});
+			}
+			Some(Token::DotDot) => {
+				return Ok(Step {
+					axis: Axis::Parent,
+					test: NodeTest::Node,
+					predicates: Vec::new(),
+				});
+			}
+			Some(Token::At) => Axis::Attribute,
+			Some(Token::AxisName(name)) => {
+				let axis = Axis::from_name(&name).ok_or_else(|| format!("unknown axis '{}'", name))?;
+				self.expect(&Token::ColonColon, "'::'")?;
+				axis
+			}
+			_ => {
+				self.index -= 1;
+				Axis::Child
+			}
+		};
+		let test = match self.next() {
+			Some(Token::Star) => NodeTest::Any,
+			Some(Token::Name(None, name)) => NodeTest::Name(None, name),
+			Some(Token::Name(Some(prefix), name)) => {
+				let namespace = namespace_of_prefix(&prefix)
+					.ok_or_else(|| format!("undeclared namespace prefix '{}'", prefix))?;
+				if name == "*" {
+					NodeTest::NamespaceAny(prefix, namespace)
+				} else {
+					NodeTest::Name(Some((prefix, namespace)), name)
+				}
+			}
+			Some(Token::NodeType(node_type)) => {
+				self.expect(&Token::LeftParen, "'('")?;
+				let test = match node_type.as_str() {
+					"node" => NodeTest::Node,
+					"text" => NodeTest::Text,
+					"comment" => NodeTest::Comment,
+					_ => match self.peek() {
+						Some(Token::Literal(target)) => {
+							let target = target.clone();
+							self.index += 1;
+							NodeTest::ProcessingInstruction(Some(target))
+						}
+						_ => NodeTest::ProcessingInstruction(None),
+					},
+				};
+				self.expect(&Token::RightParen, "')'")?;
+				test
+			}
+			Some(token) => return Err(format!("expect a node test, found {:?}", token)),
+			None => return Err(String::from("expect a node test, found the end")),
+		};
+		let predicates = self.parse_predicates()?;
+		Ok(Step {
+			axis,
+			test,
+			predicates,
+		})
+	}
+	fn parse_predicates(&mut self) -> Result<Vec<Expr>, String> {
+		let mut predicates = Vec::new();
+		while self.eat(&Token::LeftBracket) {
+			predicates.push(self.parse_expr()?);
+			self.expect(&Token::RightBracket, "']'")?;
+		}
+		Ok(predicates)
+	}
+	fn parse_primary(&mut self) -> Result<Expr, String> {
+		match self.next() {
+			Some(Token::LeftParen) => {
+				let expr = self.parse_expr()?;
+				self.expect(&Token::RightParen, "')'")?;
+				Ok(expr)
+			}
+			Some(Token::Literal(value)) => Ok(Expr::Literal(value)),
+			Some(Token::Number(value)) => Ok(Expr::Number(value)),
+			Some(Token::Variable(name)) => Err(format!("unsupported variable reference '${}'", name)),
+			Some(Token::FunctionName(name)) => {
+				let &(name, min, max) = FUNCTIONS
+					.iter()
+					.find(|(func, _, _)| *func == name)
+					.ok_or_else(|| format!("unknown function '{}'", name))?;
+				self.expect(&Token::LeftParen, "'('")?;
+				let mut args = Vec::new();
+				if !self.eat(&Token::RightParen) {
+					loop {
+						args.push(self.parse_expr()?);
+						if self.eat(&Token::Comma) {
+							continue;
+						}
+						self.expect(&Token::RightParen, "')'")?;
+						break;
+					}
+				}
+				if args.len() < min || args.len() > max {
+					return Err(format!(
+						"function '{}' does not accept {} arguments",
+						name,
+						args.len()
+					));
+				}
+				Ok(Expr::Function(name, args))
+			}
+			Some(token) => Err(format!("unexpected token {:?}", token)),
+			None => Err(String::from("unexpected end of the expression")),
+		}
+	}
+}
+
+pub fn parse(expression: &str) -> Result<Expr, Error> {
+	let to_error = |reason: String| Error::InvalidXPath {
+		context: String::from(expression),
+		reason,
+	};
+	let chars: Vec<char> = expression.chars().collect();
+	let tokens = tokenize(&chars).map_err(to_error)?;
+	if tokens.is_empty() {
+		return Err(to_error(String::from("empty expression")));
+	}
+	let mut parser = Parser { tokens, index: 0 };
+	let expr = parser.parse_expr().map_err(to_error)?;
+	if let Some(token) = parser.peek() {
+		return Err(to_error(format!("unexpected token {:?}", token)));
+	}
+	Ok(expr)
+}
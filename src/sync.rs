@@ -0,0 +1,735 @@
+// feature="insertion"
+cfg_feat_insertion! {
+	use crate::mesdoc::interface::InsertPosition;
+}
+use crate::check_if_content_tag;
+use crate::mesdoc::error::{BoxDynError, Error as IError};
+use crate::mesdoc::interface::{
+	BoxDynElement, BoxDynNode, BoxDynText, BoxDynUncareNode, Elements, IAttrValue, IDocumentTrait,
+	IElementTrait, IEnumTyped, INodeTrait, INodeType, ITextTrait, IUncareNodeTrait, MaybeDoc,
+	MaybeElement,
+};
+use rphtml::{
+	entity::{decode_chars, encode_chars, EncodeType, EntitySet},
+	parser::{Node, NodeType, RefNode},
+};
+use std::any::Any;
+use std::collections::VecDeque;
+use std::convert::TryFrom;
+use std::ops::Range;
+use std::rc::Rc;
+use std::sync::Arc;
+
+/// The index of the node in the arena of a `SyncDocument`
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+struct NodeId(usize);
+
+// the root node is always the first node in the arena
+const ROOT_ID: NodeId = NodeId(0);
+
+// the node types of the snapshot
+enum NodeKind {
+	Root { is_document: bool },
+	Tag(TagData),
+	Text,
+	Comment,
+	CData,
+	// the rendered doctype
+	Doctype(Range<usize>),
+	Other,
+}
+
+struct SyncAttr {
+	key: Option<String>,
+	value: Option<(String, Option<char>)>,
+}
+
+// the characters are saved in the arena, the ranges are the positions
+struct TagData {
+	// the lowercase tag name
+	name: Range<usize>,
+	// the rendered start tag and end tag
+	start_tag: Range<usize>,
+	end_tag: Option<Range<usize>>,
+	// the range of the attributes in the arena
+	attrs: Range<usize>,
+	// 'textarea' or 'title', the content need encode in html
+	is_plain_text: bool,
+}
+
+struct NodeData {
+	kind: NodeKind,
+	index: usize,
+	// the content of the text, comment and content tag nodes
+	content: Option<Range<usize>>,
+	parent: Option<NodeId>,
+	// the childs are added together, so the ids are contiguous
+	childs: Range<usize>,
+}
+
+// the render options, the same as the options used by the `Rc<RefCell<Node>>`
+#[derive(Clone, Copy)]
+struct RenderMode {
+	// render the tags and the comments, `false` when get the inner text
+	with_tag: bool,
+	decode_entity: bool,
+	// encode the content of the 'textarea' and 'title' tags
+	encode_content: bool,
+}
+
+const HTML_MODE: RenderMode = RenderMode {
+	with_tag: true,
+	decode_entity: false,
+	encode_content: true,
+};
+
+// all the nodes of a document, they are dropped together with the arena
+struct Arena {
+	nodes: Vec<NodeData>,
+	chars: Vec<char>,
+	attrs: Vec<SyncAttr>,
+}
+
+impl Arena {
+	// copy the rphtml nodes in breadth-first order, so the childs of a node are contiguous
+	fn from_root(root: &RefNode) -> Self {
+		let mut arena = Arena {
+			nodes: Vec::new(),
+			chars: Vec::new(),
+			attrs: Vec::new(),
+		};
+		let mut queue: VecDeque<(RefNode, Option<NodeId>)> = VecDeque::new();
+		queue.push_back((Rc::clone(root), None));
+		let mut total = 1;
+		while let Some((node, parent)) = queue.pop_front() {
+			let id = NodeId(arena.nodes.len());
+			let node = node.borrow();
+			let childs = node.childs.as_deref().unwrap_or_default();
+			for child in childs {
+				queue.push_back((Rc::clone(child), Some(id)));
+			}
+			let kind = arena.add_kind(&node);
+			let content = node
+				.content
+				.as_ref()
+				.map(|content| arena.add_chars(content.iter().copied()));
+			arena.nodes.push(NodeData {
+				kind,
+				index: node.index,
+				content,
+				parent,
+				childs: total..total + childs.len(),
+			});
+			total += childs.len();
+		}
+		arena
+	}
+	fn add_chars(&mut self, chars: impl IntoIterator<Item = char>) -> Range<usize> {
+		let start = self.chars.len();
+		self.chars.extend(chars);
+		start..self.chars.len()
+	}
+	fn add_kind(&mut self, node: &Node) -> NodeKind {
+		match node.node_type {
+			NodeType::AbstractRoot => NodeKind::Root {
+				is_document: node.is_document().0,
+			},
+			NodeType::Tag => {
+				let meta = node
+					.meta
+					.as_ref()
+					.expect("Element node must have a meta field.")
+					.borrow();
+				let name = self.add_chars(meta.name.iter().map(|ch| ch.to_ascii_lowercase()));
+				let is_plain_text = matches!(
+					&self.chars[name.clone()],
+					['t', 'i', 't', 'l', 'e'] | ['t', 'e', 'x', 't', 'a', 'r', 'e', 'a']
+				);
+				let start = self.chars.len();
+				self.chars.push('<');
+				self.chars.extend_from_slice(&meta.name);
+				self.chars.extend(meta.attrs_to_string(false));
+				if meta.self_closed {
+					self.chars.extend_from_slice(&[' ', '/']);
+				}
+				self.chars.push('>');
+				let start_tag = start..self.chars.len();
+				let end_tag = node.end_tag.as_ref().map(|end_tag| {
+					let start = self.chars.len();
+					self.chars.extend_from_slice(&['<', '/']);
+					self.chars.extend(end_tag.borrow().content.iter().flatten());
+					self.chars.push('>');
+					start..self.chars.len()
+				});
+				let start = self.attrs.len();
+				self.attrs.extend(meta.attrs.iter().map(|attr| SyncAttr {
+					key: attr.key.as_ref().map(|key| key.content.iter().collect()),
+					value: attr
+						.value
+						.as_ref()
+						.map(|value| (value.content.iter().collect(), attr.quote)),
+				}));
+				NodeKind::Tag(TagData {
+					name,
+					start_tag,
+					end_tag,
+					attrs: start..self.attrs.len(),
+					is_plain_text,
+				})
+			}
+			NodeType::Text | NodeType::SpacesBetweenTag => NodeKind::Text,
+			NodeType::Comment => NodeKind::Comment,
+			NodeType::XMLCDATA => NodeKind::CData,
+			NodeType::HTMLDOCTYPE => {
+				let meta = node
+					.meta
+					.as_ref()
+					.expect("Doctype node must have a meta field.")
+					.borrow();
+				let start = self.chars.len();
+				self.chars.extend_from_slice(&['<', '!']);
+				self.chars.extend_from_slice(&meta.name);
+				self.chars.extend(meta.attrs_to_string(false));
+				self.chars.push('>');
+				NodeKind::Doctype(start..self.chars.len())
+			}
+			_ => NodeKind::Other,
+		}
+	}
+	fn node(&self, id: NodeId) -> &NodeData {
+		&self.nodes[id.0]
+	}
+	fn chars(&self, range: &Range<usize>) -> &[char] {
+		&self.chars[range.clone()]
+	}
+	fn is_element(&self, id: NodeId) -> bool {
+		matches!(self.node(id).kind, NodeKind::Tag(_))
+	}
+	// the attributes of the tag, the first one is used when the names are duplicate
+	fn attrs(&self, id: NodeId) -> &[SyncAttr] {
+		match &self.node(id).kind {
+			NodeKind::Tag(tag) => &self.attrs[tag.attrs.clone()],
+			_ => &[],
+		}
+	}
+	// render the node and its childs
+	fn build(&self, id: NodeId, mode: RenderMode) -> Vec<char> {
+		let mut result = Vec::with_capacity(50);
+		self.build_tree(id, mode, &mut result);
+		result
+	}
+	fn build_tree(&self, id: NodeId, mode: RenderMode, result: &mut Vec<char>) {
+		self.build_node(id, mode, result);
+		for child in self.node(id).childs.clone() {
+			self.build_tree(NodeId(child), mode, result);
+		}
+		if let NodeKind::Tag(tag) = &self.node(id).kind {
+			if let Some(end_tag) = tag.end_tag.as_ref().filter(|_| mode.with_tag) {
+				result.extend_from_slice(self.chars(end_tag));
+			}
+		}
+	}
+	fn build_node(&self, id: NodeId, mode: RenderMode, result: &mut Vec<char>) {
+		let node = self.node(id);
+		let content = node
+			.content
+			.as_ref()
+			.map(|content| self.chars(content))
+			.unwrap_or_default();
+		match &node.kind {
+			NodeKind::Text => {
+				if mode.decode_entity {
+					result.extend_from_slice(&decode_chars(content));
+				} else {
+					result.extend_from_slice(content);
+				}
+			}
+			NodeKind::Tag(tag) => {
+				if mode.with_tag {
+					result.extend_from_slice(self.chars(&tag.start_tag));
+				}
+				// content for the special tags, such as style/script
+				if mode.encode_content && tag.is_plain_text {
+					result.extend(encode_chars(content, EntitySet::Html, EncodeType::Named));
+				} else {
+					result.extend_from_slice(content);
+				}
+			}
+			NodeKind::Comment => {
+				// the inner text doesn't contain the comments
+				if mode.with_tag {
+					result.extend_from_slice(&['<', '!', '-', '-']);
+					result.extend_from_slice(content);
+					result.extend_from_slice(&['-', '-', '>']);
+				}
+			}
+			NodeKind::CData => {
+				result.extend_from_slice(&['<', '!', '[', 'C', 'D', 'A', 'T', 'A', '[']);
+				result.extend_from_slice(content);
+				result.extend_from_slice(&[']', ']', '>']);
+			}
+			NodeKind::Doctype(doctype) => result.extend_from_slice(self.chars(doctype)),
+			NodeKind::Root { .. } | NodeKind::Other => {}
+		}
+	}
+	// find the first element with the id in the document order
+	fn find_by_id(&self, parent: NodeId, id: &str) -> Option<NodeId> {
+		for child in self.node(parent).childs.clone().map(NodeId) {
+			if !self.is_element(child) {
+				continue;
+			}
+			let is_matched = self.attrs(child).iter().any(|attr| {
+				matches!((&attr.key, &attr.value), (Some(key), Some((value, _))) if key.eq_ignore_ascii_case("id") && value == id)
+			});
+			if is_matched {
+				return Some(child);
+			}
+			if let Some(node) = self.find_by_id(child, id) {
+				return Some(node);
+			}
+		}
+		None
+	}
+}
+
+// the handle of a node, the arena is kept alive by the handles
+#[derive(Clone)]
+struct SyncNode {
+	arena: Arc<Arena>,
+	id: NodeId,
+}
+
+impl SyncNode {
+	fn data(&self) -> &NodeData {
+		self.arena.node(self.id)
+	}
+	fn with_id(&self, id: NodeId) -> Self {
+		SyncNode {
+			arena: Arc::clone(&self.arena),
+			id,
+		}
+	}
+	fn build(&self, mode: RenderMode) -> Vec<char> {
+		self.arena.build(self.id, mode)
+	}
+}
+
+// the snapshot can't be changed, the mutation methods will trigger an error
+fn read_only(node: &SyncNode, method: &str) {
+	if let Some(doc) = &node.owner_document() {
+		doc.trigger_error(Box::new(IError::InvalidTraitMethodCall {
+			method: String::from(method),
+			message: String::from("The sync document is read-only."),
+		}));
+	}
+}
+
+impl INodeTrait for SyncNode {
+	/// impl `to_node`
+	fn to_node(self: Box<Self>) -> Box<dyn Any> {
+		self
+	}
+
+	/// impl `index`
+	fn index(&self) -> usize {
+		self.data().index
+	}
+
+	/// impl `clone_node`
+	fn clone_node<'b>(&self) -> BoxDynNode<'b> {
+		Box::new(self.clone())
+	}
+
+	/// impl `typed`
+	fn typed<'b>(self: Box<Self>) -> IEnumTyped<'b> {
+		match self.node_type() {
+			INodeType::Element | INodeType::DocumentFragement | INodeType::Document => {
+				IEnumTyped::Element(self as BoxDynElement)
+			}
+			INodeType::Text => IEnumTyped::Text(self as BoxDynText),
+			_ => IEnumTyped::UncareNode(self as BoxDynUncareNode),
+		}
+	}
+
+	/// impl `node_type`
+	fn node_type(&self) -> INodeType {
+		match &self.data().kind {
+			NodeKind::Root { is_document: true } => INodeType::Document,
+			NodeKind::Root { is_document: false } => INodeType::DocumentFragement,
+			NodeKind::Tag(_) => INodeType::Element,
+			NodeKind::Text => INodeType::Text,
+			NodeKind::Comment => INodeType::Comment,
+			NodeKind::CData => INodeType::XMLCDATA,
+			NodeKind::Doctype(_) => INodeType::HTMLDOCTYPE,
+			NodeKind::Other => INodeType::Other,
+		}
+	}
+
+	/// impl `parent`
+	fn parent<'b>(&self) -> MaybeElement<'b> {
+		self
+			.data()
+			.parent
+			.map(|parent| Box::new(self.with_id(parent)) as BoxDynElement)
+	}
+
+	/// impl `owner_document`
+	fn owner_document(&self) -> MaybeDoc<'_> {
+		Some(Box::new(SyncDocument {
+			arena: Arc::clone(&self.arena),
+		}))
+	}
+
+	/// impl `text_contents`
+	fn text_contents(&self) -> Vec<char> {
+		self.build(RenderMode {
+			with_tag: !self.arena.is_element(self.id),
+			decode_entity: true,
+			encode_content: false,
+		})
+	}
+
+	/// impl `text_chars`
+	fn text_chars(&self) -> Vec<char> {
+		self.build(RenderMode {
+			with_tag: !self.arena.is_element(self.id),
+			decode_entity: false,
+			encode_content: false,
+		})
+	}
+
+	/// impl `set_text`
+	fn set_text(&mut self, _content: &str) {
+		read_only(self, "set_text");
+	}
+
+	/// impl `set_html`
+	fn set_html(&mut self, _content: &str) {
+		read_only(self, "set_html");
+	}
+}
+
+impl ITextTrait for SyncNode {
+	cfg_feat_text! {
+		/// impl `remove`
+		fn remove(self: Box<Self>) {
+			read_only(&self, "remove");
+		}
+
+		/// impl `append_text`
+		fn append_text(&mut self, _content: &str) {
+			read_only(self, "append_text");
+		}
+
+		/// impl `prepend_text`
+		fn prepend_text(&mut self, _content: &str) {
+			read_only(self, "prepend_text");
+		}
+	}
+}
+
+impl IUncareNodeTrait for SyncNode {}
+
+impl IElementTrait for SyncNode {
+	/// impl `tag_names`
+	fn tag_names(&self) -> Vec<char> {
+		match &self.data().kind {
+			NodeKind::Tag(tag) => return self.arena.chars(&tag.name).to_vec(),
+			NodeKind::Root { .. } => {}
+			_ => {
+				if let Some(doc) = &self.owner_document() {
+					doc.trigger_error(Box::new(IError::InvalidTraitMethodCall {
+						method: String::from("tag_name"),
+						message: format!(
+							"The node type of '{:?}' doesn't have a tag name.",
+							self.node_type()
+						),
+					}));
+				}
+			}
+		}
+		vec![]
+	}
+
+	/// impl `child_nodes_length`
+	fn child_nodes_length(&self) -> usize {
+		self.data().childs.len()
+	}
+
+	/// impl `child_nodes_item`
+	fn child_nodes_item<'b>(&self, index: usize) -> Option<BoxDynNode<'b>> {
+		let childs = &self.data().childs;
+		if index < childs.len() {
+			return Some(Box::new(self.with_id(NodeId(childs.start + index))));
+		}
+		None
+	}
+
+	/// impl `child_nodes_item_since_by`
+	fn child_nodes_item_since_by<'a>(
+		&'a self,
+		node_index: usize,
+		reverse: bool,
+		mut handle: Box<dyn FnMut(&dyn IElementTrait) -> bool + 'a>,
+	) {
+		let childs = self.data().childs.clone();
+		let mut handle_element =
+			|id: usize| !self.arena.is_element(NodeId(id)) || handle(&self.with_id(NodeId(id)));
+		if reverse {
+			for id in (childs.start..=childs.start + node_index).rev() {
+				if !handle_element(id) {
+					break;
+				}
+			}
+		} else {
+			for id in childs.start + node_index..childs.end {
+				if !handle_element(id) {
+					break;
+				}
+			}
+		}
+	}
+
+	/// impl `children_by`
+	fn children_by<'a>(&'a self, mut matcher: Box<dyn FnMut(&dyn IElementTrait) + 'a>) {
+		for id in self.data().childs.clone().map(NodeId) {
+			if self.arena.is_element(id) {
+				matcher(&self.with_id(id));
+			}
+		}
+	}
+
+	/// impl `get_attribute`
+	fn get_attribute(&self, name: &str) -> Option<IAttrValue> {
+		let attr = self
+			.arena
+			.attrs(self.id)
+			.iter()
+			.find(|attr| matches!(&attr.key, Some(key) if key.eq_ignore_ascii_case(name)))?;
+		Some(match &attr.value {
+			Some((value, quote)) => IAttrValue::Value(value.clone(), *quote),
+			None => IAttrValue::True,
+		})
+	}
+
+	/// impl `get_attribute_names`
+	fn get_attribute_names(&self) -> Vec<String> {
+		self
+			.arena
+			.attrs(self.id)
+			.iter()
+			.filter_map(|attr| attr.key.clone())
+			.collect()
+	}
+
+	/// impl `set_attribute`
+	fn set_attribute(&mut self, _name: &str, _value: Option<&str>) {
+		read_only(self, "set_attribute");
+	}
+
+	/// impl `remove_attribute`
+	fn remove_attribute(&mut self, _name: &str) {
+		read_only(self, "remove_attribute");
+	}
+
+	/// impl `inner_html`
+	fn inner_html(&self) -> String {
+		let mut result = Vec::with_capacity(50);
+		self.arena.build_node(
+			self.id,
+			RenderMode {
+				with_tag: false,
+				..HTML_MODE
+			},
+			&mut result,
+		);
+		for child in self.data().childs.clone() {
+			self.arena.build_tree(NodeId(child), HTML_MODE, &mut result);
+		}
+		result.iter().collect()
+	}
+
+	/// impl `outer_html`
+	fn outer_html(&self) -> String {
+		self.build(HTML_MODE).iter().collect()
+	}
+
+	// when the feature `destory` or `insertion` is open
+	cfg_feat_mutation! {
+		/// impl `remove_child`
+		fn remove_child(&mut self, _ele: BoxDynElement) {
+			read_only(self, "remove_child");
+		}
+	}
+	// when the feature `insertion` is open
+	cfg_feat_insertion! {
+		/// impl `insert_adjacent`
+		fn insert_adjacent(&mut self, position: &InsertPosition, _node: &BoxDynElement) {
+			read_only(self, position.action());
+		}
+	}
+
+	/// impl `into_text`
+	fn into_text<'b>(self: Box<Self>) -> Result<BoxDynText<'b>, BoxDynError> {
+		if check_if_content_tag(&self.tag_names()) {
+			Ok(self as BoxDynText)
+		} else {
+			Err(Box::new(IError::InvalidTraitMethodCall {
+				method: "into_text".into(),
+				message: "Can't call 'into_text' with tags those are not content tags.".into(),
+			}))
+		}
+	}
+
+	/// impl `is`
+	fn is(&self, ele: &BoxDynElement) -> bool {
+		let specified: Box<dyn Any> = ele.cloned().to_node();
+		if let Ok(node) = specified.downcast::<SyncNode>() {
+			return self.id == node.id && Arc::ptr_eq(&self.arena, &node.arena);
+		}
+		false
+	}
+
+	/// impl `is_root_element`
+	fn is_root_element(&self) -> bool {
+		self.id == ROOT_ID
+	}
+}
+
+/// A read-only snapshot of the parsed document, it's `Send` and `Sync` so it can be shared across threads.
+///
+/// The nodes are saved in an arena and referenced by their indexes, the elements are light handles
+/// those keep the arena alive, so the whole document is dropped at once.
+/// The arena is copied from the tree built by the `rphtml` parser, so `Vis::load_sync` is slower than `Vis::load`,
+/// it pays off when the document is queried many times or shared across threads.
+/// The nodes are immutable, the mutation methods of the elements do nothing but trigger an error,
+/// and there is no error handle, use the `try_*` methods to get the selector errors.
+///
+/// ```
+/// use std::sync::Arc;
+/// use std::thread;
+/// use visdom::Vis;
+/// use visdom::types::BoxDynError;
+/// fn main()-> Result<(), BoxDynError>{
+///   let html = r##"<ul><li>a</li><li class="b">b</li><li>c</li></ul>"##;
+///   let doc = Arc::new(Vis::load_sync(html)?);
+///   let worker = {
+///     let doc = Arc::clone(&doc);
+///     thread::spawn(move || doc.elements().find("li.b").text())
+///   };
+///   assert_eq!(worker.join().unwrap(), "b");
+///   assert_eq!(doc.elements().find("li").length(), 3);
+///   Ok(())
+/// }
+/// ```
+#[derive(Clone)]
+pub struct SyncDocument {
+	arena: Arc<Arena>,
+}
+
+impl SyncDocument {
+	pub(crate) fn from_root(root: &RefNode) -> Self {
+		SyncDocument {
+			arena: Arc::new(Arena::from_root(root)),
+		}
+	}
+	// the handle of the root node
+	fn root(&self) -> SyncNode {
+		SyncNode {
+			arena: Arc::clone(&self.arena),
+			id: ROOT_ID,
+		}
+	}
+	/// Get an elements set of the document, it can be used in the current thread.
+	pub fn elements<'b>(&self) -> Elements<'b> {
+		Elements::with_all(vec![Box::new(self.root())], Some(Box::new(self.clone())))
+	}
+}
+
+impl IDocumentTrait for SyncDocument {
+	// get element by id
+	fn get_element_by_id<'b>(&self, id: &str) -> Option<BoxDynElement<'b>> {
+		let root = self.root();
+		self
+			.arena
+			.find_by_id(ROOT_ID, id)
+			.map(|id| Box::new(root.with_id(id)) as BoxDynElement)
+	}
+	// source code
+	fn source_code(&self) -> String {
+		self
+			.root()
+			.build(RenderMode {
+				encode_content: false,
+				..HTML_MODE
+			})
+			.iter()
+			.collect()
+	}
+	// get root node
+	fn get_root_node<'b>(&self) -> BoxDynNode<'b> {
+		Box::new(self.root())
+	}
+}
+
+/// The nodes of the `SyncDocument` those can be sent to other threads, the documents of the nodes are kept alive with them.
+///
+/// ```
+/// use std::convert::TryFrom;
+/// use std::thread;
+/// use visdom::{SyncElements, Vis};
+/// use visdom::types::BoxDynError;
+/// fn main()-> Result<(), BoxDynError>{
+///   let doc = Vis::load_sync(r##"<div><a href="/a">a</a><a href="/b">b</a></div>"##)?;
+///   let links = SyncElements::try_from(&doc.elements().find("a"))?;
+///   let hrefs = thread::spawn(move || links.elements().extract("::attr(href)"))
+///     .join()
+///     .unwrap()?;
+///   assert_eq!(hrefs, vec!["/a", "/b"]);
+///   // the nodes not in a sync document can't be converted
+///   assert!(SyncElements::try_from(&Vis::load("<a></a>")?.find("a")).is_err());
+///   Ok(())
+/// }
+/// ```
+#[derive(Clone, Default)]
+pub struct SyncElements {
+	nodes: Vec<SyncNode>,
+}
+
+impl SyncElements {
+	/// Get an elements set of the nodes, it can be used in the current thread.
+	pub fn elements(&self) -> Elements<'_> {
+		Elements::with_nodes(
+			self
+				.nodes
+				.iter()
+				.map(|node| Box::new(node.clone()) as BoxDynElement)
+				.collect(),
+		)
+	}
+	/// the count of the nodes
+	pub fn length(&self) -> usize {
+		self.nodes.len()
+	}
+	/// check if there is no node
+	pub fn is_empty(&self) -> bool {
+		self.nodes.is_empty()
+	}
+}
+
+impl<'a> TryFrom<&Elements<'a>> for SyncElements {
+	type Error = IError;
+	fn try_from(eles: &Elements<'a>) -> Result<Self, Self::Error> {
+		let mut result = SyncElements::default();
+		for ele in eles.get_ref() {
+			let node = ele.cloned().to_node().downcast::<SyncNode>().map_err(|_| {
+				IError::InvalidTraitMethodCall {
+					method: String::from("try_from"),
+					message: String::from("Only the nodes of a sync document can be converted."),
+				}
+			})?;
+			result.nodes.push(*node);
+		}
+		Ok(result)
+	}
+}
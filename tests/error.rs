@@ -1,4 +1,7 @@
-use visdom::{types::BoxDynError, Vis};
+use visdom::{
+	types::{BoxDynError, Elements, Error, SelectorErrorKind},
+	Vis,
+};
 
 #[test]
 #[should_panic]
@@ -73,3 +76,141 @@ fn test_wrong_selector_catch() {
 	// no pseudo selector ":all-child"
 	let _ = root.find("a:all-childs");
 }
+
+#[test]
+fn test_selector_error_kind() {
+	fn parse_error(selector: &str) -> (SelectorErrorKind, usize, String) {
+		let error = Vis::compile(selector).unwrap_err();
+		match error.downcast::<Error>().map(|e| *e) {
+			Ok(Error::InvalidSelector {
+				kind,
+				context,
+				offset,
+				token,
+			}) => {
+				assert_eq!(context, selector);
+				(kind, offset, token)
+			}
+			_ => unreachable!(),
+		}
+	}
+	assert_eq!(
+		parse_error("a > > b"),
+		(
+			SelectorErrorKind::UnexpectedCombinator,
+			4,
+			String::from(">")
+		)
+	);
+	assert_eq!(
+		parse_error("a,,b"),
+		(SelectorErrorKind::EmptySelector, 2, String::from(","))
+	);
+	assert_eq!(
+		parse_error(",a"),
+		(SelectorErrorKind::EmptySelector, 0, String::from(","))
+	);
+	assert_eq!(
+		parse_error("div >"),
+		(SelectorErrorKind::UnexpectedEnd, 5, String::new())
+	);
+	assert_eq!(
+		parse_error("a:all-childs b"),
+		(
+			SelectorErrorKind::UnexpectedToken,
+			1,
+			String::from(":all-childs")
+		)
+	);
+	// the offset is counted by bytes
+	assert_eq!(
+		parse_error("[title='中文'] !a"),
+		(SelectorErrorKind::UnexpectedToken, 17, String::from("!a"))
+	);
+	// nested selectors
+	assert_eq!(
+		parse_error("ul:not(li > > a)"),
+		(
+			SelectorErrorKind::UnexpectedCombinator,
+			12,
+			String::from(">")
+		)
+	);
+	assert_eq!(
+		parse_error("ul:has(li:unknown)"),
+		(
+			SelectorErrorKind::UnexpectedToken,
+			9,
+			String::from(":unknown)")
+		)
+	);
+	assert_eq!(
+		parse_error("ul:not(  )"),
+		(SelectorErrorKind::EmptySelector, 9, String::from(")"))
+	);
+	assert_eq!(
+		parse_error("ul:not(li"),
+		(SelectorErrorKind::UnexpectedEnd, 9, String::new())
+	);
+	// too large nth numbers
+	assert_eq!(
+		parse_error("li:nth-child(99999999999999999999n)").0,
+		SelectorErrorKind::UnexpectedToken
+	);
+	assert!(parse_error("a + ~ b").1 == 4);
+	assert!(Vis::compile("li:nth-child(2147483647n+1)").is_ok());
+}
+
+#[test]
+fn test_try_methods() -> Result<(), BoxDynError> {
+	let html = r#"
+  <html>
+    <head></head>
+    <body>
+      <ul>
+        <li class="first">a</li>
+        <li>b</li>
+      </ul>
+    </body>
+  </html>
+  "#;
+	let root = Vis::load_catch(
+		html,
+		Box::new(|e: BoxDynError| {
+			// the try methods never trigger the error handle
+			panic!("{:?}", e.to_string());
+		}),
+	);
+	let items = root.try_find("li")?;
+	assert_eq!(items.length(), 2);
+	assert!(matches!(
+		root.try_find("ul >"),
+		Err(Error::InvalidSelector {
+			kind: SelectorErrorKind::UnexpectedEnd,
+			..
+		})
+	));
+	assert!(items.try_is("li:unknown").is_err());
+	assert!(items.try_is_all("li").unwrap());
+	assert!(items.try_filter("li,,a").is_err());
+	assert_eq!(items.try_not(".first")?.text(), "b");
+	assert!(items.try_closest("ul >").is_err());
+	assert_eq!(items.try_closest("ul")?.length(), 1);
+	assert!(items.try_parent("ul >").is_err());
+	assert!(items.try_parents("ul:not(").is_err());
+	assert!(items.try_parents_until("body", "ul >", false).is_err());
+	assert!(items.try_prev("!li").is_err());
+	assert!(items.try_next_all("!li").is_err());
+	assert!(items.try_next_until("", "!li", false).is_err());
+	assert!(items.try_siblings("li >").is_err());
+	assert_eq!(items.try_siblings("")?.length(), 2);
+	assert!(root.try_children("!html").is_err());
+	assert!(root.try_has("li:unknown").is_err());
+	// validated even if the elements is empty
+	assert!(root.find("table").try_find("ul >").is_err());
+	// the elements created by nodes
+	let eles = Elements::with_nodes(items.get_ref().iter().map(|e| e.cloned()).collect());
+	assert!(eles.try_is("li").unwrap());
+	assert!(eles.try_is("li >").is_err());
+	Ok(())
+}
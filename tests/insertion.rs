@@ -210,3 +210,23 @@ fn test_append_wrong_parent() {
 	let mut parent = root.find("#main");
 	child.append(&mut parent);
 }
+
+#[test]
+fn test_append_with_index() -> Result {
+	let root = Vis::load(r#"<div class="parent"><b class="item">1</b></div>"#)?;
+	let other = Vis::load(r#"<p><b class="item">2</b><b class="item">3</b></p>"#)?;
+	assert_eq!(root.find(".item").length(), 1);
+	assert_eq!(other.find(".item").length(), 2);
+	let mut parent = root.find(".parent");
+	parent.append(&mut other.find(".item").eq(0));
+	assert_eq!(root.find(".item").text(), "12");
+	assert_eq!(other.find(".item").text(), "3");
+	// change the moved element
+	root.find("b").eq(1).set_attr("class", Some("moved"));
+	assert_eq!(root.find(".item").text(), "1");
+	assert_eq!(root.find(".moved").text(), "2");
+	// prepend the new elements
+	parent.prepend(&mut Vis::load(r#"<b class="item">0</b>"#)?);
+	assert_eq!(root.find(".item").text(), "01");
+	Ok(())
+}
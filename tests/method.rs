@@ -1,5 +1,7 @@
 use std::result::Result as StdResult;
-use visdom::types::{BoxDynError, Combinator, Elements, IAttrValue};
+use visdom::types::{
+	BoxDynError, Combinator, Elements, Error, IAttrValue, SelectorErrorKind, UniqueSelectorOptions,
+};
 use visdom::Vis;
 type Result = StdResult<(), BoxDynError>;
 
@@ -707,13 +709,67 @@ fn test_method_closest() -> Result {
 	)?;
 	let abc = root.find("a,b,c");
 	assert_eq!(abc.length(), 4);
-	// wrong selector :first
+	// positional selectors never match, same as jquery
 	assert_eq!(abc.closest(":first").length(), 0);
 	// empty selector, always return empty elements
 	assert_eq!(abc.closest("").length(), 0);
 	let closest = abc.closest(".closest");
 	assert_eq!(closest.length(), 3);
 	assert_eq!(closest.eq(0).get(0).unwrap().tag_name(), "DIV");
+	// the lookup selector
+	let closest = abc.closest("div .closest");
+	assert_eq!(closest.length(), 2);
+	assert_eq!(closest.text(), "aaabbb");
+	Ok(())
+}
+
+#[test]
+fn test_method_compiled() -> Result {
+	let root = Vis::load(HTML)?;
+	let selector = Vis::compile("div .class")?;
+	assert_eq!(selector.as_str(), "div .class");
+	let finded = root.find_compiled(&selector);
+	assert_eq!(finded.length(), root.find("div .class").length());
+	assert!(!finded.is_empty());
+	assert_eq!(root.find("div").filter_compiled(&selector).length(), 1);
+	assert!(root.find("div").is_compiled(&selector));
+	assert!(!root.find("p").is_compiled(&selector));
+	let class_div = finded.get(0).unwrap();
+	assert!(class_div.matches(&selector));
+	let id_selector = Vis::compile("#id")?;
+	assert!(!class_div.matches(&id_selector));
+	assert!(is_attr(&finded.closest_compiled(&id_selector), "id", "id"));
+	// the compiled selector can be shared between threads
+	let selector = std::sync::Arc::new(Vis::compile("p")?);
+	let count = {
+		let selector = std::sync::Arc::clone(&selector);
+		std::thread::spawn(move || Vis::load(HTML).unwrap().find_compiled(&selector).length())
+			.join()
+			.unwrap()
+	};
+	assert_eq!(count, root.find_compiled(&selector).length());
+	// wrong selector
+	assert!(Vis::compile("div >").is_err());
+	Ok(())
+}
+
+#[test]
+fn test_method_selector_cache() -> Result {
+	Vis::selector_cache(2);
+	let root = Vis::load(HTML)?;
+	for _ in 0..3 {
+		for selector in &["div", "p", "#id", "div .class"] {
+			let count = root.find(selector).length();
+			assert!(count > 0);
+			assert_eq!(root.find(selector).filter(selector).length(), count);
+			assert!(root.find(selector).is(selector));
+		}
+	}
+	// wrong selector will not be cached
+	assert!(root.find("div >").is_empty());
+	assert!(root.find("div >").is_empty());
+	Vis::selector_cache(0);
+	assert!(root.find("#id").length() == 1);
 	Ok(())
 }
 
@@ -944,3 +1000,459 @@ fn test_method_contains() -> Result {
 		.contains(childs.get(2).unwrap(), &Combinator::Next));
 	Ok(())
 }
+
+#[test]
+fn test_method_unique_selector() -> Result {
+	let html = r##"
+  <html>
+    <head><title>unique</title></head>
+    <body>
+      <div id="main">
+        <ul class="list nav">
+          <li class="item">one</li>
+          <li class="item active">two</li>
+          <li class="item"><a href="#">three</a></li>
+        </ul>
+        <ul class="list">
+          <li class="item css-1x2y3z"><button name="save">save</button></li>
+          <li class="item"><span>4</span></li>
+        </ul>
+      </div>
+      <div id="r1234"><span class="x9k2m">generated</span></div>
+      <svg><linearGradient id="g"></linearGradient><rect /></svg>
+    </body>
+  </html>
+  "##;
+	let root = Vis::load(html)?;
+	let options = UniqueSelectorOptions::default();
+	let check = |selector: &str, expected: &str| -> Result {
+		let ele = root.find(selector);
+		let unique = ele
+			.get(0)
+			.unwrap()
+			.unique_selector(&options)
+			.expect("unique selector");
+		assert_eq!(unique, expected);
+		// verified by find
+		assert!(root.find(&unique).is_all_in(&ele));
+		assert_eq!(root.find(&unique).length(), 1);
+		Ok(())
+	};
+	check("#main", "#main")?;
+	check("li.active", "li.active")?;
+	check("ul.nav", "ul.nav")?;
+	check("button", "button[name=\"save\"]")?;
+	check("li a", "a")?;
+	check("li span", "li.item > span")?;
+	check(
+		"ul:not(.nav) li:first-child",
+		"ul:nth-child(2) > li:nth-child(1)",
+	)?;
+	// generated ids and classes are skipped
+	check(".x9k2m", "div > span")?;
+	check("rect", "rect")?;
+	// allow the generated names
+	let options = UniqueSelectorOptions {
+		avoid_generated: false,
+		..Default::default()
+	};
+	let span = root.find(".x9k2m");
+	assert_eq!(
+		span.get(0).unwrap().unique_selector(&options).unwrap(),
+		"span.x9k2m"
+	);
+	// the max depth
+	let options = UniqueSelectorOptions {
+		prefer_id: false,
+		use_classes: false,
+		attributes: vec![],
+		max_depth: 1,
+		..Default::default()
+	};
+	let li = root.find("li.active");
+	assert!(li.get(0).unwrap().unique_selector(&options).is_none());
+	let options = UniqueSelectorOptions {
+		max_depth: 5,
+		..options
+	};
+	let unique = li.get(0).unwrap().unique_selector(&options).unwrap();
+	assert_eq!(unique, "ul:nth-child(1) > li:nth-child(2)");
+	assert!(root.find(&unique).is_all_in(&li));
+	// the root has no unique selector
+	assert!(root.get(0).unwrap().unique_selector(&options).is_none());
+	Ok(())
+}
+
+#[test]
+fn test_method_absolute_xpath() -> Result {
+	let html = r##"
+  <html>
+    <head></head>
+    <body>
+      <div><p>1</p><p>2</p></div>
+      <div><span>3</span></div>
+    </body>
+  </html>
+  "##;
+	let root = Vis::load(html)?;
+	for (selector, xpath) in [
+		("html", "/html"),
+		("body", "/html/body"),
+		("div:last-child span", "/html/body/div[2]/span"),
+		("p:last-child", "/html/body/div[1]/p[2]"),
+	] {
+		let ele = root.find(selector);
+		assert_eq!(ele.get(0).unwrap().absolute_xpath(), xpath);
+		assert!(root.xpath(xpath)?.into_elements().is_all_in(&ele));
+	}
+	assert_eq!(root.get(0).unwrap().absolute_xpath(), "/");
+	Ok(())
+}
+
+#[test]
+fn test_method_induce_selectors() -> Result {
+	let html = r##"
+  <html>
+    <body>
+      <div id="list">
+        <article class="card featured" data-kind="product"><h3 class="name">A</h3><a class="btn" href="/a">buy</a></article>
+        <article class="card" data-kind="product"><h3 class="name">B</h3><a class="btn" href="/b">buy</a></article>
+        <article class="card" data-kind="ad"><h3 class="name">Ad</h3></article>
+        <article class="card" data-kind="product"><h3 class="name">C</h3><a class="btn" href="/c">buy</a></article>
+      </div>
+      <aside><h3 class="name">Side</h3></aside>
+    </body>
+  </html>
+  "##;
+	let root = Vis::load(html)?;
+	// the positive examples
+	let titles = root
+		.find("article h3")
+		.filter(":contains('A'),:contains('C')");
+	assert_eq!(titles.length(), 3);
+	let titles = titles.not(":contains('Ad')");
+	let candidates = titles.induce_selectors(&root.find("aside h3"));
+	assert_eq!(candidates[0].selector, "article > h3");
+	assert_eq!(
+		(
+			candidates[0].matched,
+			candidates[0].positives,
+			candidates[0].negatives
+		),
+		(4, 2, 0)
+	);
+	// the negative examples are ranked behind
+	let last = candidates.last().unwrap();
+	assert_eq!((last.selector.as_str(), last.negatives), ("h3", 1));
+	// each candidate is validated by `find` and `is`
+	for candidate in &candidates {
+		let matched = root.find(&candidate.selector);
+		assert_eq!(matched.length(), candidate.matched);
+		assert!(titles.is(&candidate.selector));
+	}
+	// the attributes
+	let products = root.find("article:first, article:eq(1)");
+	let candidates = products.induce_selectors(&root.find("article:eq(2)"));
+	assert_eq!(candidates[0].selector, r#"article[data-kind="product"]"#);
+	assert_eq!(candidates[0].matched, 3);
+	// the different tag names
+	let candidates = root
+		.find("article:first")
+		.children("")
+		.induce_selectors(&Elements::new());
+	assert_eq!(candidates[0].selector, "article.card.featured > *");
+	assert!(Elements::new()
+		.induce_selectors(&Elements::new())
+		.is_empty());
+	// the document root is skipped
+	assert!(root.induce_selectors(&Elements::new()).is_empty());
+	let with_root = Elements::with_nodes(vec![
+		root.get(0).unwrap().cloned(),
+		root.find("article:first > h3").get(0).unwrap().cloned(),
+	]);
+	let candidates = with_root.induce_selectors(&root);
+	assert_eq!(candidates[0].positives, 1);
+	// the examples from the sibling pages
+	let page = Vis::load(
+		r##"<div id="list"><article class="card" data-kind="product"><h3 class="name">D</h3></article></div>"##,
+	)?;
+	let titles = Elements::with_nodes(vec![
+		root.find("article:first > h3").get(0).unwrap().cloned(),
+		page.find("h3").get(0).unwrap().cloned(),
+	]);
+	let candidates = titles.induce_selectors(&Elements::new());
+	let best = &candidates[0];
+	assert_eq!(best.selector, "article > h3");
+	assert_eq!((best.matched, best.positives, best.negatives), (5, 2, 0));
+	for candidate in &candidates {
+		let matched = root.find(&candidate.selector).length() + page.find(&candidate.selector).length();
+		assert_eq!(matched, candidate.matched, "{}", candidate.selector);
+	}
+	Ok(())
+}
+
+#[test]
+fn test_method_extract() -> Result {
+	let html = r##"
+    <div class="post">
+      <h2 title="a::b, c">Title<em>!</em></h2>
+      <a href="/next" class="next" download>next</a>
+      <a class="prev">prev</a>
+    </div>
+  "##;
+	let root = Vis::load(html)?;
+	assert_eq!(root.extract("h2::text")?, vec!["Title!"]);
+	assert_eq!(root.extract("h2::own-text")?, vec!["Title"]);
+	assert_eq!(root.extract("h2::html")?, vec!["Title<em>!</em>"]);
+	assert_eq!(root.extract("em::outer-html")?, vec!["<em>!</em>"]);
+	assert_eq!(root.extract("em")?, vec!["<em>!</em>"]);
+	// the elements without the attribute are skipped, the attribute without value is empty
+	assert_eq!(root.extract("a::attr(href)")?, vec!["/next"]);
+	assert_eq!(root.extract("a::attr( download )")?, vec![""]);
+	// the '::' and ',' in the quotes and the nested selectors
+	assert_eq!(
+		root.extract("[title='a::b, c']::attr(title)")?,
+		vec!["a::b, c"]
+	);
+	assert_eq!(
+		root.extract("a:not(.next, h2)::text , h2::own-text")?,
+		vec!["prev", "Title"]
+	);
+	// the pseudo element without selector
+	assert_eq!(root.find("a").extract("::text")?, vec!["next", "prev"]);
+	// the document root is not an element
+	assert!(root.extract("::attr(href)")?.is_empty());
+	assert!(root
+		.extract("::text, ::html, ::own-text, ::outer-html")?
+		.is_empty());
+	assert_eq!(root.extract_first("::attr(class)")?, None);
+	let mixed = root.find(".post").add(root.cloned());
+	assert_eq!(mixed.extract("::attr(class)")?, vec!["post"]);
+	// the values are grouped by the selectors
+	assert_eq!(
+		root.extract("a.prev::text, h2::own-text, a.next::text")?,
+		vec!["prev", "Title", "next"]
+	);
+	assert_eq!(root.extract_first("a::text")?, Some(String::from("next")));
+	assert_eq!(root.extract_first("a::attr(title)")?, None);
+	assert!(Elements::new().extract("a::text")?.is_empty());
+	// the errors
+	fn parse_error(root: &Elements, selector: &str) -> (SelectorErrorKind, usize, String) {
+		match root.extract(selector) {
+			Err(Error::InvalidSelector {
+				kind,
+				offset,
+				token,
+				..
+			}) => (kind, offset, token),
+			result => panic!("'{}' should be an invalid selector: {:?}", selector, result),
+		}
+	}
+	assert_eq!(
+		parse_error(&root, "a::src"),
+		(SelectorErrorKind::UnexpectedToken, 1, String::from("::src"))
+	);
+	assert_eq!(
+		parse_error(&root, "a::text b"),
+		(SelectorErrorKind::UnexpectedToken, 8, String::from("b"))
+	);
+	assert_eq!(
+		parse_error(&root, "a::attr()").0,
+		SelectorErrorKind::UnexpectedToken
+	);
+	assert_eq!(
+		parse_error(&root, "a::attr").0,
+		SelectorErrorKind::UnexpectedEnd
+	);
+	assert_eq!(
+		parse_error(&root, "a::text,,b").0,
+		SelectorErrorKind::EmptySelector
+	);
+	assert_eq!(
+		parse_error(&root, "a >::text").0,
+		SelectorErrorKind::UnexpectedEnd
+	);
+	// the pseudo element must follow the selector
+	assert_eq!(
+		parse_error(&root, "a ::text"),
+		(
+			SelectorErrorKind::UnexpectedToken,
+			2,
+			String::from("::text")
+		)
+	);
+	assert_eq!(root.find("a").extract(" ::text")?, vec!["next", "prev"]);
+	// the error is relative to the whole selector list
+	match root.extract("h2::text, div a:unknown::text") {
+		Err(Error::InvalidSelector {
+			kind,
+			context,
+			offset,
+			token,
+		}) => {
+			assert_eq!(kind, SelectorErrorKind::UnexpectedToken);
+			assert_eq!(context, "h2::text, div a:unknown::text");
+			assert_eq!(&context[offset..], ":unknown::text");
+			assert_eq!(token, ":unknown");
+		}
+		result => panic!("should be an invalid selector: {:?}", result),
+	}
+	Ok(())
+}
+
+#[test]
+fn test_method_content_document() -> Result {
+	let html = r##"
+    <div id="app">
+      <template id="row"><tr><td class="cell">1</td><td class="cell">2</td></tr></template>
+      <noscript><img class="fallback" src="/a.png" /></noscript>
+      <iframe srcdoc="<p class='intro'>hi &amp; bye</p><iframe srcdoc='<b>deep</b>'></iframe>"></iframe>
+      <iframe src="/page.html"></iframe>
+    </div>
+  "##;
+	let root = Vis::load(html)?;
+	// the content documents
+	let template = root.find("template");
+	let doc = template.get(0).unwrap().content_document().unwrap();
+	assert_eq!(doc.find(".cell").length(), 2);
+	assert_eq!(doc.find(":scope > tr > td").length(), 2);
+	let iframes = root.find("iframe");
+	let doc = iframes.get(0).unwrap().content_document().unwrap();
+	assert_eq!(doc.find("p").text(), "hi & bye");
+	assert!(iframes.get(1).unwrap().content_document().is_none());
+	assert!(root
+		.find("#app")
+		.get(0)
+		.unwrap()
+		.content_document()
+		.is_none());
+	// the changes of the content document are not synchronized
+	let doc = template.get(0).unwrap().content_document().unwrap();
+	doc.find(".cell").remove_class("cell");
+	assert_eq!(root.find("template >>> .cell").length(), 2);
+	// pierce into the nested documents
+	assert_eq!(root.find("template >>> td").length(), 2);
+	assert_eq!(
+		root
+			.find("#app > template >>> tr > .cell:last-child")
+			.text(),
+		"2"
+	);
+	assert_eq!(
+		root
+			.find("noscript >>> img")
+			.attr("src")
+			.unwrap()
+			.to_string(),
+		"/a.png"
+	);
+	assert_eq!(root.find("iframe >>> iframe >>> b").text(), "deep");
+	assert_eq!(template.find(">>> td").length(), 2);
+	assert!(root.find("#app >>> td").is_empty());
+	// the nodes are kept alive with the finded elements
+	let cells = root.find("template >>> td");
+	assert_eq!(cells.parent("").length(), 1);
+	assert_eq!(cells.closest("tr").length(), 1);
+	assert_eq!(
+		root.extract("iframe >>> .intro::text, noscript >>> img::attr(src)")?,
+		vec!["hi & bye", "/a.png"]
+	);
+	// the '>>>' in the quotes is not a combinator
+	assert!(root.find("[title='>>>']").is_empty());
+	// the errors
+	let errors = [
+		"template >>>",
+		"template >>> >>> td",
+		">>> td >",
+		"template >>> td:not(",
+	];
+	for selector in &errors {
+		match root.try_find(selector) {
+			Err(Error::InvalidSelector { context, .. }) => assert_eq!(&context, selector),
+			_ => panic!("'{}' should be an invalid selector", selector),
+		}
+	}
+	match root.try_find("noscript, template >>> td, ") {
+		Err(Error::InvalidSelector { kind, offset, .. }) => {
+			assert_eq!(kind, SelectorErrorKind::EmptySelector);
+			assert_eq!(offset, 27);
+		}
+		_ => panic!("the empty selector in the list should be an error"),
+	}
+	// the selector list with '>>>'
+	let finded = root.find("iframe >>> .intro, noscript");
+	assert_eq!(finded.length(), 2);
+	assert!(finded
+		.get(0)
+		.unwrap()
+		.is(root.find("noscript").get(0).unwrap()));
+	assert_eq!(finded.eq(1).text(), "hi & bye");
+	let finded = root.find("noscript, iframe >>> .intro");
+	assert_eq!(finded.length(), 2);
+	assert_eq!(finded.eq(1).text(), "hi & bye");
+	let finded = root.find("iframe, template >>> td, #app > template");
+	assert_eq!(finded.length(), 5);
+	assert!(finded.slice(..3).is_all_by(|_, ele| ele.tag_name() != "TD"));
+	assert_eq!(finded.slice(3..).text(), "12");
+	// '>>>' is only supported in `find` and `extract`
+	let unsupported = |result: StdResult<(), Error>, expected: usize| match result {
+		Err(Error::InvalidSelector { kind, offset, .. }) => {
+			assert_eq!(kind, SelectorErrorKind::Unsupported);
+			assert_eq!(offset, expected);
+		}
+		_ => panic!("'>>>' should be unsupported"),
+	};
+	unsupported(
+		Vis::compile("template >>> td")
+			.map(|_| ())
+			.map_err(|err| *err.downcast::<Error>().unwrap()),
+		9,
+	);
+	unsupported(template.try_filter("template >>> td").map(|_| ()), 9);
+	unsupported(template.try_is("template >>> td").map(|_| ()), 9);
+	unsupported(root.try_find("template:has(>>> td)").map(|_| ()), 13);
+	Ok(())
+}
+
+#[test]
+fn test_method_load_nested() -> Result {
+	let html = r##"
+    <div id="app">
+      <template><ul><li class="item">1</li></ul></template>
+      <iframe srcdoc="<b>bold</b>"></iframe>
+    </div>
+  "##;
+	let root = Vis::load_nested(html)?;
+	let template = root.find("template");
+	let doc = template.get(0).unwrap().content_document().unwrap();
+	let same_doc = template.get(0).unwrap().content_document().unwrap();
+	assert!(doc.get(0).unwrap().is(same_doc.get(0).unwrap()));
+	// the changes of the content documents are kept
+	doc
+		.find("ul")
+		.set_html("<li class='item'>1</li><li class='item'>2</li>");
+	assert_eq!(root.find("template >>> .item").text(), "12");
+	assert_eq!(root.find("template >>> li, template >>> .item").length(), 2);
+	root.find("iframe >>> b").set_text("strong");
+	assert_eq!(root.find("iframe >>> b").text(), "strong");
+	// but not synchronized to the elements
+	assert_eq!(template.find("li").length(), 1);
+	assert_eq!(
+		root.find("iframe").attr("srcdoc").unwrap().to_string(),
+		"<b>bold</b>"
+	);
+	// the kept content documents are removed when the element or its descendants are changed
+	template.find("li").set_text("one");
+	assert_eq!(root.find("template >>> .item").text(), "one");
+	root.find("iframe").set_attr("srcdoc", Some("<b>new</b>"));
+	assert_eq!(root.find("iframe >>> b").text(), "new");
+	// the changes of the other elements keep the content documents
+	root.find("iframe >>> b").set_text("strong");
+	root.find("#app").set_attr("class", Some("app"));
+	assert_eq!(root.find("iframe >>> b").text(), "strong");
+	// the documents loaded by `load` are parsed on each call
+	let root = Vis::load(html)?;
+	root.find("iframe >>> b").set_text("strong");
+	assert_eq!(root.find("iframe >>> b").text(), "bold");
+	Ok(())
+}
@@ -64,3 +64,14 @@ fn test_remove() -> Result {
 	assert_eq!(content.text(), "This is a !");
 	Ok(())
 }
+
+#[test]
+fn test_remove_with_index() -> Result {
+	let html = r#"<ul><li class="item">1</li><li class="item">2</li></ul>"#;
+	let root = Vis::load(html)?;
+	assert_eq!(root.find(".item").length(), 2);
+	root.find(".item").eq(0).remove();
+	assert_eq!(root.find(".item").text(), "2");
+	assert_eq!(root.find("li").length(), 1);
+	Ok(())
+}
@@ -1,5 +1,9 @@
 use std::result::Result as StdResult;
-use visdom::types::BoxDynError;
+use visdom::selector::{
+	check_params_return, BoxDynPattern, Matched, MatchedQueue, Matcher, Pattern, PseudoArgument,
+	QueryStrategy, SimpleSelector, Specificity, PRIORITY_PSEUDO_SELECTOR,
+};
+use visdom::types::{BoxDynError, Elements};
 use visdom::Vis;
 type Result = StdResult<(), BoxDynError>;
 
@@ -62,6 +66,117 @@ fn test_attribute_selector() -> Result {
 	Ok(())
 }
 
+#[test]
+fn test_attribute_selector_flags() -> Result {
+	let html = r##"
+  <nav id="lang">
+    <a href="/A.PDF" lang="EN">en</a>
+    <a href="/b.pdf" lang="En-UK" class="Nav Item">en-UK</a>
+    <a href="/c.Pdf" lang="english">english</a>
+    <input type="SUBMIT" />
+  </nav>
+  "##;
+	let root = Vis::load(html)?;
+	let links = root.find("#lang a");
+	// default is case-sensitive
+	assert_eq!(links.filter("[lang='en']").length(), 0);
+	assert_eq!(links.filter("[lang='en' s]").length(), 0);
+	assert_eq!(links.filter("[lang='en' i]").length(), 1);
+	assert_eq!(links.filter("[lang=en I]").length(), 1);
+	assert_eq!(links.filter("[lang=\"EN\"i]").length(), 1);
+	assert_eq!(links.filter("[lang^=en i]").length(), 3);
+	assert_eq!(links.filter("[lang^=en s]").length(), 1);
+	assert_eq!(links.filter("[lang|=EN i]").length(), 2);
+	assert_eq!(links.filter("[lang|=EN]").length(), 1);
+	assert_eq!(links.filter("[href$='.pdf' i]").length(), 3);
+	assert_eq!(links.filter("[href$='.pdf']").length(), 1);
+	assert_eq!(links.filter("[href*='pdf' i]").length(), 3);
+	assert_eq!(links.filter("[class~=item i]").length(), 1);
+	assert_eq!(links.filter("[class~=item]").length(), 0);
+	assert_eq!(links.filter("[lang!=en i]").length(), 2);
+	assert_eq!(links.filter("[lang!=en]").length(), 3);
+	assert_eq!(root.find("input[type=submit i]").length(), 1);
+	// not a flag
+	assert!(links.filter("[lang=en ix]").is_empty());
+	assert_eq!(links.filter("[lang=english]").length(), 1);
+	Ok(())
+}
+
+#[test]
+fn test_attribute_selector_regexp() -> Result {
+	let html = r##"
+  <ul id="list">
+    <li><a href="/product/123">product</a></li>
+    <li><a href="/product/abc">category</a></li>
+    <li><a href="/Product/45" title="a/b">upper</a></li>
+    <li><a>no href</a></li>
+  </ul>
+  "##;
+	let root = Vis::load(html)?;
+	let links = root.find("#list a");
+	assert_eq!(links.filter(r#"[href=~/^\/product\/\d+$/]"#).length(), 1);
+	assert_eq!(
+		links.filter(r#"[ href =~ /^\/product\/\d+$/i ]"#).length(),
+		2
+	);
+	assert_eq!(links.filter(r#"[href=~/product/]"#).length(), 2);
+	assert_eq!(links.filter(r#"[title=~/a\/b/]"#).text(), "upper");
+	assert_eq!(root.find(r#"li:has([href=~/\d$/])"#).length(), 2);
+	// not a regex, but the value begin with '~'
+	assert_eq!(links.filter("[href=~abc]").length(), 0);
+	// invalid regex or flags
+	assert!(Vis::compile(r#"[href=~/(/]"#).is_err());
+	assert!(Vis::compile(r#"[href=~/a/g]"#).is_err());
+	assert!(Vis::compile(r#"[href=~//]"#).is_err());
+	Ok(())
+}
+
+#[test]
+fn test_namespace_selector() -> Result {
+	let html = r##"
+  <div id="content">
+    <a href="/html">html link</a>
+    <svg viewBox="0 0 10 10">
+      <defs>
+        <linearGradient id="gradient"></linearGradient>
+        <clipPath id="clip"></clipPath>
+      </defs>
+      <a xlink:href="/svg">svg link</a>
+      <title>svg title</title>
+      <foreignObject><div class="html-in-svg"><a href="/foreign">foreign</a></div></foreignObject>
+    </svg>
+    <math><mi>x</mi><mtext><b>bold</b></mtext></math>
+  </div>
+  "##;
+	let root = Vis::load(html)?;
+	let content = root.find("#content");
+	assert_eq!(content.find("a").length(), 3);
+	assert_eq!(content.find("*|a").length(), 3);
+	assert_eq!(content.find("svg|a").text(), "svg link");
+	assert_eq!(content.find("html|a").text(), "html linkforeign");
+	assert_eq!(content.find("svg|linearGradient").length(), 1);
+	assert_eq!(content.find("svg|lineargradient").length(), 0);
+	assert_eq!(content.find("svg|clipPath#clip").length(), 1);
+	assert_eq!(content.find("svg|*").length(), 7);
+	assert_eq!(content.find("math|*").length(), 3);
+	assert_eq!(content.find("html|b").text(), "bold");
+	assert_eq!(content.find("svg|title").length(), 1);
+	assert_eq!(content.find("svg > svg|a, html|div > html|a").length(), 2);
+	assert!(Vis::compile("foo|a").is_err());
+	assert!(Vis::compile("|a").is_err());
+	// attribute namespace
+	assert_eq!(content.find("[xlink|href]").text(), "svg link");
+	assert_eq!(content.find("[xlink|href^='/s']").length(), 1);
+	assert_eq!(content.find("[xlink|href='/html']").length(), 0);
+	assert_eq!(content.find("[*|href]").length(), 3);
+	assert_eq!(content.find("[*|href$='N' i]").text(), "foreign");
+	assert_eq!(content.find("[|href]").length(), 2);
+	assert_eq!(content.find("a[*|href!='/svg']").length(), 2);
+	assert_eq!(content.find("[lang|=en], [xlink|href|='/svg']").length(), 1);
+	assert!(Vis::compile("[foo|href]").is_err());
+	Ok(())
+}
+
 #[test]
 fn test_id_selector() -> Result {
 	let html = r##"
@@ -226,6 +341,158 @@ fn test_selector_pseudo_checked() -> Result {
 	Ok(())
 }
 
+#[test]
+fn test_selector_pseudo_form_states() -> Result {
+	let html = r##"
+  <form id="form">
+    <fieldset id="outer" disabled>
+      <legend><input id="in-legend" /></legend>
+      <legend><input id="in-second-legend" /></legend>
+      <input id="in-fieldset" />
+      <fieldset id="inner"><button id="inner-button">ok</button></fieldset>
+    </fieldset>
+    <input id="text" type="text" required placeholder="name" />
+    <input id="filled" type="email" placeholder="email" value="a@b.c" />
+    <input id="readonly" readonly />
+    <input id="hidden" type="hidden" required />
+    <textarea id="textarea" placeholder="desc"></textarea>
+    <select id="select" required>
+      <optgroup id="group" disabled><option id="group-option">1</option></optgroup>
+      <option id="selected" selected>2</option>
+      <option id="disabled-option" disabled>3</option>
+    </select>
+    <input id="checkbox" type="checkbox" checked />
+    <input type="radio" name="sex" value="1" />
+    <input type="radio" name="sex" value="2" />
+    <input type="radio" name="checked" value="1" />
+    <input type="radio" name="checked" value="2" id="checked-radio" checked />
+    <button type="button" id="normal-button">button</button>
+    <button id="submit">submit</button>
+    <input type="submit" id="second-submit" />
+    <progress id="progress"></progress>
+    <progress id="progress-value" value="1" max="2"></progress>
+  </form>
+  <div contenteditable><p id="editable">edit</p></div>
+  "##;
+	let root = Vis::load(html)?;
+	let form = root.find("#form");
+	let ids = |eles: Elements| {
+		eles
+			.map(|_, ele| {
+				ele
+					.get_attribute("id")
+					.map(|id| id.to_string())
+					.unwrap_or_default()
+			})
+			.join(",")
+	};
+	// :disabled, :enabled
+	assert_eq!(
+		ids(form.find(":disabled")),
+		"outer,in-second-legend,in-fieldset,inner,inner-button,group,group-option,disabled-option"
+	);
+	assert!(form.find("#in-legend").is(":enabled"));
+	assert!(form.find("#selected").is(":enabled"));
+	assert!(!form.find("legend").is(":enabled"));
+	assert_eq!(
+		form.find(":enabled").length() + form.find(":disabled").length(),
+		form
+			.find("button,input,select,textarea,fieldset,optgroup,option")
+			.length()
+	);
+	// :required, :optional
+	assert_eq!(ids(form.find(":required")), "text,select");
+	assert!(form.find("#hidden").is(":optional"));
+	assert!(!form.find("button").is(":optional"));
+	// :read-write, :read-only
+	assert!(form.find("#text").is(":read-write"));
+	assert!(form.find("#textarea").is(":read-write"));
+	assert!(form.find("#readonly").is(":read-only"));
+	assert!(form.find("#in-fieldset").is(":read-only"));
+	assert!(form.find("#checkbox").is(":read-only"));
+	assert!(root.find("#editable").is(":read-write"));
+	assert!(root.find("#form").is(":read-only"));
+	// :placeholder-shown
+	assert_eq!(ids(form.find(":placeholder-shown")), "text,textarea");
+	// :default
+	assert_eq!(
+		ids(form.find(":default")),
+		"inner-button,selected,checkbox,checked-radio"
+	);
+	assert_eq!(form.find("[name='checked']:default").length(), 1);
+	// :indeterminate
+	assert_eq!(form.find("[name='sex']:indeterminate").length(), 2);
+	assert_eq!(form.find("[name='checked']:indeterminate").length(), 0);
+	assert_eq!(ids(form.find("progress:indeterminate")), "progress");
+	assert_eq!(
+		form.find(":not(:indeterminate)").length() + form.find(":indeterminate").length(),
+		form.find("*").length()
+	);
+	Ok(())
+}
+
+#[test]
+fn test_selector_pseudo_positional() -> Result {
+	let html = r##"
+  <div id="content">
+    <ul class="list">
+      <li>1</li>
+      <li class="item">2</li>
+      <li>3</li>
+    </ul>
+    <ul class="list">
+      <li class="item">4</li>
+      <li>5</li>
+    </ul>
+  </div>
+  "##;
+	let root = Vis::load(html)?;
+	let content = root.find("#content");
+	// relative to the matched set, not the siblings
+	assert_eq!(content.find("li:first").text(), "1");
+	assert_eq!(content.find("li:last").text(), "5");
+	assert_eq!(content.find("li:first-child").length(), 2);
+	assert_eq!(content.find("li:even").text(), "135");
+	assert_eq!(content.find("li:odd").text(), "24");
+	assert_eq!(content.find("li:eq(3)").text(), "4");
+	assert_eq!(content.find("li:eq(-1)").text(), "5");
+	assert_eq!(content.find("li:eq(5)").length(), 0);
+	assert_eq!(content.find("li:eq(-6)").length(), 0);
+	assert_eq!(content.find("li:gt(2)").text(), "45");
+	assert_eq!(content.find("li:gt(-2)").text(), "5");
+	assert_eq!(content.find("li:lt(2)").text(), "12");
+	assert_eq!(content.find("li:lt(-3)").text(), "12");
+	assert_eq!(content.find("li:lt( 99999999999999999999 )").length(), 5);
+	// the index is applied after the selectors before it
+	assert_eq!(content.find("li.item:first").text(), "2");
+	assert_eq!(content.find("li:first.item").length(), 0);
+	assert_eq!(content.find("ul:last > li").text(), "45");
+	assert_eq!(content.find("ul:eq(0) li:odd").text(), "2");
+	assert_eq!(content.find(".list > :first").text(), "1");
+	assert_eq!(content.find("li:not(:first):not(:last)").text(), "234");
+	assert_eq!(content.find("li:is(:gt(0)):lt(1)").text(), "2");
+	assert_eq!(content.find("li:first, li:last").text(), "15");
+	// filter methods resolve the positional selectors from the document, whatever the selector shape
+	let items = content.find("li");
+	assert_eq!(items.filter("li:gt(2)").text(), "45");
+	assert_eq!(items.not("li:lt(4)").text(), "5");
+	assert_eq!(items.filter(":gt(2)").text(), "2345");
+	assert!(!items.eq(1).is("li:first"));
+	assert!(!items.eq(1).is("ul li:first"));
+	assert!(items.eq(0).is("li:first"));
+	assert!(items.eq(0).is("ul li:first"));
+	assert!(items.is("li:first"));
+	assert_eq!(
+		items.filter("li:odd").text(),
+		items.filter("ul li:odd").text()
+	);
+	assert_eq!(items.filter("ul:last > li:first").text(), "4");
+	assert!(items.eq(4).is("ul:last > :last"));
+	assert!(!items.eq(3).is("ul:last > :last"));
+	assert_eq!(content.find("ul").has("li:eq(2)").length(), 1);
+	Ok(())
+}
+
 #[test]
 fn test_selector_pseudo_header() -> Result {
 	let html = r#"<h1></h1><div></div>"#;
@@ -307,6 +574,46 @@ fn test_selector_pseudo_contains() -> Result {
 	Ok(())
 }
 
+#[test]
+fn test_selector_pseudo_text() -> Result {
+	let html = r##"
+  <ul id="list">
+    <li>  Price: 100  </li>
+    <li>PRICE: <b>200</b></li>
+    <li><b>price</b> unknown</li>
+    <li>Visdom</li>
+  </ul>
+  "##;
+	let root = Vis::load(html)?;
+	let items = root.find("#list li");
+	// :icontains
+	assert_eq!(items.filter(":icontains('price')").length(), 3);
+	assert_eq!(items.filter(":icontains(PRICE)").length(), 3);
+	assert_eq!(items.filter(":contains('price')").length(), 1);
+	assert_eq!(items.filter(":not(:icontains(\"price\"))").text(), "Visdom");
+	assert_eq!(items.filter(":icontains()").length(), 4);
+	// :text-equals
+	assert_eq!(items.filter(":text-equals('Price: 100')").length(), 1);
+	assert_eq!(items.filter(":text-equals(Visdom)").length(), 1);
+	assert_eq!(items.filter(":text-equals('Price')").length(), 0);
+	assert_eq!(root.find("b:text-equals(200)").length(), 1);
+	// :text-matches
+	assert_eq!(
+		items.filter(r#":text-matches(/price:\s*\d+/i)"#).length(),
+		2
+	);
+	assert_eq!(items.filter(r#":text-matches(/^\s*Price/)"#).length(), 1);
+	assert_eq!(items.filter(r#":not(:text-matches(/\d/))"#).length(), 2);
+	assert!(Vis::compile(":text-matches(price)").is_err());
+	// :own-text
+	assert_eq!(items.filter(":own-text('PRICE')").length(), 1);
+	assert_eq!(items.filter(":own-text('200')").length(), 0);
+	assert_eq!(items.filter(":own-text(unknown)").length(), 1);
+	assert_eq!(items.filter(":own-text('price')").length(), 0);
+	assert_eq!(items.filter(":not(:own-text('i'))").length(), 2);
+	Ok(())
+}
+
 #[test]
 fn test_selector_pseudo_only_child() -> Result {
 	let html = r#"
@@ -521,6 +828,59 @@ fn test_selector_pseudo_nth_child() -> Result {
 	Ok(())
 }
 
+#[test]
+fn test_selector_pseudo_nth_child_of() -> Result {
+	let html = r##"
+  <div id="content">
+    <ul>
+      <li class="item">1</li>
+      <li class="ad">ad</li>
+      <li class="item">2</li>
+      <li class="item">3</li>
+      <li class="ad">ad</li>
+      <li class="item hot">4</li>
+    </ul>
+    <ul>
+      <li class="ad">ad</li>
+      <li class="item">5</li>
+      <li class="item hot">6</li>
+    </ul>
+  </div>
+  "##;
+	let root = Vis::load(html)?;
+	let content = root.find("#content");
+	assert_eq!(content.find("li:nth-child(2 of .item)").text(), "26");
+	assert_eq!(content.find("li:nth-child(2)").text(), "ad5");
+	assert_eq!(content.find(":nth-child(2n+1 of .item)").text(), "135");
+	assert_eq!(
+		content.find("li:nth-child(odd of li:not(.ad))").text(),
+		"135"
+	);
+	assert_eq!(content.find("li:nth-last-child(1 of .item)").text(), "46");
+	assert_eq!(
+		content.find("li:nth-last-child(-n+2 of .item)").text(),
+		"3456"
+	);
+	assert_eq!(content.find("li:nth-child(1 of .hot, .ad)").text(), "adad");
+	assert_eq!(content.find("li:nth-child(1 of .item).hot").length(), 0);
+	assert_eq!(content.find("ul > :nth-child(3 of .item)").text(), "3");
+	// filter
+	let items = content.find("li");
+	assert_eq!(items.filter(":nth-child(1 of .hot)").text(), "46");
+	assert_eq!(items.filter(":nth-last-child(2 of .item)").text(), "35");
+	// overlapped selectors after 'of'
+	let list =
+		Vis::load("<ul><li class=a>1</li><li>2</li><li class=a>3</li><li>4</li><li>5</li></ul>")?;
+	assert_eq!(list.find("li:nth-child(3n+2 of .a, li)").text(), "25");
+	assert_eq!(list.find("li:nth-child(3n+2 of li, .a)").text(), "25");
+	assert_eq!(list.find("li:nth-last-child(2 of .a, li)").text(), "4");
+	assert_eq!(list.find("li:nth-last-child(2 of li, .a)").text(), "4");
+	// not a selector list after 'of'
+	assert!(Vis::compile("li:nth-child(2n of)").is_err());
+	assert!(Vis::compile("li:nth-child(2nof .item)").is_err());
+	Ok(())
+}
+
 #[test]
 fn test_selector_pseudo_nth_last_child() -> Result {
 	let html = r#"
@@ -825,6 +1185,176 @@ fn test_selector_pseudo_not() -> Result {
 	Ok(())
 }
 
+#[test]
+fn test_selector_pseudo_is_where() -> Result {
+	let html = r#"
+    <article>
+      <h1>a-h1</h1>
+      <section><h2>a-h2</h2><h3>a-h3</h3></section>
+    </article>
+    <section>
+      <h1>s-h1</h1>
+      <h2 class="title">s-h2</h2>
+    </section>
+  "#;
+	let root = Vis::load(html)?;
+	assert_eq!(root.find("article :is(h1, h2, h3)").text(), "a-h1a-h2a-h3");
+	assert_eq!(root.find("article :where(h1, h2, h3)").length(), 3);
+	assert_eq!(root.find(":is(article, section) > h1").text(), "a-h1s-h1");
+	// combinators inside
+	assert_eq!(
+		root.find(":is(article h2, section > .title)").text(),
+		"a-h2s-h2"
+	);
+	assert_eq!(root.find("h2:where(article > section > *)").text(), "a-h2");
+	assert_eq!(
+		root.find(":is(article, body) :is(section h3)").text(),
+		"a-h3"
+	);
+	// work with the lookup
+	assert_eq!(root.find("section :is(h1, h2).title").text(), "s-h2");
+	assert_eq!(
+		root.find("section > :where(h2, h3):is(.title)").text(),
+		"s-h2"
+	);
+	// nested
+	assert_eq!(root.find("h2:not(:is(.title))").text(), "a-h2");
+	assert_eq!(root.find("section:is(:has(h3), :has(.title))").length(), 2);
+	// filter, is, closest
+	let heads = root.find(":header");
+	assert_eq!(heads.filter(":is(section > *)").length(), 4);
+	assert!(heads.is(":where(article > h1)"));
+	assert_eq!(heads.closest(":is(article, section)").length(), 3);
+	// overlapped selectors in the list
+	let list =
+		Vis::load("<ul><li class=a>1</li><li>2</li><li class=a>3</li><li>4</li><li>5</li></ul>")?;
+	assert_eq!(list.find("li:is(.a, li)").text(), "12345");
+	assert_eq!(list.find("li:is(li, .a)").text(), "12345");
+	assert_eq!(list.find(":where(.a, li)").text(), "12345");
+	assert_eq!(list.find(":where(li, .a)").text(), "12345");
+	assert_eq!(list.find("li").filter(".a, li").text(), "12345");
+	Ok(())
+}
+
+#[test]
+fn test_selector_pseudo_has() -> Result {
+	let html = r#"
+    <div class="card" id="card1">
+      <img class="hero" />
+      <h2>card1</h2>
+    </div>
+    <div class="card" id="card2">
+      <p><img class="hero" /></p>
+      <h2>card2</h2>
+    </div>
+    <div class="card" id="card3">
+      <h2>card3</h2>
+      <span>tip</span>
+    </div>
+  "#;
+	let root = Vis::load(html)?;
+	// descendants
+	assert_eq!(root.find("div.card:has(img.hero)").length(), 2);
+	// leading combinators
+	assert_eq!(root.find("div.card:has(> img.hero) h2").text(), "card1");
+	assert_eq!(root.find("h2:has(+ span)").text(), "card3");
+	assert_eq!(root.find("img:has(~ h2)").length(), 1);
+	assert_eq!(root.find(".card:has(> p img, > span)").length(), 2);
+	// nested in :not
+	assert_eq!(root.find(".card:not(:has(> img))").length(), 2);
+	assert_eq!(root.find(".card:not(:has(img)) h2").text(), "card3");
+	assert_eq!(root.find(".card:not(:has(> p, span)) h2").text(), "card1");
+	// filter, is, closest
+	let cards = root.find(".card");
+	assert_eq!(
+		cards.filter(":has(p)").attr("id").unwrap().to_string(),
+		"card2"
+	);
+	assert!(cards.is(":has(> span)"));
+	assert!(!cards.is(":has(> strong)"));
+	let imgs = root.find("img");
+	assert_eq!(imgs.closest(":has(> h2)").length(), 2);
+	assert_eq!(imgs.closest("div:has(> p)").length(), 1);
+	Ok(())
+}
+
+#[test]
+fn test_selector_pseudo_scope() -> Result {
+	let html = r#"
+    <div id="menu">
+      <ul class="list">
+        <li class="item">item1
+          <ul>
+            <li class="item"><a>item1-1</a></li>
+          </ul>
+        </li>
+        <li class="item active">item2</li>
+      </ul>
+      <p class="tip">tip</p>
+    </div>
+  "#;
+	let root = Vis::load(html)?;
+	let list = root.find(".list");
+	// find, relative to each element
+	assert_eq!(list.find(":scope > li").length(), 2);
+	assert_eq!(list.find(":scope li").length(), 3);
+	assert_eq!(list.find(":scope + .tip").text(), "tip");
+	assert_eq!(list.find("#menu > :scope > li").length(), 2);
+	assert_eq!(list.find("p :scope > li").length(), 0);
+	assert_eq!(list.find(":scope.list > li.active").text(), "item2");
+	// the scope itself is not a descendant
+	assert!(list.find(":scope").is_empty());
+	// the document root is the scope, it has no class or attributes
+	assert!(root.find(".list:scope li").is_empty());
+	assert!(root.find(":scope.list li").is_empty());
+	assert!(root.find("[id]:scope li, #menu:scope li").is_empty());
+	assert_eq!(root.find(":scope > div li").length(), 3);
+	assert_eq!(root.find("*").filter(".list:scope a").length(), 1);
+	assert_eq!(root.find("*").filter("[id] :scope a").length(), 1);
+	let items = root.find("li");
+	assert_eq!(items.find(":scope > ul > li").length(), 1);
+	assert_eq!(items.find(":scope > ul > li, > a").length(), 2);
+	// leading combinators are same as begin with ':scope'
+	assert_eq!(list.find("> li").length(), 2);
+	assert_eq!(list.find("+ .tip").text(), "tip");
+	// filter, is, closest, the ':scope' alone is the element itself
+	assert_eq!(items.filter(":scope").length(), 3);
+	assert_eq!(items.filter(":scope.active").text(), "item2");
+	assert_eq!(items.filter("li li:scope").length(), 1);
+	assert!(items.is(":scope"));
+	// the relative selectors match the elements can be found from any element
+	assert_eq!(items.filter("> li").length(), 3);
+	assert_eq!(items.filter("+ li").text(), "item2");
+	assert_eq!(items.filter("> ul > li").length(), 3);
+	assert_eq!(items.filter("> li > ul > li").text(), "item1-1");
+	assert_eq!(items.filter(":scope > li > ul > li").text(), "item1-1");
+	assert_eq!(items.filter(".list > :scope > ul > li").text(), "item1-1");
+	assert!(items.filter("div > :scope > ul > li").is_empty());
+	assert!(items.is("+ li"));
+	assert!(items.is(":scope > li"));
+	assert!(!items.is("~ p"));
+	assert_eq!(items.not("+ li").length(), 2);
+	let link = root.find("a");
+	assert_eq!(link.closest(":scope").length(), 1);
+	assert_eq!(link.closest(":scope, ul").length(), 1);
+	assert!(link.closest(".item:scope").is_empty());
+	assert_eq!(link.closest("> ul").length(), 1);
+	assert!(link.closest("> ul").is("ul"));
+	assert_eq!(link.closest(":scope > li").text(), "item1-1");
+	assert!(link.closest("+ ul").is_empty());
+	assert_eq!(link.closest("ul:scope, ul").length(), 1);
+	assert!(link.closest("ul:scope, ul").is("ul"));
+	// has, relative to each element
+	assert_eq!(items.has(":scope > a").length(), 1);
+	assert_eq!(items.has("> ul").length(), 1);
+	assert!(items.has(":scope").is_empty());
+	// nested in :has
+	assert_eq!(root.find("li:has(:scope > a)").length(), 1);
+	// the document element is the scope of the root
+	assert_eq!(root.find(":scope > div").length(), 1);
+	Ok(())
+}
+
 #[test]
 fn test_wrong_selector_splitter() -> Result {
 	let root = Vis::load("<b>anything</b>")?;
@@ -851,3 +1381,333 @@ fn test_wrong_selector_wrong_nested() {
 	let root = Vis::load("<b>anything</b>").unwrap();
 	assert!(root.find(":not(:not(:a)").is_empty());
 }
+
+#[test]
+fn test_register_pseudo_selector() -> Result {
+	let html = r##"
+	<ul>
+		<li data-price="5">a</li>
+		<li data-price="50">b</li>
+		<li data-price="500">c</li>
+	</ul>
+	"##;
+	let root = Vis::load(html)?;
+	Vis::register_pseudo(
+		":price-between({spaces}{regexp#(\\d+)\\s*,\\s*(\\d+)#}{spaces})",
+		PRIORITY_PSEUDO_SELECTOR,
+		Box::new(|data: MatchedQueue| {
+			let min = data[2].data.get("1").unwrap().parse::<u32>().unwrap();
+			let max = data[2].data.get("2").unwrap().parse::<u32>().unwrap();
+			Matcher {
+				one_handle: Some(Box::new(move |ele, _| {
+					if let Some(price) = ele.get_attribute("data-price") {
+						let price = price.to_string().parse::<u32>().unwrap_or(0);
+						return price >= min && price <= max;
+					}
+					false
+				})),
+				..Default::default()
+			}
+		}),
+	)?;
+	assert_eq!(root.find("li:price-between(10, 100)").text(), "b");
+	assert_eq!(root.find("li:price-between(1,500)").length(), 3);
+	assert_eq!(root.find("li:not(:price-between(1, 10))").length(), 2);
+	// all handle, a longer name with the prefix of an exist selector
+	Vis::register_pseudo(
+		":last-two",
+		PRIORITY_PSEUDO_SELECTOR,
+		Box::new(|_| Matcher {
+			all_handle: Some(Box::new(|eles: &Elements, _| {
+				let total = eles.length();
+				eles.slice(total.saturating_sub(2)..)
+			})),
+			..Default::default()
+		}),
+	)?;
+	assert_eq!(root.find("li").filter(":last-two").text(), "bc");
+	assert_eq!(root.find("li:last-child").text(), "c");
+	// custom pattern
+	#[derive(Debug)]
+	struct Price;
+	impl Pattern for Price {
+		fn matched(&self, chars: &[char]) -> Option<Matched> {
+			let len = chars.iter().take_while(|ch| ch.is_ascii_digit()).count();
+			if len > 0 && chars.get(len) == Some(&'$') {
+				return Some(Matched {
+					chars: chars[..=len].to_vec(),
+					name: "price",
+					..Default::default()
+				});
+			}
+			None
+		}
+		fn from_params(s: &str, p: &str) -> StdResult<BoxDynPattern, String> {
+			check_params_return(&[s, p], || Box::new(Price))
+		}
+	}
+	Vis::register_pattern("price", Box::new(Price::from_params))?;
+	assert!(Vis::register_pattern("price", Box::new(Price::from_params)).is_err());
+	Vis::register_pseudo(
+		":price({price})",
+		PRIORITY_PSEUDO_SELECTOR,
+		Box::new(|data: MatchedQueue| {
+			let chars = &data[1].chars;
+			let price = chars[..chars.len() - 1].iter().collect::<String>();
+			Matcher {
+				one_handle: Some(Box::new(move |ele, _| {
+					ele
+						.get_attribute("data-price")
+						.map(|value| value.to_string() == price)
+						.unwrap_or(false)
+				})),
+				..Default::default()
+			}
+		}),
+	)?;
+	assert_eq!(root.find("li:price(500$)").text(), "c");
+	assert!(root.find("li:price(500)").is_empty());
+	// name collisions
+	assert!(Vis::register_pseudo(":contains", 10, Box::new(|_| Matcher::default())).is_err());
+	assert!(Vis::register_pseudo(":price-between", 10, Box::new(|_| Matcher::default())).is_err());
+	assert!(Vis::register_pseudo("price", 10, Box::new(|_| Matcher::default())).is_err());
+	Ok(())
+}
+
+#[test]
+fn test_selector_ast() -> Result {
+	fn canonical(selector: &str) -> String {
+		Vis::parse_selector(selector).unwrap().to_string()
+	}
+	assert_eq!(canonical("div>p  +  a~span"), "div > p + a ~ span");
+	assert_eq!(canonical(" ul li , a "), "ul li, a");
+	assert_eq!(
+		canonical("li:nth-child( 2n + 1 of .a,.b )"),
+		"li:nth-child(2n+1 of .a, .b)"
+	);
+	assert_eq!(canonical(":has(>li,+ p)"), ":has(> li, + p)");
+	assert_eq!(canonical("[title='a\"b']"), "[title='a\"b']");
+	assert_eq!(
+		canonical("[ xlink|href = 'x' S ][|id][*|lang]"),
+		r#"[xlink|href="x" s][|id][*|lang]"#
+	);
+	assert_eq!(canonical("a[href=~/\\d+/i]"), "a[href=~/\\d+/i]");
+	assert_eq!(canonical("svg|rect,*|*"), "svg|rect, *|*");
+	assert_eq!(
+		canonical("p:contains( 'a b' ):eq(-1)"),
+		"p:contains('a b'):eq(-1)"
+	);
+	// the quotes and backslashes in the value are escaped
+	assert_eq!(canonical(r#"[a="x'y\"z"]"#), r#"[a='x\'y"z']"#);
+	assert_eq!(canonical(r#"[a='x\\y']"#), r#"[a="x\\y"]"#);
+	// the html type names are lowercase, the names in the svg and mathml namespaces are case-sensitive
+	assert_eq!(canonical("DIV > Span.A"), "div > span.A");
+	assert_eq!(
+		canonical("html|DIV, svg|linearGradient, *|linearGradient"),
+		"html|div, svg|linearGradient, *|linearGradient"
+	);
+	// the canonical string is parsed to the same tree
+	let selector = Vis::parse_selector("ul>li.item:not( #first , [data-id] )")?;
+	assert_eq!(Vis::parse_selector(&selector.to_string())?, selector);
+	for selector in [
+		r#"[a="x'y\"z"]"#,
+		r#"[a='x\\y\'']"#,
+		r#"[a=x\y]"#,
+		"DIV P:not(SPAN):is(svg|Rect, math|mi)",
+		"li:nth-child(2n+1 of LI.a) ~ [title^='a\\\'b' i]",
+	] {
+		let parsed = Vis::parse_selector(selector)?;
+		let canonical = parsed.to_string();
+		assert_eq!(Vis::parse_selector(&canonical)?, parsed, "{}", selector);
+		assert_eq!(Vis::parse_selector(&canonical)?.to_string(), canonical);
+	}
+	assert_eq!(Vis::parse_selector("div")?, Vis::parse_selector("DIV")?);
+	// the values are matched unescaped
+	let root = Vis::load(r#"<p title='x"z'>a</p><p title="a\b">b</p>"#)?;
+	assert_eq!(root.find(r#"[title="x\"z"]"#).text(), "a");
+	assert_eq!(root.find(r#"[title='x"z']"#).text(), "a");
+	assert_eq!(root.find(r#"[title="a\\b"]"#).text(), "b");
+	// the tree
+	let compounds = &selector.selectors[0].compounds;
+	assert_eq!(compounds.len(), 2);
+	assert_eq!(
+		compounds[1].1.simples[0],
+		SimpleSelector::Type {
+			namespace: None,
+			name: String::from("li")
+		}
+	);
+	assert_eq!(
+		compounds[1].1.simples[1],
+		SimpleSelector::Class(String::from("item"))
+	);
+	match &compounds[1].1.simples[2] {
+		SimpleSelector::PseudoClass {
+			name,
+			argument: Some(PseudoArgument::Selectors(list)),
+		} => {
+			assert_eq!(name, "not");
+			assert_eq!(list.selectors.len(), 2);
+		}
+		_ => unreachable!(),
+	}
+	assert!(Vis::parse_selector("ul:not(li").is_err());
+	Ok(())
+}
+
+#[test]
+fn test_selector_specificity() -> Result {
+	fn specificity(selector: &str) -> Specificity {
+		Vis::parse_selector(selector).unwrap().specificity()
+	}
+	assert_eq!(specificity("*"), Specificity(0, 0, 0));
+	assert_eq!(specificity("li"), Specificity(0, 0, 1));
+	assert_eq!(specificity("ul li"), Specificity(0, 0, 2));
+	assert_eq!(specificity("ul ol+li"), Specificity(0, 0, 3));
+	assert_eq!(specificity("h1 + *[rel=up]"), Specificity(0, 1, 1));
+	assert_eq!(specificity("ul ol li.red"), Specificity(0, 1, 3));
+	assert_eq!(specificity("li.red.level"), Specificity(0, 2, 1));
+	assert_eq!(specificity("#x34y"), Specificity(1, 0, 0));
+	assert_eq!(specificity("#s12:not(FOO)"), Specificity(1, 0, 1));
+	assert_eq!(specificity(".foo :is(.bar, #baz)"), Specificity(1, 1, 0));
+	assert_eq!(specificity(":where(#a, .b) p"), Specificity(0, 0, 1));
+	assert_eq!(
+		specificity("li:nth-child(2n of .a, #b)"),
+		Specificity(1, 1, 1)
+	);
+	assert_eq!(specificity("li:nth-child(2n)"), Specificity(0, 1, 1));
+	assert_eq!(specificity("div:has(> p.a)"), Specificity(0, 1, 2));
+	assert_eq!(specificity("svg|rect:first"), Specificity(0, 1, 1));
+	// the max specificity of the list
+	assert_eq!(specificity("a, #b, .c"), Specificity(1, 0, 0));
+	assert!(Specificity(0, 2, 0) > Specificity(0, 1, 9));
+	Ok(())
+}
+
+#[test]
+fn test_selector_index() -> Result {
+	let html = r##"
+  <div class="list">
+    <ul>
+      <li class="item a a" data-id="1">1</li>
+      <li class="item" DATA-ID="2">2</li>
+      <li>3</li>
+    </ul>
+    <svg><linearGradient id="g"></linearGradient></svg>
+  </div>
+  "##;
+	let root = Vis::load(html)?;
+	// the results from the root use the indexes, the same as the results from other elements
+	let list = root.find(".list");
+	for selector in [
+		"li",
+		"LI",
+		".item",
+		".a",
+		"[data-id]",
+		"[data-id='2']",
+		"li[data-id!='2']",
+		"[data-id=~/\\d/]",
+		"li.item[data-id]",
+		"ul > li:nth-child(2n)",
+		"lineargradient",
+	] {
+		assert_eq!(
+			root.find(selector).text(),
+			list.find(selector).text(),
+			"{}",
+			selector
+		);
+	}
+	assert_eq!(root.find("li").length(), 3);
+	assert_eq!(root.find(".a").length(), 1);
+	assert_eq!(root.find("[data-id]").length(), 2);
+	// `!=` matches the elements without the attribute too
+	assert_eq!(root.find("[data-id!='2']").length(), 6);
+	assert_eq!(root.find("li[data-id!='2']").text(), "13");
+	// the indexes are updated after the changes
+	let lis = root.find("li");
+	lis.eq(2).set_attr("data-id", Some("3"));
+	assert_eq!(root.find("[data-id]").length(), 3);
+	lis.eq(2).add_class("item");
+	assert_eq!(root.find(".item").text(), "123");
+	lis.eq(0).remove_class("item");
+	assert_eq!(root.find(".item").text(), "23");
+	lis.eq(1).remove_attr("data-id");
+	assert_eq!(root.find("[data-id]").text(), "13");
+	root.find("ul").set_html("<li class='new'>4</li>");
+	assert_eq!(root.find("li").text(), "4");
+	assert_eq!(root.find(".item").length(), 0);
+	assert_eq!(root.find(".new").length(), 1);
+	root.find("ul").set_text("");
+	assert_eq!(root.find("li").length(), 0);
+	Ok(())
+}
+
+#[test]
+fn test_query_planner() -> Result {
+	let html = format!(
+		r##"<section><div class="list">{}<p class="item"><span>last</span><em>a</em> <em>b</em></p></div></section>"##,
+		r##"<p class="item"><b>1</b><em>2</em></p>"##.repeat(30)
+	);
+	let root = Vis::load(&html)?;
+	let section = root.find("section");
+	// the plans from the root are chosen by the statistics, the results must be the same as from other elements
+	for (selector, strategy) in [
+		(".item span", QueryStrategy::BottomUp { start: 1 }),
+		(".list > .item > span", QueryStrategy::BottomUp { start: 2 }),
+		(".item span + em", QueryStrategy::BottomUp { start: 1 }),
+		(".item span ~ em", QueryStrategy::BottomUp { start: 1 }),
+		("div span", QueryStrategy::BottomUp { start: 1 }),
+		("span em", QueryStrategy::TopDown),
+		(".item b", QueryStrategy::TopDown),
+		(".item:last span", QueryStrategy::TopDown),
+		(
+			".item span:contains('last')",
+			QueryStrategy::BottomUp { start: 1 },
+		),
+	] {
+		let plan = &root.explain(selector)?[0];
+		assert_eq!(plan.strategy, strategy, "{}", selector);
+		assert!(plan.cost.is_some());
+		assert_eq!(
+			root.find(selector).outer_html(),
+			section.find(selector).outer_html(),
+			"{}",
+			selector
+		);
+	}
+	assert_eq!(root.find(".item span + em").text(), "a");
+	assert_eq!(root.find(".item span ~ em").text(), "ab");
+	// the statistics are not rebuilt by the queries soon after the document is changed
+	root.find("b").set_html("<span>b</span>");
+	assert!(root.explain(".item span")?[0].estimates.is_none());
+	assert_eq!(root.find(".item span").length(), 31);
+	// but rebuilt after the document is queried many times without changes
+	for _ in 0..16 {
+		assert_eq!(root.find(".item span").length(), 31);
+	}
+	let plan = &root.explain(".item span")?[0];
+	assert_eq!(plan.strategy, QueryStrategy::TopDown);
+	assert!(plan.estimates.is_some());
+	// the sibling combinators are estimated by the count of the siblings, not the depth
+	let html = format!(
+		"{}{}{}",
+		"<div>".repeat(30),
+		r#"<i class="a"></i><b></b>"#.repeat(5),
+		"</div>".repeat(30)
+	);
+	let deep = Vis::load(&html)?;
+	for selector in [".a ~ b", ".a + b"] {
+		let plan = &deep.explain(selector)?[0];
+		assert_eq!(plan.estimates, Some(vec![5, 5]), "{}", selector);
+		assert_eq!(plan.cost, Some(10), "{}", selector);
+		assert_eq!(deep.find(selector).length(), 5, "{}", selector);
+	}
+	// the elements not the root use the fixed priorities
+	let plans = section.explain(".item span, span")?;
+	assert_eq!(plans.len(), 2);
+	assert!(plans.iter().all(|plan| plan.estimates.is_none()));
+	assert!(root.explain(".item span[").is_err());
+	Ok(())
+}
@@ -0,0 +1,143 @@
+#![cfg(feature = "sync")]
+use std::convert::TryFrom;
+use std::result::Result as StdResult;
+use visdom::types::{BoxDynError, IDocumentTrait};
+use visdom::{SyncElements, Vis};
+type Result = StdResult<(), BoxDynError>;
+
+const HTML: &str = r##"
+<!doctype html>
+<html>
+  <head>
+    <title>a &amp; b</title>
+    <style>.a > b{}</style>
+  </head>
+  <body>
+    <!--comment-->
+    <div id="content" class="list">
+      <p>first &lt;p&gt;</p>
+      <ul><li class="item">1</li><li class="item" data-id=2>2</li><li>3</li></ul>
+      <input name="age" value="18" disabled />
+      <select><option value="a">a</option><option value="b" selected>b</option></select>
+      <textarea>&lt;text&gt;</textarea>
+      <svg><rect width="1"/></svg>
+    </div>
+  </body>
+</html>
+"##;
+
+#[test]
+fn test_sync_document_same_as_document() -> Result {
+	let root = Vis::load(HTML)?;
+	let doc = Vis::load_sync(HTML)?;
+	let sync_root = doc.elements();
+	assert_eq!(doc.source_code(), root.document().unwrap().source_code());
+	assert_eq!(
+		sync_root.document().unwrap().title(),
+		root.document().unwrap().title()
+	);
+	for selector in [
+		"html",
+		"head",
+		"style",
+		"#content",
+		"li:nth-child(2)",
+		"[data-id]",
+		"p",
+		"textarea",
+		"svg",
+		"input:disabled",
+		"li:contains('3')",
+	] {
+		let finded = root.find(selector);
+		let sync_finded = sync_root.find(selector);
+		assert_eq!(sync_finded.length(), finded.length(), "{}", selector);
+		assert_eq!(
+			sync_finded.outer_html(),
+			finded.outer_html(),
+			"{}",
+			selector
+		);
+		assert_eq!(sync_finded.html(), finded.html(), "{}", selector);
+		assert_eq!(sync_finded.text(), finded.text(), "{}", selector);
+	}
+	assert_eq!(
+		sync_root.find("select").val().to_string(),
+		root.find("select").val().to_string()
+	);
+	assert_eq!(sync_root.find("input").val().to_string(), "18");
+	assert_eq!(
+		sync_root
+			.find("li.item")
+			.eq(1)
+			.attr("data-id")
+			.unwrap()
+			.to_string(),
+		"2"
+	);
+	assert_eq!(sync_root.find("li").eq(1).prev("").text(), "1");
+	assert_eq!(sync_root.find("li").eq(2).closest(".list").length(), 1);
+	assert_eq!(
+		sync_root
+			.find("li")
+			.get(0)
+			.unwrap()
+			.unique_selector(&Default::default()),
+		root
+			.find("li")
+			.get(0)
+			.unwrap()
+			.unique_selector(&Default::default())
+	);
+	Ok(())
+}
+
+#[test]
+fn test_sync_document_read_only() -> Result {
+	let doc = Vis::load_sync(HTML)?;
+	let mut lis = doc.elements().find("li");
+	lis.set_text("changed");
+	lis.set_attr("class", None);
+	lis.remove_attr("data-id");
+	assert_eq!(lis.text(), "123");
+	assert_eq!(doc.elements().find("li.item").length(), 2);
+	Ok(())
+}
+
+#[test]
+fn test_sync_document_across_threads() -> Result {
+	let doc = Vis::load_sync(HTML)?;
+	let selectors = ["li", "li.item", "#content p", "option[selected]"];
+	let lengths = crossbeam::scope(|scope| {
+		let handles = selectors
+			.iter()
+			.map(|&selector| {
+				let doc = &doc;
+				scope.spawn(move |_| doc.elements().find(selector).length())
+			})
+			.collect::<Vec<_>>();
+		handles
+			.into_iter()
+			.map(|handle| handle.join().unwrap())
+			.collect::<Vec<usize>>()
+	})
+	.unwrap();
+	assert_eq!(lengths, vec![3, 2, 1, 1]);
+	// send the finded nodes through a channel, the document can be dropped
+	let (sender, receiver) = crossbeam::channel::unbounded();
+	let worker = std::thread::spawn(move || {
+		let items = SyncElements::try_from(&doc.elements().find("li.item")).unwrap();
+		sender.send(items).unwrap();
+	});
+	worker.join().unwrap();
+	let items = receiver.recv()?;
+	assert_eq!(items.length(), 2);
+	let items = items.elements();
+	assert_eq!(items.text(), "12");
+	assert_eq!(items.eq(0).parent("").children("").length(), 3);
+	assert_eq!(items.eq(0).closest("#content").length(), 1);
+	// the nodes of the document loaded by `Vis::load` can't be sent
+	let root = Vis::load(HTML)?;
+	assert!(SyncElements::try_from(&root.find("li")).is_err());
+	Ok(())
+}
@@ -0,0 +1,232 @@
+use std::result::Result as StdResult;
+use visdom::types::BoxDynError;
+use visdom::xpath::{XPathNode, XPathValue};
+use visdom::Vis;
+type Result = StdResult<(), BoxDynError>;
+
+const HTML: &str = r##"
+  <html>
+    <head><title>xpath</title></head>
+    <body>
+      <div id="main" lang="en-US">
+        <h1>Title</h1>
+        <!--comment-->
+        <ul class="list">
+          <li class="item" data-price="10">one</li>
+          <li class="item active" data-price="20">two</li>
+          <li class="item" data-price="30">  three  <span>3</span></li>
+        </ul>
+        <p id="intro">intro <a href="/a">link</a></p>
+        <svg xmlns:xlink="http://www.w3.org/1999/xlink"><linearGradient id="grad"></linearGradient><use xlink:href="#grad" /></svg>
+      </div>
+      <div id="footer"></div>
+    </body>
+  </html>
+"##;
+
+#[test]
+fn test_xpath_location_path() -> Result {
+	let root = Vis::load(HTML)?;
+	assert_eq!(root.xpath("//li")?.into_elements().length(), 3);
+	assert_eq!(root.xpath("/html/body/div")?.into_elements().length(), 2);
+	assert_eq!(root.xpath("/")?.into_nodes().len(), 1);
+	assert_eq!(root.xpath("//ul/li[2]")?.into_elements().text(), "two");
+	assert_eq!(root.xpath("//li[last()]/span")?.into_elements().text(), "3");
+	assert_eq!(root.xpath("(//li)[1]")?.into_elements().text(), "one");
+	assert_eq!(
+		root
+			.xpath("//li[@data-price > 15]")?
+			.into_elements()
+			.length(),
+		2
+	);
+	assert_eq!(
+		root
+			.xpath("//li[contains(@class, 'active')]")?
+			.into_elements()
+			.text(),
+		"two"
+	);
+	assert_eq!(root.xpath("//h1 | //p")?.into_elements().length(), 2);
+	// relative to the elements
+	let list = root.find("ul");
+	assert_eq!(list.xpath("li")?.into_elements().length(), 3);
+	assert_eq!(list.xpath("./li/span/..")?.into_elements().length(), 1);
+	assert_eq!(list.xpath("//div")?.into_elements().length(), 2);
+	// merged in document order
+	let items = root.find("li").xpath("parent::*")?.into_elements();
+	assert_eq!(items.length(), 1);
+	assert!(items.is("ul"));
+	Ok(())
+}
+
+#[test]
+fn test_xpath_axes() -> Result {
+	let root = Vis::load(HTML)?;
+	let active = root.find("li.active");
+	assert_eq!(active.xpath("ancestor::*")?.into_elements().length(), 4);
+	assert_eq!(
+		active
+			.xpath("ancestor-or-self::*")?
+			.into_elements()
+			.length(),
+		5
+	);
+	// the proximity position of the reverse axis
+	assert!(active.xpath("ancestor::*[1]")?.into_elements().is("ul"));
+	assert_eq!(
+		active
+			.xpath("following-sibling::li")?
+			.into_elements()
+			.length(),
+		1
+	);
+	assert_eq!(
+		active
+			.xpath("preceding-sibling::*[1]")?
+			.into_elements()
+			.text(),
+		"one"
+	);
+	assert_eq!(
+		active.xpath("following::*")?.into_elements().length(),
+		// li, span, p, a, svg, linearGradient, use, div
+		8
+	);
+	assert!(active.xpath("preceding::*[1]")?.into_elements().is("li"));
+	// head, title, h1, li
+	assert_eq!(active.xpath("preceding::*")?.into_elements().length(), 4);
+	assert_eq!(
+		root.xpath("//ul/descendant::*")?.into_elements().length(),
+		4
+	);
+	assert_eq!(
+		root
+			.xpath("//ul/descendant-or-self::*")?
+			.into_elements()
+			.length(),
+		5
+	);
+	assert!(active.xpath("self::li")?.boolean());
+	assert!(!active.xpath("self::div")?.boolean());
+	assert_eq!(root.xpath("//li/@data-price")?.into_nodes().len(), 3);
+	assert_eq!(root.xpath("//li/attribute::*")?.into_nodes().len(), 6);
+	assert_eq!(
+		root.xpath("//li/@data-price/..")?.into_elements().length(),
+		3
+	);
+	assert_eq!(root.xpath("//ul/text()")?.into_nodes().len(), 4);
+	assert_eq!(root.xpath("//comment()")?.string(), "comment");
+	assert!(root.xpath("//namespace::*")?.into_nodes().is_empty());
+	// attributes
+	match root.xpath("//li[2]/@class")?.into_nodes().remove(0) {
+		XPathNode::Attribute { name, value, owner } => {
+			assert_eq!(name, "class");
+			assert_eq!(value, "item active");
+			assert_eq!(owner.text(), "two");
+		}
+		_ => unreachable!(),
+	}
+	// namespaces
+	assert_eq!(root.xpath("//svg:*")?.into_elements().length(), 3);
+	assert_eq!(
+		root.xpath("//svg:linearGradient")?.into_elements().length(),
+		1
+	);
+	assert_eq!(
+		root.xpath("//svg:lineargradient")?.into_elements().length(),
+		0
+	);
+	assert_eq!(root.xpath("//use/@xlink:href")?.string(), "#grad");
+	assert_eq!(
+		root.xpath("namespace-uri(//use/@xlink:*)")?.string(),
+		"http://www.w3.org/1999/xlink"
+	);
+	Ok(())
+}
+
+#[test]
+fn test_xpath_functions() -> Result {
+	let root = Vis::load(HTML)?;
+	assert_eq!(root.xpath("count(//li)")?.number(), 3.0);
+	assert_eq!(root.xpath("sum(//li/@data-price)")?.number(), 60.0);
+	assert_eq!(root.xpath("string(//li[3])")?.string(), "  three  3");
+	assert_eq!(root.xpath("normalize-space(//li[3])")?.string(), "three 3");
+	assert_eq!(root.xpath("string-length(//h1)")?.number(), 5.0);
+	assert!(root.xpath("starts-with(//h1, 'Ti')")?.boolean());
+	assert_eq!(root.xpath("concat('a', 1, true())")?.string(), "a1true");
+	assert_eq!(root.xpath("substring('12345', 1.5, 2.6)")?.string(), "234");
+	assert_eq!(root.xpath("substring('12345', 0, 3)")?.string(), "12");
+	assert_eq!(root.xpath("substring-before('a-b-c', '-')")?.string(), "a");
+	assert_eq!(root.xpath("substring-after('a-b-c', '-')")?.string(), "b-c");
+	assert_eq!(root.xpath("translate('bar', 'abc', 'AB')")?.string(), "BAr");
+	assert_eq!(
+		root.xpath("local-name(//svg/*[1])")?.string(),
+		"linearGradient"
+	);
+	assert_eq!(root.xpath("name(//li/@data-price)")?.string(), "data-price");
+	assert_eq!(
+		root.xpath("id('intro footer')")?.into_elements().length(),
+		2
+	);
+	assert_eq!(
+		root.xpath("//li[position() < 3]")?.into_elements().length(),
+		2
+	);
+	assert!(root.xpath("boolean(//li[lang('en')])")?.boolean());
+	assert!(!root.xpath("boolean(//li[lang('de')])")?.boolean());
+	assert!(root.xpath("not(//table)")?.boolean());
+	// numbers
+	assert_eq!(
+		root
+			.xpath("floor(2.5) + ceiling(2.5) + round(2.5)")?
+			.number(),
+		8.0
+	);
+	assert_eq!(root.xpath("7 mod 3 * 2 div 4")?.number(), 0.5);
+	assert_eq!(root.xpath("-(1 - 3)")?.string(), "2");
+	assert!(root.xpath("number('abc')")?.number().is_nan());
+	assert_eq!(root.xpath("1 div 0")?.string(), "Infinity");
+	assert_eq!(root.xpath("0.1 + 0.2 > 0.3")?.string(), "true");
+	// comparisons
+	assert!(root.xpath("//li = 'two'")?.boolean());
+	assert!(root.xpath("//li != 'two'")?.boolean());
+	assert!(root.xpath("//li/@data-price = 20")?.boolean());
+	assert!(root.xpath("//h1 = true()")?.boolean());
+	assert!(root.xpath("1 = '1.0'")?.boolean());
+	// strings are compared as numbers
+	assert!(!root.xpath("'a' < 'b'")?.boolean());
+	// the value types
+	assert!(matches!(root.xpath("count(//li)")?, XPathValue::Number(_)));
+	assert!(matches!(root.xpath("1 = 1")?, XPathValue::Boolean(true)));
+	assert!(root.xpath("//li")?.is_node_set());
+	// the empty node-set
+	let sum = root.xpath("sum(//table/@width)")?.number();
+	assert!(sum == 0.0 && sum.is_sign_positive());
+	// without context node
+	let empty = root.find("table");
+	assert!(matches!(empty.xpath("count(//a)")?, XPathValue::Number(n) if n == 0.0));
+	assert_eq!(empty.xpath("concat('a', string(.))")?.string(), "a");
+	assert!(matches!(empty.xpath("1 = 1")?, XPathValue::Boolean(true)));
+	assert!(empty.xpath("//li")?.is_node_set());
+	assert!(empty.xpath("count(1)").is_err());
+	Ok(())
+}
+
+#[test]
+fn test_xpath_error() -> Result {
+	let root = Vis::load(HTML)?;
+	assert!(root.xpath("").is_err());
+	assert!(root.xpath("//li[").is_err());
+	assert!(root.xpath("//li[1]]").is_err());
+	assert!(root.xpath("unknown(1)").is_err());
+	assert!(root.xpath("count()").is_err());
+	assert!(root.xpath("foo:bar").is_err());
+	assert!(root.xpath("child:li").is_err());
+	assert!(root.xpath("unknown::li").is_err());
+	assert!(root.xpath("$var").is_err());
+	assert!(root.xpath("'unclosed").is_err());
+	assert!(root.xpath("count(1)").is_err());
+	assert!(root.xpath("1 | //li").is_err());
+	Ok(())
+}